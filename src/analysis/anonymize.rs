@@ -0,0 +1,138 @@
+use super::CombinedFindings;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// Replaces contributor names and emails with stable pseudonyms such as "Author #3".
+/// Labels are assigned in commit-history order, so they are deterministic within a run.
+pub struct Anonymizer {
+    by_name: HashMap<String, usize>,
+    by_email: HashMap<String, usize>,
+    next_id: usize,
+    email_re: Regex,
+}
+
+impl Anonymizer {
+    pub fn new() -> Self {
+        Self {
+            by_name: HashMap::new(),
+            by_email: HashMap::new(),
+            next_id: 1,
+            email_re: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+        }
+    }
+
+    /// Pseudonymize every author/committer reference in the findings
+    pub fn anonymize(&mut self, findings: &mut CombinedFindings) {
+        let git_stats = &mut findings.git_stats;
+
+        // Register identities first so name and email of one person share a label
+        for commit in &git_stats.commit_history {
+            self.register(&commit.author, &commit.author_email);
+            self.register(&commit.committer, &commit.committer_email);
+            // Reviewers and co-authors named in trailers get labels too
+            for value in commit.trailers.values().flatten() {
+                if let Some((name, email)) = trailer_identity(value) {
+                    self.register(name, email);
+                }
+            }
+        }
+
+        for commit in &mut git_stats.commit_history {
+            commit.author = self.name_label(&commit.author);
+            commit.author_email = self.email_label(&commit.author_email);
+            commit.committer = self.name_label(&commit.committer);
+            commit.committer_email = self.email_label(&commit.committer_email);
+            commit.message = self.scrub_text(&commit.message);
+            self.scrub_trailers(&mut commit.trailers);
+        }
+
+        for history in git_stats.file_history.values_mut() {
+            let authors: HashSet<String> =
+                history.authors.iter().map(|a| self.name_label(a)).collect();
+            history.authors = authors;
+        }
+
+        let author_stats = std::mem::take(&mut git_stats.author_stats);
+        for (_, mut stats) in author_stats {
+            stats.name = self.name_label(&stats.name);
+            stats.email = self.email_label(&stats.email);
+            let key = format!("{}:{}", stats.name, stats.email);
+            git_stats.author_stats.insert(key, stats);
+        }
+
+        for rewrite in &mut git_stats.history_rewrites {
+            rewrite.committer = self.name_label(&rewrite.committer);
+        }
+
+        for change in &mut git_stats.meta_file_changes {
+            change.author = self.name_label(&change.author);
+            change.subject = self.scrub_text(&change.subject);
+        }
+
+        for finding in &mut findings.vulnerabilities {
+            finding.author = self.name_label(&finding.author);
+            finding.commit_message = self.scrub_text(&finding.commit_message);
+            self.scrub_trailers(&mut finding.trailers);
+            for related in &mut finding.related_commits {
+                related.author = self.name_label(&related.author);
+                related.subject = self.scrub_text(&related.subject);
+            }
+            for pattern_match in &mut finding.patterns_matched {
+                pattern_match.context = self.scrub_text(&pattern_match.context);
+            }
+        }
+    }
+
+    fn register(&mut self, name: &str, email: &str) {
+        let email = email.to_lowercase();
+        let id = match (self.by_email.get(&email), self.by_name.get(name)) {
+            (Some(&id), _) | (None, Some(&id)) => id,
+            (None, None) => {
+                let id = self.next_id;
+                self.next_id += 1;
+                id
+            }
+        };
+        self.by_name.entry(name.to_string()).or_insert(id);
+        self.by_email.entry(email).or_insert(id);
+    }
+
+    fn name_label(&self, name: &str) -> String {
+        match self.by_name.get(name) {
+            Some(id) => format!("Author #{}", id),
+            None => "Author #?".to_string(),
+        }
+    }
+
+    fn email_label(&self, email: &str) -> String {
+        match self.by_email.get(&email.to_lowercase()) {
+            Some(id) => format!("author{}@anonymized.invalid", id),
+            None => "redacted@anonymized.invalid".to_string(),
+        }
+    }
+
+    /// Replace email addresses in free text (e.g. Signed-off-by trailers)
+    fn scrub_text(&self, text: &str) -> String {
+        self.email_re
+            .replace_all(text, |caps: &regex::Captures| self.email_label(&caps[0]))
+            .to_string()
+    }
+
+    /// Pseudonymize `Name <email>` trailer values and scrub emails from the others
+    fn scrub_trailers(&self, trailers: &mut HashMap<String, Vec<String>>) {
+        for value in trailers.values_mut().flatten() {
+            *value = match trailer_identity(value) {
+                Some((name, email)) => {
+                    format!("{} <{}>", self.name_label(name), self.email_label(email))
+                }
+                None => self.scrub_text(value),
+            };
+        }
+    }
+}
+
+/// Name and email of a `Name <email>` trailer value such as a `Reviewed-by:` entry
+fn trailer_identity(value: &str) -> Option<(&str, &str)> {
+    let (name, email) = value.split_once(" <")?;
+    Some((name.trim(), email.strip_suffix('>')?))
+}
@@ -1,26 +1,66 @@
 use super::*;
 use super::complexity::ComplexityCalculator;
+use super::complexity_cache::ComplexityCache;
+use super::dependencies::{self, Ecosystem};
+use super::licenses::{self, LicensePolicy};
+use super::lockfiles;
+use crate::config::AnalysisConfig;
+use crate::git::normalize::normalize_path;
+use crate::profile;
 use anyhow::Result;
-use ignore::Walk;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::Path;
+use std::time::Instant;
 use tokei::{Config as TokeiConfig, Languages};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-pub struct CodeAnalyzer;
+pub struct CodeAnalyzer {
+    complexity_extensions: Vec<String>,
+    config: AnalysisConfig,
+    /// Date manifest staleness is measured from, see `OutputConfig::reference_date`
+    reference_date: chrono::DateTime<chrono::Utc>,
+}
 
 impl CodeAnalyzer {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: &AnalysisConfig) -> Self {
+        Self {
+            complexity_extensions: config
+                .complexity_extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                .collect(),
+            config: config.clone(),
+            reference_date: chrono::Utc::now(),
+        }
+    }
+
+    /// Measure staleness from `reference_date` instead of now
+    pub fn with_reference_date(mut self, reference_date: chrono::DateTime<chrono::Utc>) -> Self {
+        self.reference_date = reference_date;
+        self
     }
 
-    pub async fn analyze(&self, repo_path: &Path, stale_days: u64) -> Result<CodeStats> {
+    pub async fn analyze(
+        &self,
+        repo_path: &Path,
+        git_stats: &RepositoryStats,
+        stale_days: u64,
+    ) -> Result<CodeStats> {
         // Use tokei for language analysis
         debug!("Starting tokei language analysis...");
         let mut languages = Languages::new();
         let tokei_config = TokeiConfig::default();
 
-        languages.get_statistics(&[repo_path], &[], &tokei_config);
+        let excluded: Vec<String> = self
+            .config
+            .report_artifact_names
+            .iter()
+            .flat_map(|base| [format!("{}*.html", base), format!("{}*.json", base)])
+            // Lock files and vendored code would inflate the line counts of the project itself
+            .chain(self.config.line_count_exclusions.iter().cloned())
+            .collect();
+        let excluded: Vec<&str> = excluded.iter().map(|s| s.as_str()).collect();
+        languages.get_statistics(&[repo_path], &excluded, &tokei_config);
         debug!("Tokei analysis complete");
 
         let language_breakdown = self.extract_language_stats(&languages);
@@ -33,9 +73,16 @@ impl CodeAnalyzer {
         debug!("File complexity analysis complete");
 
         // Analyze dependencies
-        let dependency_analysis = self.analyze_dependencies(repo_path).await?;
+        let mut dependency_analysis = self.analyze_dependencies(repo_path).await?;
+        dependency_analysis.stale_manifests =
+            self.stale_manifests(repo_path, git_stats, stale_days);
         let risk_factors = self
-            .calculate_risk_factors(repo_path, &file_complexity, stale_days)
+            .calculate_risk_factors(
+                repo_path,
+                &file_complexity,
+                &dependency_analysis.stale_manifests,
+                stale_days,
+            )
             .await?;
 
         info!(
@@ -81,23 +128,24 @@ impl CodeAnalyzer {
         &self,
         repo_path: &Path,
     ) -> Result<HashMap<String, ComplexityMetrics>> {
+        let started = Instant::now();
         let mut complexity_map = HashMap::new();
 
         // First pass: collect all files to analyze
         debug!("Collecting files for complexity analysis...");
         let mut files_to_analyze = Vec::new();
 
-        for entry in Walk::new(repo_path) {
+        for entry in self.config.file_walker(repo_path).build() {
             let entry = entry?;
             if entry.file_type().map_or(false, |ft| ft.is_file()) {
                 let path = entry.path();
                 if let Some(extension) = path.extension() {
                     if self.should_analyze_file(extension.to_string_lossy().as_ref()) {
-                        let relative_path = path
-                            .strip_prefix(repo_path)
-                            .unwrap_or(path)
-                            .display()
-                            .to_string();
+                        let relative_path =
+                            normalize_path(path.strip_prefix(repo_path).unwrap_or(path));
+                        if self.config.is_report_artifact(&relative_path) {
+                            continue;
+                        }
                         files_to_analyze.push((path.to_path_buf(), relative_path));
                     }
                 }
@@ -123,9 +171,28 @@ impl CodeAnalyzer {
             .progress_chars("#>-")
         );
 
+        let mut cache = self.config.cache_dir.as_deref().map(ComplexityCache::load);
+
         // Second pass: analyze files with progress bar
         for (path, relative_path) in files_to_analyze {
-            let metrics = self.calculate_simple_complexity(&path).await?;
+            let metrics = match &mut cache {
+                Some(cache) => {
+                    let extension = path
+                        .extension()
+                        .map(|e| e.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let key = ComplexityCache::key(&tokio::fs::read(&path).await?, &extension);
+                    match cache.get(&key) {
+                        Some(metrics) => metrics,
+                        None => {
+                            let metrics = self.calculate_simple_complexity(&path).await?;
+                            cache.insert(key, metrics.clone());
+                            metrics
+                        }
+                    }
+                }
+                None => self.calculate_simple_complexity(&path).await?,
+            };
             complexity_map.insert(relative_path, metrics);
 
             pb.inc(1);
@@ -135,11 +202,25 @@ impl CodeAnalyzer {
         }
 
         pb.finish_with_message("File complexity analysis complete");
+        if let Some(cache) = cache {
+            info!(
+                "Reused cached complexity for {} of {} files",
+                cache.hits(),
+                complexity_map.len()
+            );
+            cache.save();
+        }
+        profile::record(
+            "Complexity",
+            "files",
+            started.elapsed(),
+            complexity_map.len(),
+        );
         Ok(complexity_map)
     }
 
     async fn calculate_simple_complexity(&self, file_path: &Path) -> Result<ComplexityMetrics> {
-        let calculator = ComplexityCalculator::new();
+        let calculator = ComplexityCalculator::new(self.config.python_tab_width);
         // Skip binary files
         if self.is_binary_file(file_path).await? {
             return Ok(ComplexityMetrics {
@@ -174,58 +255,29 @@ impl CodeAnalyzer {
 
     async fn is_binary_file(&self, file_path: &Path) -> Result<bool> {
         // Check file extension first
-        if let Some(extension) = file_path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                let binary_extensions = [
-                    "exe", "dll", "so", "dylib", "bin", "o", "obj", "lib", "a", "zip", "tar", "gz",
-                    "bz2", "xz", "7z", "rar", "jpg", "jpeg", "png", "gif", "bmp", "ico", "tiff",
-                    "mp3", "mp4", "avi", "mov", "wav", "pdf", "doc", "docx",
-                ];
-                if binary_extensions.contains(&ext_str.to_lowercase().as_str()) {
-                    return Ok(true);
-                }
-            }
+        if is_binary_path(file_path) {
+            return Ok(true);
         }
 
         // Read first few bytes to check for null bytes (binary indicator)
         match tokio::fs::read(file_path).await {
-            Ok(bytes) => {
-                if bytes.len() > 0 {
-                    // Check first 1024 bytes for null bytes
-                    let check_len = std::cmp::min(1024, bytes.len());
-                    let contains_null = bytes[..check_len].iter().any(|&b| b == 0);
-                    Ok(contains_null)
-                } else {
-                    Ok(false) // Empty files are not binary
-                }
-            }
+            Ok(bytes) => Ok(is_binary_content(&bytes)),
             Err(_) => Ok(false), // If we can't read it, assume it's not binary
         }
     }
 
     fn should_analyze_file(&self, extension: &str) -> bool {
-        matches!(
-            extension,
-            "rs" | "py"
-                | "js"
-                | "ts"
-                | "java"
-                | "cpp"
-                | "c"
-                | "h"
-                | "hpp"
-                | "go"
-                | "rb"
-                | "php"
-                | "cs"
-        )
+        let extension = extension.to_lowercase();
+        self.complexity_extensions.contains(&extension)
     }
 
     async fn analyze_dependencies(&self, repo_path: &Path) -> Result<DependencyAnalysis> {
         let mut total_dependencies = 0;
         let outdated_dependencies = Vec::new();
         let vulnerable_dependencies = Vec::new();
-        let license_issues = Vec::new();
+        let mut license_issues = Vec::new();
+        let mut suspicious_dependencies = Vec::new();
+        let license_policy = self.license_policy(repo_path).await;
 
         // Check for different dependency files
         let dependency_files = [
@@ -241,64 +293,223 @@ impl CodeAnalyzer {
         for dep_file in dependency_files {
             let dep_path = repo_path.join(dep_file);
             if dep_path.exists() {
-                match dep_file {
-                    "Cargo.toml" => {
-                        if let Ok(analysis) = self.analyze_cargo_dependencies(&dep_path).await {
-                            total_dependencies += analysis.0;
-                            // In a real implementation, you'd check crates.io API
-                        }
-                    }
-                    "package.json" => {
-                        if let Ok(analysis) = self.analyze_npm_dependencies(&dep_path).await {
-                            total_dependencies += analysis.0;
-                            // In a real implementation, you'd check npm API
-                        }
-                    }
+                let parsed = match dep_file {
+                    "Cargo.toml" => self
+                        .analyze_cargo_dependencies(&dep_path)
+                        .await
+                        .map(|deps| (Ecosystem::Cargo, deps)),
+                    "package.json" => self
+                        .analyze_npm_dependencies(&dep_path)
+                        .await
+                        .map(|deps| (Ecosystem::Npm, deps)),
+                    "requirements.txt" => self
+                        .analyze_requirements_dependencies(&dep_path)
+                        .await
+                        .map(|deps| (Ecosystem::PyPI, deps)),
                     _ => {
                         // Handle other dependency types
                         debug!("Found dependency file: {}", dep_file);
+                        continue;
+                    }
+                };
+                let (ecosystem, deps) = match parsed {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        warn!("Failed to parse {}: {}", dep_path.display(), e);
+                        continue;
+                    }
+                };
+
+                total_dependencies += deps.len();
+                // In a real implementation, you'd also check the registry's advisory API
+                suspicious_dependencies.extend(deps.iter().filter_map(|(name, version)| {
+                    dependencies::check_dependency(
+                        name,
+                        version,
+                        dep_file,
+                        ecosystem,
+                        &self.config.dependency_allowlist,
+                    )
+                }));
+
+                if let Some(policy) = &license_policy {
+                    for (name, _) in &deps {
+                        if let Some(license) =
+                            self.installed_license(repo_path, ecosystem, name).await
+                        {
+                            license_issues.extend(policy.check(name, &license));
+                        }
                     }
                 }
             }
         }
 
+        let dependency_trees = self.dependency_trees(repo_path).await;
+
+        if !license_issues.is_empty() {
+            info!("Found {} dependency license issue(s)", license_issues.len());
+        }
+
+        if !suspicious_dependencies.is_empty() {
+            info!(
+                "Found {} suspicious dependency name(s)",
+                suspicious_dependencies.len()
+            );
+        }
+
         Ok(DependencyAnalysis {
             total_dependencies,
             outdated_dependencies,
             vulnerable_dependencies,
             license_issues,
+            suspicious_dependencies,
+            direct_dependencies: dependency_trees.iter().map(|t| t.direct).sum(),
+            transitive_dependencies: dependency_trees.iter().map(|t| t.transitive).sum(),
+            dependency_tree_depth: dependency_trees.iter().map(|t| t.depth).max().unwrap_or(0),
+            dependency_trees,
+            stale_manifests: Vec::new(),
         })
     }
 
-    async fn analyze_cargo_dependencies(&self, cargo_toml: &Path) -> Result<(usize, Vec<String>)> {
-        let content = tokio::fs::read_to_string(cargo_toml).await?;
+    /// Dependency trees of the lockfiles at the repository root
+    async fn dependency_trees(&self, repo_path: &Path) -> Vec<DependencyTree> {
+        let mut trees = Vec::new();
+        for lockfile in ["Cargo.lock", "package-lock.json", "npm-shrinkwrap.json"] {
+            let Ok(content) = tokio::fs::read_to_string(repo_path.join(lockfile)).await else {
+                continue;
+            };
+            let tree = match lockfile {
+                "Cargo.lock" => lockfiles::cargo_lock_tree(&content),
+                _ => match lockfiles::npm_lock_tree(&content, lockfile) {
+                    Ok(None) => {
+                        debug!(
+                            "Skipping {}: lockfile version 1 has no package tree",
+                            lockfile
+                        );
+                        None
+                    }
+                    Ok(tree) => tree,
+                    Err(e) => {
+                        warn!("Failed to parse {}: {}", lockfile, e);
+                        None
+                    }
+                },
+            };
+            if let Some(tree) = tree {
+                info!(
+                    "{}: {} direct and {} transitive dependencies, {} levels deep",
+                    tree.lockfile, tree.direct, tree.transitive, tree.depth
+                );
+                trees.push(tree);
+            }
+        }
+        trees
+    }
 
-        // Simple parsing - in practice, you'd use proper TOML parsing
-        let dependency_count = content
-            .lines()
-            .filter(|line| line.contains("=") && !line.starts_with('#'))
-            .count();
+    /// The configured project license, else the one declared in Cargo.toml or package.json
+    async fn license_policy(&self, repo_path: &Path) -> Option<LicensePolicy> {
+        let declared = match &self.config.project_license {
+            Some(license) => Some(license.clone()),
+            None => {
+                let cargo = tokio::fs::read_to_string(repo_path.join("Cargo.toml")).await;
+                let npm = tokio::fs::read_to_string(repo_path.join("package.json")).await;
+                cargo
+                    .ok()
+                    .and_then(|content| licenses::cargo_package_license(&content))
+                    .or_else(|| {
+                        npm.ok()
+                            .and_then(|content| licenses::npm_package_license(&content))
+                    })
+            }
+        };
+        let Some(declared) = declared else {
+            debug!("No project license declared, skipping license conflict checks");
+            return None;
+        };
+        match LicensePolicy::parse(&declared) {
+            Ok(policy) => Some(policy),
+            Err(e) => {
+                warn!(
+                    "Skipping license conflict checks, project license has an {}",
+                    e
+                );
+                None
+            }
+        }
+    }
 
-        Ok((dependency_count, Vec::new()))
+    /// License of a dependency as installed in the working tree: vendored crates
+    /// (`cargo vendor`) and `node_modules` carry their own manifests
+    async fn installed_license(
+        &self,
+        repo_path: &Path,
+        ecosystem: Ecosystem,
+        name: &str,
+    ) -> Option<String> {
+        match ecosystem {
+            Ecosystem::Cargo => {
+                let manifest = repo_path.join("vendor").join(name).join("Cargo.toml");
+                let content = tokio::fs::read_to_string(manifest).await.ok()?;
+                licenses::cargo_package_license(&content)
+            }
+            Ecosystem::Npm => {
+                let manifest = repo_path
+                    .join("node_modules")
+                    .join(name)
+                    .join("package.json");
+                let content = tokio::fs::read_to_string(manifest).await.ok()?;
+                licenses::npm_package_license(&content)
+            }
+            Ecosystem::PyPI => None,
+        }
     }
 
-    async fn analyze_npm_dependencies(&self, package_json: &Path) -> Result<(usize, Vec<String>)> {
-        let content = tokio::fs::read_to_string(package_json).await?;
+    async fn analyze_cargo_dependencies(&self, cargo_toml: &Path) -> Result<Vec<(String, String)>> {
+        let content = tokio::fs::read_to_string(cargo_toml).await?;
+        Ok(dependencies::cargo_dependencies(&content))
+    }
 
-        // In practice, you'd use proper JSON parsing
-        let dependency_count = content.matches("\":").count() / 2; // Rough approximation
+    async fn analyze_npm_dependencies(&self, package_json: &Path) -> Result<Vec<(String, String)>> {
+        let content = tokio::fs::read_to_string(package_json).await?;
+        Ok(dependencies::npm_dependencies(&content)?)
+    }
 
-        Ok((dependency_count, Vec::new()))
+    async fn analyze_requirements_dependencies(
+        &self,
+        requirements_txt: &Path,
+    ) -> Result<Vec<(String, String)>> {
+        let content = tokio::fs::read_to_string(requirements_txt).await?;
+        Ok(dependencies::requirements_dependencies(&content))
     }
 
     async fn calculate_risk_factors(
         &self,
         _repo_path: &Path,
         file_complexity: &HashMap<String, ComplexityMetrics>,
-        _stale_days: u64,
+        stale_manifests: &[StaleManifest],
+        stale_days: u64,
     ) -> Result<Vec<RiskFactor>> {
         let mut risk_factors = Vec::new();
 
+        for manifest in stale_manifests {
+            risk_factors.push(RiskFactor {
+                factor_type: RiskType::OutdatedDependencies,
+                // Twice the stale window means the dependency set missed several release cycles
+                severity: if manifest.days_since_update > 2 * stale_days as i64 {
+                    RiskSeverity::High
+                } else {
+                    RiskSeverity::Medium
+                },
+                description: format!(
+                    "Dependencies in {} not updated for {} days while the project is active",
+                    manifest.path, manifest.days_since_update
+                ),
+                affected_files: vec![manifest.path.clone()],
+                recommendation: "Update the dependency set and consider automated update PRs"
+                    .to_string(),
+            });
+        }
+
         // High complexity files
         for (file, metrics) in file_complexity {
             if metrics.cyclomatic_complexity > 15.0 {
@@ -335,4 +546,95 @@ impl CodeAnalyzer {
 
         Ok(risk_factors)
     }
-}
\ No newline at end of file
+
+    /// Dependency manifests of the working tree whose last update, by their own commits or
+    /// their lockfile's, is older than `stale_days` before the reference date while the
+    /// history has commits within it.
+    /// A lockfile refresh counts as an update, since it moves dependencies within the manifest's
+    /// version ranges.
+    fn stale_manifests(
+        &self,
+        repo_path: &Path,
+        git_stats: &RepositoryStats,
+        stale_days: u64,
+    ) -> Vec<StaleManifest> {
+        let cutoff = self.reference_date - chrono::Duration::days(stale_days as i64);
+        if git_stats.total_commits == 0 || git_stats.last_commit < cutoff {
+            return Vec::new();
+        }
+
+        let last_commit = |path: &str| git_stats.file_history.get(path).map(|h| h.last_commit);
+        let mut stale: Vec<StaleManifest> = git_stats
+            .file_history
+            .iter()
+            .filter_map(|(path, history)| {
+                let (directory, file_name) = match path.rsplit_once('/') {
+                    Some((directory, file_name)) => (Some(directory), file_name),
+                    None => (None, path.as_str()),
+                };
+                let lockfiles = MANIFEST_LOCKFILES
+                    .iter()
+                    .find(|(manifest, _)| *manifest == file_name)?
+                    .1;
+                // Removed manifests no longer describe the project's dependencies
+                if !repo_path.join(path).is_file() {
+                    return None;
+                }
+                let last_update = lockfiles
+                    .iter()
+                    .map(|lockfile| match directory {
+                        Some(directory) => format!("{}/{}", directory, lockfile),
+                        None => lockfile.to_string(),
+                    })
+                    .filter_map(|lockfile| last_commit(&lockfile))
+                    .fold(history.last_commit, |latest, date| latest.max(date));
+                (last_update < cutoff).then(|| StaleManifest {
+                    path: path.clone(),
+                    last_update,
+                    days_since_update: (self.reference_date - last_update).num_days(),
+                })
+            })
+            .collect();
+        stale.sort_by(|a, b| a.last_update.cmp(&b.last_update).then(a.path.cmp(&b.path)));
+        stale
+    }
+}
+
+/// Dependency manifests and the lockfiles that pin their resolved versions
+const MANIFEST_LOCKFILES: &[(&str, &[&str])] = &[
+    ("Cargo.toml", &["Cargo.lock"]),
+    (
+        "package.json",
+        &[
+            "package-lock.json",
+            "npm-shrinkwrap.json",
+            "yarn.lock",
+            "pnpm-lock.yaml",
+        ],
+    ),
+    ("requirements.txt", &[]),
+    ("pyproject.toml", &["poetry.lock", "uv.lock", "pdm.lock"]),
+    ("Pipfile", &["Pipfile.lock"]),
+    ("pom.xml", &[]),
+    ("build.gradle", &["gradle.lockfile"]),
+    ("go.mod", &["go.sum"]),
+    ("Gemfile", &["Gemfile.lock"]),
+    ("composer.json", &["composer.lock"]),
+];
+
+/// Whether the extension names a binary format (executables, libraries, archives, media)
+pub fn is_binary_path(path: &Path) -> bool {
+    let binary_extensions = [
+        "exe", "dll", "so", "dylib", "bin", "o", "obj", "lib", "a", "zip", "tar", "gz", "bz2",
+        "xz", "7z", "rar", "jpg", "jpeg", "png", "gif", "bmp", "ico", "tiff", "mp3", "mp4", "avi",
+        "mov", "wav", "pdf", "doc", "docx",
+    ];
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| binary_extensions.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Whether the first 1024 bytes contain a NUL byte; empty content is not binary
+pub fn is_binary_content(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(1024)].contains(&0)
+}
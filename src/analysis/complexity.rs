@@ -3,16 +3,25 @@ use anyhow::Result;
 
 use super::ComplexityMetrics;
 
-pub struct ComplexityCalculator;
+pub struct ComplexityCalculator {
+    /// Columns a leading tab advances to in Python indentation
+    python_tab_width: usize,
+}
 
 impl ComplexityCalculator {
-    pub fn new() -> Self {
-        Self
+    pub fn new(python_tab_width: usize) -> Self {
+        Self {
+            python_tab_width: python_tab_width.max(1),
+        }
     }
 
     pub fn calculate_complexity_metrics(&self, lines: &[&str], file_path: &Path) -> Result<ComplexityMetrics> {
         let function_count = self.calculate_function_count(lines, file_path);
-        let max_nesting = self.calculate_max_nesting(lines);
+        let max_nesting = if Self::is_python(file_path) {
+            self.python_nesting_levels(lines).into_iter().max().unwrap_or(0)
+        } else {
+            self.calculate_max_nesting(lines)
+        };
         let cyclomatic_complexity = self.calculate_cyclomatic_complexity(lines, file_path)?;
         let cognitive_complexity = self.calculate_cognitive_complexity(lines, file_path)?;
 
@@ -368,7 +377,14 @@ impl ComplexityCalculator {
         let mut nesting_level = 0;
         let mut in_switch = false;
 
-        for line in lines {
+        // Python nests by indentation, so its levels come from a separate pass
+        let python_levels = if Self::is_python(file_path) {
+            Some(self.python_nesting_levels(lines))
+        } else {
+            None
+        };
+
+        for (index, line) in lines.iter().enumerate() {
             let line = line.trim();
 
             // Skip comments and empty lines
@@ -377,9 +393,14 @@ impl ComplexityCalculator {
                 continue;
             }
 
+            if let Some(levels) = &python_levels {
+                let increment = self.calculate_cognitive_increment(line, extension, levels[index] as i32, &mut in_switch);
+                cognitive_complexity += increment;
+                continue;
+            }
+
             // Track nesting level changes
-            let _old_nesting = nesting_level;
-            nesting_level += self.calculate_nesting_increment(line, extension);
+            nesting_level += self.calculate_nesting_increment(line);
             nesting_level = nesting_level.max(0);
 
             // Calculate cognitive complexity increment based on constructs
@@ -387,7 +408,7 @@ impl ComplexityCalculator {
             cognitive_complexity += increment;
 
             // Special handling for closing braces that reduce nesting
-            if line.contains("}") || (extension == "py" && !line.starts_with(' ')) {
+            if line.contains("}") {
                 nesting_level = (nesting_level - 1).max(0);
                 if line.contains("}") && in_switch {
                     in_switch = false;
@@ -398,30 +419,85 @@ impl ComplexityCalculator {
         Ok(cognitive_complexity)
     }
 
-    fn calculate_nesting_increment(&self, line: &str, extension: &str) -> i32 {
+    fn calculate_nesting_increment(&self, line: &str) -> i32 {
+        // Brace-based languages
         let mut increment = 0;
+        if line.contains("{") { increment += 1; }
+        if line.contains("}") { increment -= 1; }
+        increment
+    }
 
-        match extension {
-            "py" => {
-                // Python uses indentation
-                let _indent_level = (line.len() - line.trim_start().len()) / 4;
-                // This is simplified - proper implementation would track indentation changes
-                if line.trim_start().starts_with("if ") ||
-                   line.trim_start().starts_with("for ") ||
-                   line.trim_start().starts_with("while ") ||
-                   line.trim_start().starts_with("try:") ||
-                   line.trim_start().starts_with("with ") {
-                    increment += 1;
+    fn is_python(file_path: &Path) -> bool {
+        matches!(file_path.extension().and_then(|e| e.to_str()), Some("py" | "pyw"))
+    }
+
+    /// Number of enclosing blocks for each line of a Python file. A block opens at a line
+    /// ending in `:` and closes at the first later line indented no deeper than its header.
+    /// Continuation lines inside brackets and docstrings never open or close blocks.
+    fn python_nesting_levels(&self, lines: &[&str]) -> Vec<usize> {
+        let mut levels = Vec::with_capacity(lines.len());
+        // Indentation of each open block's header line
+        let mut blocks: Vec<usize> = Vec::new();
+        // Indentation of the logical line a continuation line belongs to
+        let mut statement_indent = 0;
+        let mut bracket_depth: i32 = 0;
+        let mut in_docstring = false;
+
+        for line in lines {
+            let trimmed = line.trim();
+            let continuation = bracket_depth > 0 || in_docstring;
+
+            if trimmed.is_empty() || (!continuation && trimmed.starts_with('#')) {
+                levels.push(blocks.len());
+                continue;
+            }
+
+            if !continuation {
+                statement_indent = self.python_indent_width(line);
+                // Dedenting may land between two open blocks when tabs and spaces are mixed,
+                // so close every block at least as deep rather than expecting an exact match
+                while blocks.last().is_some_and(|&indent| indent >= statement_indent) {
+                    blocks.pop();
+                }
+            }
+            levels.push(blocks.len());
+
+            let quotes = trimmed.matches("\"\"\"").count() + trimmed.matches("'''").count();
+            if quotes % 2 == 1 {
+                in_docstring = !in_docstring;
+                continue;
+            }
+            if in_docstring {
+                continue;
+            }
+
+            let code = trimmed.split(" #").next().unwrap_or(trimmed).trim_end();
+            for c in code.chars() {
+                match c {
+                    '(' | '[' | '{' => bracket_depth += 1,
+                    ')' | ']' | '}' => bracket_depth = (bracket_depth - 1).max(0),
+                    _ => {}
                 }
             }
-            _ => {
-                // Brace-based languages
-                if line.contains("{") { increment += 1; }
-                if line.contains("}") { increment -= 1; }
+            if bracket_depth == 0 && code.ends_with(':') {
+                blocks.push(statement_indent);
             }
         }
 
-        increment
+        levels
+    }
+
+    /// Width of a line's leading whitespace, with tabs advancing to the next tab stop
+    fn python_indent_width(&self, line: &str) -> usize {
+        let mut width = 0;
+        for c in line.chars() {
+            match c {
+                ' ' => width += 1,
+                '\t' => width += self.python_tab_width - width % self.python_tab_width,
+                _ => break,
+            }
+        }
+        width
     }
 
     fn calculate_cognitive_increment(&self, line: &str, extension: &str, nesting_level: i32, in_switch: &mut bool) -> f64 {
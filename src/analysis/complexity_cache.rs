@@ -0,0 +1,104 @@
+use super::ComplexityMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Bump when complexity calculation changes so stale metrics are not reused
+const CACHE_VERSION: u32 = 2;
+
+const CACHE_FILE: &str = "complexity.json";
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    entries: HashMap<String, ComplexityMetrics>,
+}
+
+/// Complexity metrics keyed by file content, so unchanged and renamed files are not re-analyzed.
+/// Only entries looked up during a run are written back, which drops deleted content.
+pub struct ComplexityCache {
+    path: PathBuf,
+    previous: HashMap<String, ComplexityMetrics>,
+    current: HashMap<String, ComplexityMetrics>,
+}
+
+impl ComplexityCache {
+    /// Load the cache from `cache_dir`, starting empty when it is missing, outdated or unreadable
+    pub fn load(cache_dir: &Path) -> Self {
+        let path = cache_dir.join(CACHE_FILE);
+        let previous = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<CacheFile>(&content) {
+                Ok(cache) if cache.version == CACHE_VERSION => cache.entries,
+                Ok(_) => {
+                    debug!("Ignoring complexity cache from an older version");
+                    HashMap::new()
+                }
+                Err(e) => {
+                    warn!(
+                        "Ignoring unreadable complexity cache {}: {}",
+                        path.display(),
+                        e
+                    );
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path,
+            previous,
+            current: HashMap::new(),
+        }
+    }
+
+    /// Cache key for a file: its git blob hash plus extension, since metrics are language-aware
+    pub fn key(content: &[u8], extension: &str) -> String {
+        let blob = git2::Oid::hash_object(git2::ObjectType::Blob, content)
+            .map(|oid| oid.to_string())
+            .unwrap_or_default();
+        format!("{}.{}", blob, extension.to_lowercase())
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<ComplexityMetrics> {
+        let metrics = self.previous.get(key)?.clone();
+        self.current.insert(key.to_string(), metrics.clone());
+        Some(metrics)
+    }
+
+    pub fn insert(&mut self, key: String, metrics: ComplexityMetrics) {
+        self.current.insert(key, metrics);
+    }
+
+    /// Number of lookups answered from the previous run
+    pub fn hits(&self) -> usize {
+        self.current
+            .keys()
+            .filter(|key| self.previous.contains_key(*key))
+            .count()
+    }
+
+    /// Write this run's entries back; failures only cost the next run its speedup
+    pub fn save(self) {
+        let cache = CacheFile {
+            version: CACHE_VERSION,
+            entries: self.current,
+        };
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let content = serde_json::to_string(&cache)?;
+                std::fs::write(&self.path, content)
+            });
+        if let Err(e) = result {
+            warn!(
+                "Failed to write complexity cache {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::patterns::VulnerabilityFinding;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// MITRE CWE Top 25 Most Dangerous Software Weaknesses (2024), in rank order
+pub const CWE_TOP_25: [(&str, &str); 25] = [
+    ("CWE-79", "Cross-site Scripting"),
+    ("CWE-787", "Out-of-bounds Write"),
+    ("CWE-89", "SQL Injection"),
+    ("CWE-352", "Cross-Site Request Forgery"),
+    ("CWE-22", "Path Traversal"),
+    ("CWE-125", "Out-of-bounds Read"),
+    ("CWE-78", "OS Command Injection"),
+    ("CWE-416", "Use After Free"),
+    ("CWE-862", "Missing Authorization"),
+    ("CWE-434", "Unrestricted Upload of File with Dangerous Type"),
+    ("CWE-94", "Code Injection"),
+    ("CWE-20", "Improper Input Validation"),
+    ("CWE-77", "Command Injection"),
+    ("CWE-287", "Improper Authentication"),
+    ("CWE-269", "Improper Privilege Management"),
+    ("CWE-502", "Deserialization of Untrusted Data"),
+    ("CWE-200", "Exposure of Sensitive Information"),
+    ("CWE-863", "Incorrect Authorization"),
+    ("CWE-918", "Server-Side Request Forgery"),
+    ("CWE-119", "Memory Buffer Bounds Violation"),
+    ("CWE-476", "NULL Pointer Dereference"),
+    ("CWE-798", "Use of Hard-coded Credentials"),
+    ("CWE-190", "Integer Overflow or Wraparound"),
+    ("CWE-400", "Uncontrolled Resource Consumption"),
+    ("CWE-306", "Missing Authentication for Critical Function"),
+];
+
+/// Findings mapped to one Top 25 weakness
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CweCount {
+    pub rank: usize,
+    pub cwe: String,
+    pub name: String,
+    pub findings: usize,
+}
+
+/// Findings tallied against the CWE Top 25; weaknesses outside the list are grouped as other
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CweCoverage {
+    /// Every Top 25 entry in rank order, including those without findings
+    pub top_25: Vec<CweCount>,
+    /// Number of Top 25 weaknesses with at least one finding
+    pub covered: usize,
+    /// Findings per CWE outside the Top 25, most common first
+    pub other: Vec<(String, usize)>,
+    /// Findings whose matches carry no CWE at all
+    pub unmapped_findings: usize,
+}
+
+impl CweCoverage {
+    /// A finding counts once per distinct CWE among its matches
+    pub fn from_findings(findings: &[VulnerabilityFinding]) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut unmapped_findings = 0;
+        for finding in findings {
+            let cwes: HashSet<String> = finding
+                .patterns_matched
+                .iter()
+                .filter_map(|p| p.cwe.as_deref())
+                .map(|cwe| cwe.trim().to_uppercase())
+                .collect();
+            if cwes.is_empty() {
+                unmapped_findings += 1;
+            }
+            for cwe in cwes {
+                *counts.entry(cwe).or_insert(0) += 1;
+            }
+        }
+
+        let top_25: Vec<CweCount> = CWE_TOP_25
+            .iter()
+            .enumerate()
+            .map(|(i, (cwe, name))| CweCount {
+                rank: i + 1,
+                cwe: cwe.to_string(),
+                name: name.to_string(),
+                findings: counts.remove(*cwe).unwrap_or(0),
+            })
+            .collect();
+        let covered = top_25.iter().filter(|c| c.findings > 0).count();
+
+        let mut other: Vec<(String, usize)> = counts.into_iter().collect();
+        other.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            top_25,
+            covered,
+            other,
+            unmapped_findings,
+        }
+    }
+
+    /// Findings mapped to a CWE outside the Top 25
+    pub fn other_findings(&self) -> usize {
+        self.other.iter().map(|(_, count)| count).sum()
+    }
+}
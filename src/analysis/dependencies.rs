@@ -1 +1,341 @@
-// Dependency analysis utilities - placeholder
+use super::SuspiciousDependency;
+
+/// Package registry a manifest resolves its dependencies from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    PyPI,
+}
+
+impl Ecosystem {
+    /// Widely used packages whose names attackers imitate
+    fn popular_packages(self) -> &'static [&'static str] {
+        match self {
+            Ecosystem::Cargo => POPULAR_CRATES,
+            Ecosystem::Npm => POPULAR_NPM_PACKAGES,
+            Ecosystem::PyPI => POPULAR_PYPI_PACKAGES,
+        }
+    }
+
+    /// Canonical form under which the registry treats names as the same package
+    fn normalize(self, name: &str) -> String {
+        let name = name.to_lowercase();
+        match self {
+            // crates.io and PyPI do not distinguish `-`, `_` (and `.` on PyPI)
+            Ecosystem::Cargo | Ecosystem::PyPI => name.replace(['_', '.'], "-"),
+            Ecosystem::Npm => name,
+        }
+    }
+}
+
+const POPULAR_CRATES: &[&str] = &[
+    "serde",
+    "serde_json",
+    "tokio",
+    "rand",
+    "clap",
+    "regex",
+    "anyhow",
+    "thiserror",
+    "log",
+    "tracing",
+    "chrono",
+    "reqwest",
+    "hyper",
+    "futures",
+    "lazy_static",
+    "once_cell",
+    "syn",
+    "quote",
+    "proc-macro2",
+    "itertools",
+    "bytes",
+    "libc",
+    "base64",
+    "uuid",
+    "rayon",
+    "crossbeam",
+    "parking_lot",
+    "env_logger",
+    "time",
+    "url",
+    "openssl",
+    "rustls",
+    "sha2",
+    "hex",
+    "bitflags",
+    "memchr",
+];
+
+const POPULAR_NPM_PACKAGES: &[&str] = &[
+    "react",
+    "react-dom",
+    "lodash",
+    "express",
+    "axios",
+    "chalk",
+    "commander",
+    "moment",
+    "webpack",
+    "typescript",
+    "vue",
+    "jquery",
+    "request",
+    "debug",
+    "dotenv",
+    "uuid",
+    "yargs",
+    "minimist",
+    "underscore",
+    "async",
+    "bluebird",
+    "mongoose",
+    "body-parser",
+    "cross-env",
+    "eslint",
+    "prettier",
+    "jest",
+    "mocha",
+    "classnames",
+    "prop-types",
+    "babel-core",
+    "node-fetch",
+    "socket.io",
+    "colors",
+    "electron",
+    "coffee-script",
+    "event-stream",
+    "ua-parser-js",
+];
+
+const POPULAR_PYPI_PACKAGES: &[&str] = &[
+    "requests",
+    "numpy",
+    "pandas",
+    "django",
+    "flask",
+    "urllib3",
+    "setuptools",
+    "boto3",
+    "botocore",
+    "six",
+    "python-dateutil",
+    "pyyaml",
+    "cryptography",
+    "certifi",
+    "idna",
+    "jinja2",
+    "pytest",
+    "scipy",
+    "matplotlib",
+    "pillow",
+    "sqlalchemy",
+    "beautifulsoup4",
+    "colorama",
+    "click",
+    "tensorflow",
+    "torch",
+    "scikit-learn",
+    "selenium",
+    "paramiko",
+    "pycrypto",
+    "pycryptodome",
+];
+
+/// Names this short are too close to each other for edit distance to mean anything
+const MIN_TYPOSQUAT_NAME_LEN: usize = 5;
+
+/// Name parts that suggest a package was meant to come from a private registry
+const INTERNAL_NAME_MARKERS: &[&str] = &["internal", "private", "corp", "intranet", "inhouse"];
+
+/// `(name, version requirement)` pairs of the `[*dependencies]` tables of a Cargo.toml.
+/// Renamed dependencies report the `package` they resolve to.
+pub fn cargo_dependencies(content: &str) -> Vec<(String, String)> {
+    let mut dependencies: Vec<(String, String)> = Vec::new();
+    // Inside a dependency table, or inside `[dependencies.<name>]` at the given index
+    let mut in_table = false;
+    let mut current: Option<usize> = None;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.starts_with('[') {
+            let header = line.trim_matches(|c| c == '[' || c == ']').trim();
+            let (table, name) = match header.rsplit_once('.') {
+                Some((table, name)) if is_dependency_table(table) => (table, Some(name)),
+                _ => (header, None),
+            };
+            in_table = name.is_none() && is_dependency_table(table);
+            current = name.map(|name| {
+                dependencies.push((unquote(name).to_string(), String::new()));
+                dependencies.len() - 1
+            });
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (unquote(key.trim()), value.trim());
+        if let Some(i) = current {
+            match key {
+                "version" => dependencies[i].1 = unquote(value).to_string(),
+                "package" => dependencies[i].0 = unquote(value).to_string(),
+                _ => {}
+            }
+        } else if in_table {
+            if value.starts_with('{') {
+                let name = inline_value(value, "package").unwrap_or(key);
+                let version = inline_value(value, "version").unwrap_or_default();
+                dependencies.push((name.to_string(), version.to_string()));
+            } else {
+                dependencies.push((key.to_string(), unquote(value).to_string()));
+            }
+        }
+    }
+    dependencies
+}
+
+fn is_dependency_table(table: &str) -> bool {
+    ["dependencies", "dev-dependencies", "build-dependencies"]
+        .iter()
+        .any(|kind| table == *kind || table.ends_with(&format!(".{}", kind)))
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// Value of `key` in a TOML inline table such as `{ version = "1", package = "x" }`
+fn inline_value<'a>(table: &'a str, key: &str) -> Option<&'a str> {
+    table
+        .trim_matches(|c| c == '{' || c == '}')
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| unquote(v))
+}
+
+/// `(name, version range)` pairs of every dependency map in a package.json
+pub fn npm_dependencies(content: &str) -> serde_json::Result<Vec<(String, String)>> {
+    let manifest: serde_json::Value = serde_json::from_str(content)?;
+    Ok([
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ]
+    .iter()
+    .filter_map(|section| manifest.get(section)?.as_object())
+    .flatten()
+    .map(|(name, version)| {
+        (
+            name.clone(),
+            version.as_str().unwrap_or_default().to_string(),
+        )
+    })
+    .collect())
+}
+
+/// `(name, version specifier)` pairs of a requirements.txt, skipping options and includes
+pub fn requirements_dependencies(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .filter_map(|line| {
+            let end = line
+                .find(|c: char| "=<>!~;[ @".contains(c))
+                .unwrap_or(line.len());
+            let name = line[..end].trim();
+            (!name.is_empty()).then(|| (name.to_string(), line[end..].trim().to_string()))
+        })
+        .collect()
+}
+
+/// Flag a dependency whose name is a near miss of a popular package (typosquatting) or
+/// looks like an internal package that the public registry would resolve (dependency
+/// confusion). Names matching an `allowlist` entry, where a trailing `*` matches any
+/// suffix, are never flagged.
+pub fn check_dependency(
+    name: &str,
+    version: &str,
+    manifest: &str,
+    ecosystem: Ecosystem,
+    allowlist: &[String],
+) -> Option<SuspiciousDependency> {
+    let normalized = ecosystem.normalize(name);
+    let allowed = allowlist.iter().any(|entry| {
+        let entry = ecosystem.normalize(entry);
+        match entry.strip_suffix('*') {
+            Some(prefix) => normalized.starts_with(prefix),
+            None => normalized == entry,
+        }
+    });
+    if allowed {
+        return None;
+    }
+
+    let suspicious = |reason: String, resembles: Option<&str>, severity: &str| {
+        Some(SuspiciousDependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            manifest: manifest.to_string(),
+            reason,
+            resembles: resembles.map(str::to_string),
+            severity: severity.to_string(),
+        })
+    };
+
+    let popular = ecosystem.popular_packages();
+    if !popular.iter().any(|p| ecosystem.normalize(p) == normalized)
+        && normalized.len() >= MIN_TYPOSQUAT_NAME_LEN
+    {
+        let max_distance = if normalized.len() > 8 { 2 } else { 1 };
+        let closest = popular
+            .iter()
+            .map(|p| (*p, edit_distance(&normalized, &ecosystem.normalize(p))))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by_key(|(_, distance)| *distance);
+        if let Some((target, distance)) = closest {
+            return suspicious(
+                format!(
+                    "Name is {} edit(s) away from the popular package '{}' (possible typosquat)",
+                    distance, target
+                ),
+                Some(target),
+                "high",
+            );
+        }
+    }
+
+    // Scoped npm packages can only be published by the scope's owner
+    let unscoped = !(ecosystem == Ecosystem::Npm && name.starts_with('@'));
+    let looks_internal = normalized
+        .split(['-', '_', '.', '/'])
+        .any(|part| INTERNAL_NAME_MARKERS.contains(&part));
+    if unscoped && looks_internal {
+        return suspicious(
+            "Name suggests an internal package but is resolved from the public registry, where anyone can claim it (dependency confusion)".to_string(),
+            None,
+            "medium",
+        );
+    }
+
+    None
+}
+
+/// Levenshtein distance between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,127 @@
+use super::ComplexityMetrics;
+use crate::patterns::{Severity, VulnerabilityFinding};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Stands in for the repository root, the directory of top-level files
+pub const ROOT_DIRECTORY: &str = ".";
+
+/// Findings and complexity rolled up for one directory and everything below it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DirectoryRisk {
+    /// Path relative to the repository root, e.g. `src/parser`
+    pub path: String,
+    /// Number of path components, 1 for top-level directories
+    pub depth: usize,
+    /// Findings touching at least one file below the directory, each counted once
+    pub findings: usize,
+    pub critical_findings: usize,
+    pub total_risk: f64,
+    pub max_risk: f64,
+    /// Files below the directory changed by a finding
+    pub files_with_findings: usize,
+    /// Files below the directory with complexity metrics
+    pub analyzed_files: usize,
+    /// Mean cyclomatic complexity of the analyzed files; `None` without code analysis
+    pub average_complexity: Option<f64>,
+}
+
+#[derive(Default)]
+struct DirectoryTotals<'a> {
+    findings: HashSet<usize>,
+    files: HashSet<&'a str>,
+    complexity: Vec<f64>,
+}
+
+/// Directories down to `max_depth` components as a tree in depth-first order: each
+/// directory is followed by its subdirectories, and siblings are ordered by total risk
+pub fn directory_tree(
+    findings: &[VulnerabilityFinding],
+    file_complexity: &HashMap<String, ComplexityMetrics>,
+    max_depth: usize,
+) -> Vec<DirectoryRisk> {
+    let max_depth = max_depth.max(1);
+    let mut totals: BTreeMap<String, DirectoryTotals> = BTreeMap::new();
+
+    for (i, finding) in findings.iter().enumerate() {
+        for file in &finding.files_changed {
+            for directory in ancestors(file, max_depth) {
+                let entry = totals.entry(directory).or_default();
+                entry.findings.insert(i);
+                entry.files.insert(file.as_str());
+            }
+        }
+    }
+    for (file, metrics) in file_complexity {
+        for directory in ancestors(file, max_depth) {
+            totals
+                .entry(directory)
+                .or_default()
+                .complexity
+                .push(metrics.cyclomatic_complexity);
+        }
+    }
+
+    let mut children: BTreeMap<Option<String>, Vec<DirectoryRisk>> = BTreeMap::new();
+    for (path, totals) in totals {
+        let risks: Vec<&VulnerabilityFinding> =
+            totals.findings.iter().map(|&i| &findings[i]).collect();
+        let parent = path.rsplit_once('/').map(|(parent, _)| parent.to_string());
+        children.entry(parent).or_default().push(DirectoryRisk {
+            depth: path.split('/').count(),
+            findings: risks.len(),
+            critical_findings: risks
+                .iter()
+                .filter(|f| {
+                    f.patterns_matched
+                        .iter()
+                        .any(|m| matches!(m.severity, Severity::Critical))
+                })
+                .count(),
+            total_risk: risks.iter().map(|f| f.risk_score).sum(),
+            max_risk: risks.iter().map(|f| f.risk_score).fold(0.0, f64::max),
+            files_with_findings: totals.files.len(),
+            analyzed_files: totals.complexity.len(),
+            average_complexity: (!totals.complexity.is_empty())
+                .then(|| totals.complexity.iter().sum::<f64>() / totals.complexity.len() as f64),
+            path,
+        });
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| {
+            b.total_risk
+                .partial_cmp(&a.total_risk)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+    }
+
+    let mut tree = Vec::new();
+    push_subtree(&mut children, None, &mut tree);
+    tree
+}
+
+fn push_subtree(
+    children: &mut BTreeMap<Option<String>, Vec<DirectoryRisk>>,
+    parent: Option<String>,
+    tree: &mut Vec<DirectoryRisk>,
+) {
+    for directory in children.remove(&parent).unwrap_or_default() {
+        let path = directory.path.clone();
+        tree.push(directory);
+        push_subtree(children, Some(path), tree);
+    }
+}
+
+/// Directories containing `file`, outermost first, at most `max_depth` deep
+fn ancestors(file: &str, max_depth: usize) -> Vec<String> {
+    let components: Vec<&str> = file.split('/').filter(|c| !c.is_empty()).collect();
+    let directories = components.len().saturating_sub(1).min(max_depth);
+    if directories == 0 {
+        return vec![ROOT_DIRECTORY.to_string()];
+    }
+    (1..=directories)
+        .map(|depth| components[..depth].join("/"))
+        .collect()
+}
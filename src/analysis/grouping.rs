@@ -0,0 +1,135 @@
+use super::timing::TIMING_ANOMALY_PATTERN;
+use crate::patterns::{RelatedCommit, VulnerabilityFinding};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use tracing::info;
+
+/// Findings collapsed into one: the patterns and files they share and the newest commit date
+struct Group {
+    members: Vec<usize>,
+    patterns: HashSet<String>,
+    files: HashSet<String>,
+    last_date: DateTime<Utc>,
+}
+
+impl Group {
+    fn accepts(&self, finding: &VulnerabilityFinding, window: Duration) -> bool {
+        finding.date - self.last_date <= window
+            && finding
+                .files_changed
+                .iter()
+                .any(|file| self.files.contains(file))
+            && pattern_names(finding).any(|name| self.patterns.contains(name))
+    }
+
+    fn add(&mut self, index: usize, finding: &VulnerabilityFinding) {
+        self.members.push(index);
+        self.patterns
+            .extend(pattern_names(finding).map(str::to_string));
+        self.files.extend(finding.files_changed.iter().cloned());
+        self.last_date = self.last_date.max(finding.date);
+    }
+}
+
+/// Pattern names a finding can be grouped by; timing anomalies say nothing about the issue
+fn pattern_names(finding: &VulnerabilityFinding) -> impl Iterator<Item = &str> {
+    finding
+        .patterns_matched
+        .iter()
+        .map(|m| m.pattern_name.as_str())
+        .filter(|name| *name != TIMING_ANOMALY_PATTERN)
+}
+
+/// Collapse findings that share a pattern and touch overlapping files within `window_days`
+/// of each other, such as a fix, its revert and follow-ups. The highest-risk finding of each
+/// group stands for it, listing the others as related commits and taking over their files and
+/// CVE references. Returns the number of findings folded into another.
+pub fn group_duplicates(findings: &mut Vec<VulnerabilityFinding>, window_days: i64) -> usize {
+    let window = Duration::days(window_days);
+    let mut order: Vec<usize> = (0..findings.len()).collect();
+    order.sort_by_key(|&i| findings[i].date);
+
+    let mut groups: Vec<Group> = Vec::new();
+    for i in order {
+        let finding = &findings[i];
+        match groups
+            .iter_mut()
+            .find(|group| group.accepts(finding, window))
+        {
+            Some(group) => group.add(i, finding),
+            None => {
+                let mut group = Group {
+                    members: Vec::new(),
+                    patterns: HashSet::new(),
+                    files: HashSet::new(),
+                    last_date: finding.date,
+                };
+                group.add(i, finding);
+                groups.push(group);
+            }
+        }
+    }
+
+    let mut slots: Vec<Option<VulnerabilityFinding>> =
+        std::mem::take(findings).into_iter().map(Some).collect();
+    let mut grouped = 0;
+    let mut kept = Vec::with_capacity(groups.len());
+    for group in groups {
+        let representative = group
+            .members
+            .iter()
+            .copied()
+            .max_by(|&a, &b| {
+                let (a, b) = (slots[a].as_ref().unwrap(), slots[b].as_ref().unwrap());
+                a.risk_score
+                    .partial_cmp(&b.risk_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    // Among equal scores the earliest commit stands for the group
+                    .then_with(|| b.date.cmp(&a.date))
+            })
+            .unwrap_or(group.members[0]);
+        let mut finding = slots[representative].take().unwrap();
+
+        for &member in &group.members {
+            let Some(other) = slots[member].take() else {
+                continue;
+            };
+            grouped += 1;
+            for file in other.files_changed {
+                if !finding.files_changed.contains(&file) {
+                    finding.files_changed.push(file);
+                }
+            }
+            for cve in other.cve_references {
+                if !finding.cve_references.contains(&cve) {
+                    finding.cve_references.push(cve);
+                }
+            }
+            finding.related_commits.push(RelatedCommit {
+                commit_id: other.commit_id,
+                subject: other
+                    .commit_message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                author: other.author,
+                date: other.date,
+            });
+        }
+        finding.related_commits.sort_by_key(|related| related.date);
+        kept.push((representative, finding));
+    }
+    // Keep the scan's order of the findings that remain
+    kept.sort_by_key(|(index, _)| *index);
+    findings.extend(kept.into_iter().map(|(_, finding)| finding));
+
+    if grouped > 0 {
+        info!(
+            "Grouped {} duplicate findings into {} distinct findings",
+            grouped,
+            findings.len()
+        );
+    }
+    grouped
+}
@@ -0,0 +1,219 @@
+use super::LicenseIssue;
+use spdx::{Expression, LicenseItem, LicenseReq, ParseMode};
+
+/// Exceptions that let code under a GPL license be linked without the combination becoming
+/// GPL, which makes the license behave like a weak copyleft one
+const LINKING_EXCEPTIONS: &[&str] = &[
+    "Classpath-exception-2.0",
+    "LLVM-exception",
+    "GCC-exception-2.0",
+    "GCC-exception-3.1",
+    "Autoconf-exception-2.0",
+    "Autoconf-exception-3.0",
+    "Bison-exception-2.2",
+    "Font-exception-2.0",
+];
+
+const PERMISSIVE_LICENSES: &[&str] = &[
+    "MIT",
+    "MIT-0",
+    "ISC",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "0BSD",
+    "Zlib",
+    "Unlicense",
+    "CC0-1.0",
+    "BSL-1.0",
+    "Unicode-DFS-2016",
+    "Unicode-3.0",
+    "Python-2.0",
+    "PSF-2.0",
+    "X11",
+    "WTFPL",
+];
+
+const WEAK_COPYLEFT_LICENSES: &[&str] = &[
+    "LGPL-2.0", "LGPL-2.1", "LGPL-3.0", "MPL-2.0", "EPL-1.0", "EPL-2.0", "CDDL-1.0", "CDDL-1.1",
+];
+
+/// Licenses grouped by what they demand of the combined work, the axis of the
+/// compatibility matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseFamily {
+    /// Closed source, including npm's `UNLICENSED` and `LicenseRef-*` licenses
+    Proprietary,
+    Permissive,
+    /// Permissive, but its patent terms are incompatible with GPL-2.0-only
+    Apache2,
+    /// Copyleft limited to the library or file itself (LGPL, MPL, EPL, or GPL with a linking
+    /// exception)
+    WeakCopyleft,
+    Gpl2Only,
+    Gpl2OrLater,
+    Gpl3,
+    Agpl3,
+}
+
+impl LicenseFamily {
+    /// Family of one license of an expression; `None` for licenses outside the matrix
+    fn of(req: &LicenseReq) -> Option<Self> {
+        let (name, or_later) = match &req.license {
+            LicenseItem::Spdx { id, or_later } => (id.name, *or_later),
+            LicenseItem::Other { .. } => return Some(LicenseFamily::Proprietary),
+        };
+        let or_later = or_later || name.ends_with("-or-later") || name.ends_with('+');
+        let base = name
+            .trim_end_matches('+')
+            .trim_end_matches("-or-later")
+            .trim_end_matches("-only");
+
+        let linking_exception = req
+            .exception
+            .as_ref()
+            .is_some_and(|exception| LINKING_EXCEPTIONS.contains(&exception.name));
+        if base.starts_with("GPL-") && linking_exception {
+            return Some(LicenseFamily::WeakCopyleft);
+        }
+
+        match base {
+            "GPL-2.0" if or_later => Some(LicenseFamily::Gpl2OrLater),
+            "GPL-2.0" => Some(LicenseFamily::Gpl2Only),
+            "GPL-3.0" => Some(LicenseFamily::Gpl3),
+            "AGPL-3.0" => Some(LicenseFamily::Agpl3),
+            "Apache-2.0" => Some(LicenseFamily::Apache2),
+            _ if WEAK_COPYLEFT_LICENSES.contains(&base) => Some(LicenseFamily::WeakCopyleft),
+            _ if PERMISSIVE_LICENSES.contains(&base) => Some(LicenseFamily::Permissive),
+            _ => None,
+        }
+    }
+
+    /// Whether a project under this license may include a dependency under `dependency`
+    pub fn accepts(self, dependency: LicenseFamily) -> bool {
+        use LicenseFamily::*;
+        match (self, dependency) {
+            (_, Permissive | WeakCopyleft) => true,
+            (Gpl2Only, Apache2) => false,
+            (_, Apache2) => true,
+            (Gpl2Only | Gpl2OrLater, Gpl2Only) => true,
+            (Gpl2Only | Gpl2OrLater | Gpl3 | Agpl3, Gpl2OrLater) => true,
+            // A GPL-2.0-or-later project can be distributed under GPL-3.0
+            (Gpl2OrLater | Gpl3 | Agpl3, Gpl3) => true,
+            (Gpl3 | Agpl3, Agpl3) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The license a project is distributed under, against which dependency licenses are checked
+#[derive(Debug)]
+pub struct LicensePolicy {
+    expression: String,
+    /// Families of the project's licenses; with `A OR B` the project may pick either
+    families: Vec<LicenseFamily>,
+}
+
+impl LicensePolicy {
+    /// Policy for an SPDX expression such as `MIT OR Apache-2.0`; `UNLICENSED` and
+    /// `proprietary` declare a closed-source project
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        let families = if ["UNLICENSED", "proprietary"]
+            .iter()
+            .any(|closed| expression.eq_ignore_ascii_case(closed))
+        {
+            vec![LicenseFamily::Proprietary]
+        } else {
+            let parsed = Expression::parse_mode(expression, ParseMode::LAX)
+                .map_err(|e| format!("invalid SPDX expression '{}': {}", expression, e))?;
+            parsed
+                .requirements()
+                .filter_map(|r| LicenseFamily::of(&r.req))
+                .collect()
+        };
+        Ok(Self {
+            expression: expression.to_string(),
+            families,
+        })
+    }
+
+    /// Issue for a dependency whose license expression cannot be parsed, or cannot be
+    /// satisfied under any of the project's licenses. Licenses outside the compatibility
+    /// matrix are given the benefit of the doubt.
+    pub fn check(&self, dependency: &str, license: &str) -> Option<LicenseIssue> {
+        let issue = |issue_type: &str, reason: String| {
+            Some(LicenseIssue {
+                dependency: dependency.to_string(),
+                license: license.to_string(),
+                issue_type: issue_type.to_string(),
+                reason,
+            })
+        };
+        let parsed = match Expression::parse_mode(license, ParseMode::LAX) {
+            Ok(parsed) => parsed,
+            Err(e) => return issue("unknown", format!("Not a valid SPDX expression: {}", e)),
+        };
+        if self.families.is_empty() {
+            return None;
+        }
+
+        // Custom `LicenseRef-*` terms of a dependency cannot be judged either
+        let compatible = self.families.iter().any(|project| {
+            parsed.evaluate(|req| match LicenseFamily::of(req) {
+                Some(LicenseFamily::Proprietary) | None => true,
+                Some(dependency) => project.accepts(dependency),
+            })
+        });
+        if compatible {
+            return None;
+        }
+        issue(
+            "conflicting",
+            format!(
+                "No choice of '{}' is compatible with the project license '{}'",
+                license, self.expression
+            ),
+        )
+    }
+}
+
+/// `license` of the `[package]` table of a Cargo.toml
+pub fn cargo_package_license(content: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if in_package && key.trim() == "license" {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            return (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+    None
+}
+
+/// `license` of a package.json, also in the legacy `{ "type": ... }` and `licenses` forms
+pub fn npm_package_license(content: &str) -> Option<String> {
+    let manifest: serde_json::Value = serde_json::from_str(content).ok()?;
+    let license_type = |value: &serde_json::Value| {
+        value
+            .as_str()
+            .or_else(|| value.get("type")?.as_str())
+            .map(str::to_string)
+    };
+    if let Some(license) = manifest.get("license").and_then(license_type) {
+        return Some(license);
+    }
+    let licenses: Vec<String> = manifest
+        .get("licenses")?
+        .as_array()?
+        .iter()
+        .filter_map(license_type)
+        .collect();
+    (!licenses.is_empty()).then(|| licenses.join(" OR "))
+}
@@ -0,0 +1,209 @@
+use super::DependencyTree;
+use std::collections::{HashMap, VecDeque};
+
+/// Resolved dependency graph of a lockfile: `roots` are the project's own packages and
+/// `edges[i]` the packages package `i` depends on
+struct Graph {
+    roots: Vec<usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Breadth-first levels from the project's packages: level 1 is direct, deeper levels
+    /// transitive. Packages nothing reaches, such as extraneous npm installs, are not counted.
+    fn summarize(&self, lockfile: &str) -> DependencyTree {
+        let mut levels: Vec<Option<usize>> = vec![None; self.edges.len()];
+        let mut queue = VecDeque::new();
+        for &root in &self.roots {
+            levels[root] = Some(0);
+            queue.push_back(root);
+        }
+        while let Some(package) = queue.pop_front() {
+            let next = levels[package].unwrap_or_default() + 1;
+            for &dependency in &self.edges[package] {
+                if levels[dependency].is_none() {
+                    levels[dependency] = Some(next);
+                    queue.push_back(dependency);
+                }
+            }
+        }
+
+        let levels: Vec<usize> = levels.into_iter().flatten().collect();
+        DependencyTree {
+            lockfile: lockfile.to_string(),
+            direct: levels.iter().filter(|&&level| level == 1).count(),
+            transitive: levels.iter().filter(|&&level| level > 1).count(),
+            depth: levels.into_iter().max().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct LockedCrate {
+    name: String,
+    version: String,
+    /// Registry or git source; workspace members and path dependencies have none
+    has_source: bool,
+    /// `name`, `name version` or `name version (source)` references
+    dependencies: Vec<String>,
+}
+
+/// Dependency tree of a Cargo.lock. Workspace members and path dependencies are the
+/// project's own packages; `None` for a lockfile without packages.
+pub fn cargo_lock_tree(content: &str) -> Option<DependencyTree> {
+    let mut crates: Vec<LockedCrate> = Vec::new();
+    let mut in_package = false;
+    let mut in_dependencies = false;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if in_dependencies {
+            if let Some(package) = crates.last_mut() {
+                package.dependencies.extend(quoted(line));
+            }
+            in_dependencies = !line.contains(']');
+            continue;
+        }
+        if line.starts_with('[') {
+            in_package = line == "[[package]]";
+            if in_package {
+                crates.push(LockedCrate::default());
+            }
+            continue;
+        }
+        let (Some(package), Some((key, value))) = (
+            crates.last_mut().filter(|_| in_package),
+            line.split_once('='),
+        ) else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "name" => package.name = value.trim_matches('"').to_string(),
+            "version" => package.version = value.trim_matches('"').to_string(),
+            "source" => package.has_source = true,
+            "dependencies" => {
+                package.dependencies.extend(quoted(value));
+                in_dependencies = !value.contains(']');
+            }
+            _ => {}
+        }
+    }
+    if crates.is_empty() {
+        return None;
+    }
+
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, package) in crates.iter().enumerate() {
+        by_name.entry(package.name.as_str()).or_default().push(i);
+    }
+    // A bare name is unique in the lockfile, ambiguous names carry their version
+    let resolve = |reference: &str| {
+        let mut parts = reference.split_whitespace();
+        let candidates = by_name.get(parts.next()?)?;
+        match parts.next() {
+            Some(version) => candidates
+                .iter()
+                .copied()
+                .find(|&i| crates[i].version == version),
+            None => candidates.first().copied(),
+        }
+    };
+
+    let graph = Graph {
+        roots: (0..crates.len())
+            .filter(|&i| !crates[i].has_source)
+            .collect(),
+        edges: crates
+            .iter()
+            .map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter_map(|reference| resolve(reference))
+                    .collect()
+            })
+            .collect(),
+    };
+    Some(graph.summarize("Cargo.lock"))
+}
+
+/// Dependency tree of a package-lock.json (or npm-shrinkwrap.json) in the `packages` layout
+/// of lockfile versions 2 and 3; `None` for version 1 lockfiles, which lack it. The root
+/// and workspace packages are the project's own.
+pub fn npm_lock_tree(content: &str, lockfile: &str) -> serde_json::Result<Option<DependencyTree>> {
+    let lock: serde_json::Value = serde_json::from_str(content)?;
+    let Some(packages) = lock.get("packages").and_then(|p| p.as_object()) else {
+        return Ok(None);
+    };
+
+    let paths: Vec<&str> = packages.keys().map(String::as_str).collect();
+    let index: HashMap<&str, usize> = paths.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+    let edges = paths
+        .iter()
+        .map(|&path| {
+            let package = &packages[path];
+            // Workspace links point at the package directory holding the actual manifest
+            if package.get("link").and_then(|l| l.as_bool()) == Some(true) {
+                return package
+                    .get("resolved")
+                    .and_then(|r| r.as_str())
+                    .and_then(|target| index.get(target).copied())
+                    .into_iter()
+                    .collect();
+            }
+            let mut sections = vec!["dependencies", "optionalDependencies", "peerDependencies"];
+            // Only the project's own packages list dev dependencies that get installed
+            if !path.starts_with("node_modules/") {
+                sections.push("devDependencies");
+            }
+            sections
+                .iter()
+                .filter_map(|section| package.get(section)?.as_object())
+                .flat_map(|dependencies| dependencies.keys())
+                .filter_map(|name| resolve_npm(&index, path, name))
+                .collect()
+        })
+        .collect();
+
+    let graph = Graph {
+        roots: paths
+            .iter()
+            .enumerate()
+            .filter(|(_, path)| {
+                !path.starts_with("node_modules/") && !path.contains("/node_modules/")
+            })
+            .map(|(i, _)| i)
+            .collect(),
+        edges,
+    };
+    Ok(Some(graph.summarize(lockfile)))
+}
+
+/// Package `name` as Node resolves it from the package at `from`: its own `node_modules`,
+/// then those of each enclosing package up to the project root
+fn resolve_npm(index: &HashMap<&str, usize>, from: &str, name: &str) -> Option<usize> {
+    let mut base = from;
+    loop {
+        let candidate = if base.is_empty() {
+            format!("node_modules/{}", name)
+        } else {
+            format!("{}/node_modules/{}", base, name)
+        };
+        if let Some(&i) = index.get(candidate.as_str()) {
+            return Some(i);
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = match base.rsplit_once("/node_modules/") {
+            Some((parent, _)) => parent,
+            None => "",
+        };
+    }
+}
+
+/// Double-quoted strings in a line of a TOML array
+fn quoted(line: &str) -> impl Iterator<Item = String> + '_ {
+    line.split('"').skip(1).step_by(2).map(str::to_string)
+}
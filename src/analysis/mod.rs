@@ -1,17 +1,36 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 
+pub mod anonymize;
 pub mod code_analyzer;
 pub mod complexity;
+pub mod complexity_cache;
+pub mod cwe;
 pub mod dependencies;
+pub mod directory_risk;
+pub mod grouping;
+pub mod licenses;
+pub mod lockfiles;
+pub mod policy;
+pub mod timing;
 
+pub use anonymize::Anonymizer;
 pub use code_analyzer::CodeAnalyzer;
+pub use cwe::CweCoverage;
+pub use directory_risk::DirectoryRisk;
+pub use licenses::LicensePolicy;
+pub use policy::Policy;
 
 use crate::config::Config;
 use crate::git::RepositoryStats;
-use crate::patterns::VulnerabilityFinding;
+use crate::patterns::{
+    Category, Confidence, FlatMatch, MarkerDensity, PatternHits, Severity, VulnerabilityFinding,
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CodeStats {
     pub total_lines: usize,
     pub total_files: usize,
@@ -34,7 +53,29 @@ impl Default for CodeStats {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl CodeStats {
+    /// Share of counted lines written in `prose_languages` (documentation and plain text),
+    /// `None` without language statistics
+    pub fn prose_share(&self, prose_languages: &[String]) -> Option<f64> {
+        let total: usize = self.language_breakdown.values().map(|l| l.lines).sum();
+        if total == 0 {
+            return None;
+        }
+        let prose: usize = self
+            .language_breakdown
+            .values()
+            .filter(|l| {
+                prose_languages
+                    .iter()
+                    .any(|p| p.eq_ignore_ascii_case(&l.name))
+            })
+            .map(|l| l.lines)
+            .sum();
+        Some(prose as f64 / total as f64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LanguageStats {
     pub name: String,
     pub files: usize,
@@ -44,7 +85,7 @@ pub struct LanguageStats {
     pub complexity_score: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ComplexityMetrics {
     pub cyclomatic_complexity: f64,
     pub cognitive_complexity: f64,
@@ -54,12 +95,29 @@ pub struct ComplexityMetrics {
     pub maintainability_index: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DependencyAnalysis {
     pub total_dependencies: usize,
     pub outdated_dependencies: Vec<OutdatedDependency>,
     pub vulnerable_dependencies: Vec<VulnerableDependency>,
     pub license_issues: Vec<LicenseIssue>,
+    /// Likely typosquats and dependency confusion candidates
+    #[serde(default)]
+    pub suspicious_dependencies: Vec<SuspiciousDependency>,
+    /// Packages the project depends on directly, per its lockfiles
+    #[serde(default)]
+    pub direct_dependencies: usize,
+    /// Packages pulled in only through other dependencies, per its lockfiles
+    #[serde(default)]
+    pub transitive_dependencies: usize,
+    /// Longest dependency chain of any lockfile, 1 when every dependency is direct
+    #[serde(default)]
+    pub dependency_tree_depth: usize,
+    #[serde(default)]
+    pub dependency_trees: Vec<DependencyTree>,
+    /// Manifests untouched for the stale window in an otherwise active history, oldest first
+    #[serde(default)]
+    pub stale_manifests: Vec<StaleManifest>,
 }
 
 impl Default for DependencyAnalysis {
@@ -69,11 +127,38 @@ impl Default for DependencyAnalysis {
             outdated_dependencies: Vec::new(),
             vulnerable_dependencies: Vec::new(),
             license_issues: Vec::new(),
+            suspicious_dependencies: Vec::new(),
+            direct_dependencies: 0,
+            transitive_dependencies: 0,
+            dependency_tree_depth: 0,
+            dependency_trees: Vec::new(),
+            stale_manifests: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Shape of the dependency graph a lockfile resolves, a measure of supply-chain surface
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DependencyTree {
+    /// Lockfile the graph was read from, e.g. `Cargo.lock`
+    pub lockfile: String,
+    pub direct: usize,
+    pub transitive: usize,
+    /// Longest chain of dependencies from the project to a package
+    pub depth: usize,
+}
+
+/// Dependency manifest whose dependency set has not been updated while the rest of the
+/// project kept changing
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StaleManifest {
+    pub path: String,
+    /// Last commit to the manifest or its lockfile, whichever is newer
+    pub last_update: chrono::DateTime<chrono::Utc>,
+    pub days_since_update: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OutdatedDependency {
     pub name: String,
     pub current_version: String,
@@ -81,7 +166,7 @@ pub struct OutdatedDependency {
     pub age_days: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VulnerableDependency {
     pub name: String,
     pub version: String,
@@ -89,14 +174,30 @@ pub struct VulnerableDependency {
     pub severity: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Dependency whose name suggests a supply-chain attack rather than a known vulnerability
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SuspiciousDependency {
+    pub name: String,
+    pub version: String,
+    /// Manifest declaring the dependency, relative to the repository root
+    pub manifest: String,
+    pub reason: String,
+    /// Popular package the name imitates, for typosquats
+    pub resembles: Option<String>,
+    pub severity: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LicenseIssue {
     pub dependency: String,
     pub license: String,
     pub issue_type: String, // "restrictive", "unknown", "conflicting"
+    /// Why the license was flagged, e.g. the project license it conflicts with
+    #[serde(default)]
+    pub reason: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RiskFactor {
     pub factor_type: RiskType,
     pub severity: RiskSeverity,
@@ -105,7 +206,7 @@ pub struct RiskFactor {
     pub recommendation: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum RiskType {
     SingleAuthorFile,
     StaleCode,
@@ -119,7 +220,7 @@ pub enum RiskType {
     DeadCode,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum RiskSeverity {
     Critical,
     High,
@@ -128,90 +229,1098 @@ pub enum RiskSeverity {
     Info,
 }
 
+/// Actionable risk classification derived from the overall score
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum RiskTier {
+    Critical,
+    High,
+    Medium,
+    Low,
+}
+
+impl RiskTier {
+    fn level(&self) -> u8 {
+        match self {
+            RiskTier::Critical => 3,
+            RiskTier::High => 2,
+            RiskTier::Medium => 1,
+            RiskTier::Low => 0,
+        }
+    }
+
+    pub fn is_at_least(&self, other: RiskTier) -> bool {
+        self.level() >= other.level()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskTier::Critical => "critical",
+            RiskTier::High => "high",
+            RiskTier::Medium => "medium",
+            RiskTier::Low => "low",
+        }
+    }
+}
+
+impl fmt::Display for RiskTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RiskTier {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "critical" => Ok(RiskTier::Critical),
+            "high" => Ok(RiskTier::High),
+            "medium" => Ok(RiskTier::Medium),
+            "low" => Ok(RiskTier::Low),
+            _ => Err(anyhow::anyhow!(
+                "Unknown risk tier '{}' (expected critical, high, medium or low)",
+                s
+            )),
+        }
+    }
+}
+
+/// Lowest 0-10 risk score of each severity, shared by the report styling, the risk tier and
+/// the CI gate so that recalibrating a threshold changes all of them together
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct SeverityScale {
+    pub critical: f64,
+    pub high: f64,
+    pub medium: f64,
+    /// Scores below this are informational
+    pub low: f64,
+}
+
+impl Default for SeverityScale {
+    fn default() -> Self {
+        Self {
+            critical: 8.0,
+            high: 6.0,
+            medium: 4.0,
+            low: 2.0,
+        }
+    }
+}
+
+impl SeverityScale {
+    /// Severity name of a score: critical, high, medium, low or info
+    pub fn severity(&self, score: f64) -> &'static str {
+        if score >= self.critical {
+            "critical"
+        } else if score >= self.high {
+            "high"
+        } else if score >= self.medium {
+            "medium"
+        } else if score >= self.low {
+            "low"
+        } else {
+            "info"
+        }
+    }
+
+    /// Risk tier of a score; informational scores are low risk
+    pub fn tier(&self, score: f64) -> RiskTier {
+        if score >= self.critical {
+            RiskTier::Critical
+        } else if score >= self.high {
+            RiskTier::High
+        } else if score >= self.medium {
+            RiskTier::Medium
+        } else {
+            RiskTier::Low
+        }
+    }
+}
+
+/// How heatmap churn buckets are derived from the files' commit counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChurnBucketing {
+    /// Equal fifths of the busiest file's commit count
+    #[default]
+    Linear,
+    /// Fifths of the files, so one hyperactive file cannot squash the others together
+    Quantile,
+}
+
+impl FromStr for ChurnBucketing {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(ChurnBucketing::Linear),
+            "quantile" => Ok(ChurnBucketing::Quantile),
+            _ => Err(anyhow::anyhow!(
+                "Unknown heatmap bucketing '{}' (expected linear or quantile)",
+                s
+            )),
+        }
+    }
+}
+
+/// Highest commit count of each of the four lower heatmap buckets; files above the last
+/// threshold are high churn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChurnScale {
+    thresholds: [usize; 4],
+}
+
+impl ChurnScale {
+    pub fn new(counts: &[usize], bucketing: ChurnBucketing) -> Self {
+        let thresholds = match bucketing {
+            ChurnBucketing::Linear => {
+                let max = counts.iter().copied().max().unwrap_or(0);
+                [1, 2, 3, 4].map(|fifth| max * fifth / 5)
+            }
+            ChurnBucketing::Quantile => {
+                let mut sorted = counts.to_vec();
+                sorted.sort_unstable();
+                [1, 2, 3, 4].map(|fifth| {
+                    let rank = (sorted.len() * fifth).div_ceil(5);
+                    sorted.get(rank.saturating_sub(1)).copied().unwrap_or(0)
+                })
+            }
+        };
+        Self { thresholds }
+    }
+
+    pub fn thresholds(&self) -> [usize; 4] {
+        self.thresholds
+    }
+
+    /// Bucket of a commit count: 0 for untouched files, 1-4, or 5 for high churn
+    pub fn bucket(&self, count: usize) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        1 + self.thresholds.iter().filter(|&&t| count > t).count()
+    }
+
+    /// Heatmap CSS class of a commit count
+    pub fn css_class(&self, count: usize) -> &'static str {
+        match self.bucket(count) {
+            0 => "commits-0",
+            1 => "commits-1",
+            2 => "commits-2",
+            3 => "commits-3",
+            4 => "commits-4",
+            _ => "commits-high",
+        }
+    }
+}
+
+/// Risk tier together with a short explanation of how it was reached
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskTierSummary {
+    pub tier: RiskTier,
+    pub overall_risk: f64,
+    pub critical_findings: usize,
+    pub rationale: String,
+}
+
+/// Number of findings with at least one match in a category
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryCount {
+    pub category: Category,
+    pub findings: usize,
+}
+
+/// A file's place in the maintainability ranking
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MaintainabilityRank {
+    pub path: String,
+    /// 0-100, from Halstead volume, cyclomatic complexity and line count; higher is easier
+    pub maintainability_index: f64,
+    pub cyclomatic_complexity: f64,
+    pub line_count: usize,
+    /// "difficult" below 65, "moderate" up to 85, "good" above, the classic index bands
+    pub rating: String,
+}
+
+/// Files sharing an extension and their share of all files counted
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtensionCount {
+    pub extension: String,
+    pub count: usize,
+    pub percentage: f64,
+}
+
+/// Files grouped by lower-cased extension, most common first; extensionless files come last
+pub fn extension_distribution<'a>(
+    files: impl IntoIterator<Item = &'a String>,
+) -> Vec<ExtensionCount> {
+    let mut extension_counts: HashMap<String, usize> = HashMap::new();
+    let mut no_extension_count = 0;
+    let mut total = 0;
+
+    for file in files {
+        total += 1;
+        if let Some(extension) = std::path::Path::new(file)
+            .extension()
+            .and_then(|s| s.to_str())
+        {
+            *extension_counts
+                .entry(extension.to_lowercase())
+                .or_insert(0) += 1;
+        } else {
+            no_extension_count += 1;
+        }
+    }
+
+    let percentage = |count: usize| count as f64 / total as f64 * 100.0;
+    let mut distribution: Vec<_> = extension_counts
+        .into_iter()
+        .map(|(extension, count)| ExtensionCount {
+            extension,
+            count,
+            percentage: percentage(count),
+        })
+        .collect();
+    distribution.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+
+    if no_extension_count > 0 {
+        distribution.push(ExtensionCount {
+            extension: "no extension".to_string(),
+            count: no_extension_count,
+            percentage: percentage(no_extension_count),
+        });
+    }
+
+    distribution
+}
+
+/// Findings for commits first shipped in a release tag
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReleaseFindings {
+    pub tag: String,
+    pub commits: usize,
+    pub findings: usize,
+    pub critical_findings: usize,
+    pub max_risk: f64,
+}
+
+/// Vulnerability-fix activity aggregated per author, used to route security reviews
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuthorRisk {
+    pub author: String,
+    pub findings: usize,
+    pub critical_findings: usize,
+    pub total_risk: f64,
+    pub max_risk: f64,
+}
+
+/// A file absent from the analyzed tip together with the security findings of commits that
+/// touched it while it existed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RemovedFileFindings {
+    pub path: String,
+    pub commits: usize,
+    pub last_commit: chrono::DateTime<chrono::Utc>,
+    pub findings: usize,
+    pub max_risk: f64,
+}
+
+/// A file with findings and what to do about each, one entry of the remediation checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationItem {
+    pub file: String,
+    /// Highest risk score among the file's findings
+    pub max_risk: f64,
+    /// Findings that touched the file, riskiest first
+    pub steps: Vec<RemediationStep>,
+}
+
+/// One finding to address and the recommendations of its matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationStep {
+    pub commit_id: String,
+    pub commit_subject: String,
+    pub risk_score: f64,
+    /// Distinct matched patterns, with their CWE when known
+    pub issues: Vec<String>,
+    /// Distinct recommendations of the matched patterns
+    pub recommendations: Vec<String>,
+}
+
+impl RemediationStep {
+    fn from_finding(finding: &VulnerabilityFinding) -> Self {
+        let mut issues: Vec<String> = Vec::new();
+        let mut recommendations: Vec<String> = Vec::new();
+        for pattern_match in &finding.patterns_matched {
+            let issue = match &pattern_match.cwe {
+                Some(cwe) => format!("{} ({})", pattern_match.pattern_name, cwe),
+                None => pattern_match.pattern_name.clone(),
+            };
+            if !issues.contains(&issue) {
+                issues.push(issue);
+            }
+            if let Some(remediation) = &pattern_match.remediation {
+                if !recommendations.contains(remediation) {
+                    recommendations.push(remediation.clone());
+                }
+            }
+        }
+
+        Self {
+            commit_id: finding.commit_id.clone(),
+            commit_subject: finding
+                .commit_message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            risk_score: finding.risk_score,
+            issues,
+            recommendations,
+        }
+    }
+}
+
+/// Risk of one side of a branch comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionSummary {
+    pub revision: String,
+    pub commits: usize,
+    pub findings: usize,
+    pub critical_findings: usize,
+    pub overall_risk: f64,
+    pub tier: RiskTier,
+}
+
+/// Two revisions of one repository analyzed separately, with the findings only one of them has
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchComparison {
+    pub base: RevisionSummary,
+    pub branch: RevisionSummary,
+    /// Findings on commits reachable from the base but not the branch
+    pub only_in_base: Vec<VulnerabilityFinding>,
+    /// Findings on commits reachable from the branch but not the base
+    pub only_in_branch: Vec<VulnerabilityFinding>,
+    /// Number of findings on commits both revisions contain
+    pub shared_findings: usize,
+}
+
+impl BranchComparison {
+    /// Findings are matched by commit id, since a shared commit produces the same finding
+    pub fn new(
+        base_revision: &str,
+        base: &CombinedFindings,
+        branch_revision: &str,
+        branch: &CombinedFindings,
+    ) -> Self {
+        let base_commits: HashSet<&str> = base
+            .git_stats
+            .commit_history
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        let branch_commits: HashSet<&str> = branch
+            .git_stats
+            .commit_history
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+
+        let only_in = |findings: &CombinedFindings, other: &HashSet<&str>| {
+            let mut only: Vec<_> = findings
+                .vulnerabilities
+                .iter()
+                .filter(|f| !other.contains(f.commit_id.as_str()))
+                .cloned()
+                .collect();
+            only.sort_by(|a, b| {
+                b.risk_score
+                    .partial_cmp(&a.risk_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            only
+        };
+        let only_in_base = only_in(base, &branch_commits);
+        let only_in_branch = only_in(branch, &base_commits);
+
+        Self {
+            shared_findings: base.vulnerabilities.len() - only_in_base.len(),
+            base: base.revision_summary(base_revision),
+            branch: branch.revision_summary(branch_revision),
+            only_in_base,
+            only_in_branch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CombinedFindings {
     pub git_stats: RepositoryStats,
     pub code_stats: CodeStats,
     pub vulnerabilities: Vec<VulnerabilityFinding>,
     pub config: Config,
+    #[serde(default)]
+    pub timings: PhaseTimings,
+    /// Technical-debt markers per working tree file, densest first (`--patterns debt` only)
+    #[serde(default)]
+    pub marker_density: Vec<MarkerDensity>,
+    /// Matches per loaded pattern, including patterns that never fired
+    #[serde(default)]
+    pub pattern_hits: Vec<PatternHits>,
+    /// Why the pattern scan was skipped, e.g. a repository of mostly prose
+    #[serde(default)]
+    pub scan_skipped: Option<String>,
+}
+
+/// Wall-clock time spent in each analysis phase, in seconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PhaseTimings {
+    pub git_analysis_secs: f64,
+    /// `None` when code analysis was skipped (no `--stats`)
+    pub code_analysis_secs: Option<f64>,
+    pub pattern_scan_secs: f64,
+}
+
+impl PhaseTimings {
+    /// Accumulate another repository's timings into this one
+    pub fn add(&mut self, other: &PhaseTimings) {
+        self.git_analysis_secs += other.git_analysis_secs;
+        self.code_analysis_secs = match (self.code_analysis_secs, other.code_analysis_secs) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+        self.pattern_scan_secs += other.pattern_scan_secs;
+    }
 }
 
 impl CombinedFindings {
     /// Calculate overall repository risk score
     pub fn calculate_overall_risk(&self) -> f64 {
-        let mut risk_score = 0.0;
+        self.risk_breakdown().total
+    }
 
+    /// Overall risk score broken down into the components and contributors behind it
+    pub fn risk_breakdown(&self) -> RiskBreakdown {
         // Git-based risks
-        risk_score += self.calculate_git_risks();
+        let git = self.calculate_git_risks();
 
         // Code-based risks
-        risk_score += self.calculate_code_risks();
+        let code = self.calculate_code_risks();
 
         // Vulnerability-based risks
-        risk_score += self.calculate_vulnerability_risks();
+        let vulnerabilities = self.calculate_vulnerability_risks();
+
+        let total = (git.points + code.points + vulnerabilities.points).min(10.0);
+        let weighted_vulnerabilities = self.calculate_confidence_weighted_vulnerability_risk();
+        let confidence_weighted_total =
+            (git.points + code.points + weighted_vulnerabilities).min(10.0);
+        RiskBreakdown {
+            git,
+            code,
+            vulnerabilities,
+            total,
+            confidence_weighted_total,
+        }
+    }
+
+    /// Overall risk with each finding's score scaled by its confidence, so a few reliable
+    /// critical findings outweigh many low-confidence generic matches
+    pub fn calculate_confidence_weighted_risk(&self) -> f64 {
+        self.risk_breakdown().confidence_weighted_total
+    }
+
+    /// Every pattern match across all findings as a flat list
+    pub fn flat_matches(&self) -> Vec<FlatMatch> {
+        self.vulnerabilities
+            .iter()
+            .flat_map(|v| v.flat_matches())
+            .collect()
+    }
+
+    /// Findings grouped by the release tag their commit first shipped in, oldest release first.
+    /// Empty unless tag scanning was enabled.
+    pub fn findings_per_release(&self) -> Vec<ReleaseFindings> {
+        let mut releases: Vec<_> = self
+            .git_stats
+            .release_tags
+            .iter()
+            .map(|tag| ReleaseFindings {
+                tag: tag.clone(),
+                commits: 0,
+                findings: 0,
+                critical_findings: 0,
+                max_risk: 0.0,
+            })
+            .collect();
+        let index: HashMap<&str, usize> = self
+            .git_stats
+            .release_tags
+            .iter()
+            .enumerate()
+            .map(|(i, tag)| (tag.as_str(), i))
+            .collect();
+
+        let mut release_of_commit = HashMap::new();
+        for commit in &self.git_stats.commit_history {
+            if let Some(&i) = commit.tag.as_deref().and_then(|tag| index.get(tag)) {
+                releases[i].commits += 1;
+                release_of_commit.insert(commit.id.as_str(), i);
+            }
+        }
+
+        for finding in &self.vulnerabilities {
+            if let Some(&i) = release_of_commit.get(finding.commit_id.as_str()) {
+                let release = &mut releases[i];
+                release.findings += 1;
+                release.max_risk = release.max_risk.max(finding.risk_score);
+                if finding
+                    .patterns_matched
+                    .iter()
+                    .any(|p| matches!(p.severity, Severity::Critical))
+                {
+                    release.critical_findings += 1;
+                }
+            }
+        }
+
+        releases.retain(|r| r.commits > 0);
+        releases
+    }
+
+    /// Findings per pattern category, built-in and custom, most common first
+    pub fn category_counts(&self) -> Vec<CategoryCount> {
+        let mut counts: HashMap<&Category, usize> = HashMap::new();
+        for finding in &self.vulnerabilities {
+            let categories: std::collections::HashSet<_> = finding
+                .patterns_matched
+                .iter()
+                .map(|p| &p.category)
+                .collect();
+            for category in categories {
+                *counts.entry(category).or_insert(0) += 1;
+            }
+        }
+
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .map(|(category, findings)| CategoryCount {
+                category: category.clone(),
+                findings,
+            })
+            .collect();
+        counts.sort_by(|a, b| {
+            b.findings
+                .cmp(&a.findings)
+                .then_with(|| a.category.name().cmp(b.category.name()))
+        });
+        counts
+    }
+
+    /// Files touched by findings, grouped by extension
+    pub fn findings_by_extension(&self) -> Vec<ExtensionCount> {
+        extension_distribution(self.vulnerabilities.iter().flat_map(|v| &v.files_changed))
+    }
 
-        risk_score.min(10.0)
+    /// The `limit` least maintainable files, lowest index first. Files without measured lines
+    /// (binary, undecodable or empty) have no meaningful index and are left out.
+    pub fn maintainability_ranking(&self, limit: usize) -> Vec<MaintainabilityRank> {
+        let mut ranking: Vec<MaintainabilityRank> = self
+            .code_stats
+            .file_complexity
+            .iter()
+            .filter(|(_, metrics)| metrics.line_count > 0)
+            .map(|(path, metrics)| MaintainabilityRank {
+                path: path.clone(),
+                maintainability_index: metrics.maintainability_index,
+                cyclomatic_complexity: metrics.cyclomatic_complexity,
+                line_count: metrics.line_count,
+                rating: match metrics.maintainability_index {
+                    index if index < 65.0 => "difficult",
+                    index if index <= 85.0 => "moderate",
+                    _ => "good",
+                }
+                .to_string(),
+            })
+            .collect();
+        ranking.sort_by(|a, b| {
+            a.maintainability_index
+                .partial_cmp(&b.maintainability_index)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        ranking.truncate(limit);
+        ranking
     }
 
-    fn calculate_git_risks(&self) -> f64 {
-        let mut score = 0.0;
+    /// Findings tallied against the MITRE CWE Top 25
+    pub fn cwe_coverage(&self) -> CweCoverage {
+        CweCoverage::from_findings(&self.vulnerabilities)
+    }
 
-        // Single author files
-        let single_author_ratio =
-            self.git_stats.single_author_files.len() as f64 / self.git_stats.total_files as f64;
-        score += single_author_ratio * 2.0;
+    /// Findings and complexity rolled up per directory, down to `depth` path components
+    pub fn directory_risk(&self, depth: usize) -> Vec<DirectoryRisk> {
+        directory_risk::directory_tree(
+            &self.vulnerabilities,
+            &self.code_stats.file_complexity,
+            depth,
+        )
+    }
 
-        // Stale files
-        let stale_ratio =
-            self.git_stats.stale_files.len() as f64 / self.git_stats.total_files as f64;
-        score += stale_ratio * 1.5;
+    /// File types of the files carrying technical-debt markers
+    pub fn marker_files_by_extension(&self) -> Vec<ExtensionCount> {
+        extension_distribution(self.marker_density.iter().map(|m| &m.path))
+    }
 
-        // High churn files
-        let churn_ratio =
-            self.git_stats.high_churn_files.len() as f64 / self.git_stats.total_files as f64;
-        score += churn_ratio * 1.0;
+    /// Authors whose commits carry the most (and most severe) findings
+    pub fn riskiest_authors(&self, limit: usize) -> Vec<AuthorRisk> {
+        let mut by_author: HashMap<&str, AuthorRisk> = HashMap::new();
 
-        score
+        for finding in &self.vulnerabilities {
+            let entry = by_author
+                .entry(finding.author.as_str())
+                .or_insert_with(|| AuthorRisk {
+                    author: finding.author.clone(),
+                    findings: 0,
+                    critical_findings: 0,
+                    total_risk: 0.0,
+                    max_risk: 0.0,
+                });
+            entry.findings += 1;
+            entry.total_risk += finding.risk_score;
+            entry.max_risk = entry.max_risk.max(finding.risk_score);
+            if finding
+                .patterns_matched
+                .iter()
+                .any(|p| matches!(p.severity, Severity::Critical))
+            {
+                entry.critical_findings += 1;
+            }
+        }
+
+        let mut authors: Vec<_> = by_author.into_values().collect();
+        authors.sort_by(|a, b| {
+            b.total_risk
+                .partial_cmp(&a.total_risk)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.author.cmp(&b.author))
+        });
+        authors.truncate(limit);
+        authors
     }
 
-    fn calculate_code_risks(&self) -> f64 {
-        let mut score = 0.0;
+    /// Findings from commits at most `days` old, riskiest first, for reviewers to look at first
+    pub fn recent_findings(&self, days: i64) -> Vec<&VulnerabilityFinding> {
+        let mut recent: Vec<_> = self
+            .vulnerabilities
+            .iter()
+            .filter(|f| f.age_days <= days)
+            .collect();
+        recent.sort_by(|a, b| {
+            b.risk_score
+                .partial_cmp(&a.risk_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.date.cmp(&a.date))
+        });
+        recent
+    }
 
-        // High complexity files
+    /// Files removed from the tip, those touched by security findings first since their
+    /// contents (possibly leaked secrets) remain in history. Debt markers do not count.
+    pub fn removed_file_findings(&self) -> Vec<RemovedFileFindings> {
+        let mut removed: Vec<RemovedFileFindings> = self
+            .git_stats
+            .removed_files
+            .iter()
+            .map(|file| {
+                let risks: Vec<f64> = self
+                    .vulnerabilities
+                    .iter()
+                    .filter(|f| f.files_changed.contains(&file.path))
+                    .filter(|f| {
+                        f.patterns_matched
+                            .iter()
+                            .any(|m| m.category != Category::TechnicalDebt)
+                    })
+                    .map(|f| f.risk_score)
+                    .collect();
+                RemovedFileFindings {
+                    path: file.path.clone(),
+                    commits: file.commits,
+                    last_commit: file.last_commit,
+                    findings: risks.len(),
+                    max_risk: risks.iter().copied().fold(0.0, f64::max),
+                }
+            })
+            .collect();
+        // Stable, so files without findings keep the most-recent-first order
+        removed.sort_by(|a, b| {
+            b.max_risk
+                .partial_cmp(&a.max_risk)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        removed
+    }
+
+    /// The `limit` files with the most findings, as in the report's priority areas, each with
+    /// the steps to remediate its findings
+    pub fn remediation_checklist(&self, limit: usize) -> Vec<RemediationItem> {
+        let mut by_file: HashMap<&str, Vec<&VulnerabilityFinding>> = HashMap::new();
+        for finding in &self.vulnerabilities {
+            for file in &finding.files_changed {
+                by_file.entry(file.as_str()).or_default().push(finding);
+            }
+        }
+
+        let mut items: Vec<RemediationItem> = by_file
+            .into_iter()
+            .map(|(file, mut findings)| {
+                findings.sort_by(|a, b| {
+                    b.risk_score
+                        .partial_cmp(&a.risk_score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                RemediationItem {
+                    file: file.to_string(),
+                    max_risk: findings.first().map_or(0.0, |f| f.risk_score),
+                    steps: findings
+                        .into_iter()
+                        .map(RemediationStep::from_finding)
+                        .collect(),
+                }
+            })
+            .collect();
+        items.sort_by(|a, b| {
+            b.steps
+                .len()
+                .cmp(&a.steps.len())
+                .then_with(|| {
+                    b.max_risk
+                        .partial_cmp(&a.max_risk)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| a.file.cmp(&b.file))
+        });
+        items.truncate(limit);
+        items
+    }
+
+    fn revision_summary(&self, revision: &str) -> RevisionSummary {
+        let tier_summary = self.risk_tier_summary();
+        RevisionSummary {
+            revision: revision.to_string(),
+            commits: self.git_stats.total_commits,
+            findings: self.vulnerabilities.len(),
+            critical_findings: tier_summary.critical_findings,
+            overall_risk: tier_summary.overall_risk,
+            tier: tier_summary.tier,
+        }
+    }
+
+    /// Copy of these findings keeping only matches at or above `min` confidence.
+    /// Findings left without any match are dropped.
+    pub fn with_min_confidence(&self, min: Confidence) -> CombinedFindings {
+        let mut filtered = self.clone();
+        filtered.vulnerabilities = self
+            .vulnerabilities
+            .iter()
+            .filter_map(|v| {
+                let mut v = v.clone();
+                v.patterns_matched.retain(|m| m.confidence.is_at_least(min));
+                (!v.patterns_matched.is_empty()).then_some(v)
+            })
+            .collect();
+        filtered
+    }
+
+    /// Classify the repository into a risk tier
+    pub fn risk_tier(&self) -> RiskTier {
+        self.risk_tier_summary().tier
+    }
+
+    /// Risk tier plus the inputs and rationale behind it
+    pub fn risk_tier_summary(&self) -> RiskTierSummary {
+        let overall_risk = self.calculate_overall_risk();
+        let critical_findings = self.critical_finding_count();
+        let score_tier = self.config.risk.severity_scale.tier(overall_risk);
+
+        // A handful of critical findings escalates the tier regardless of the score
+        let finding_tier = match critical_findings {
+            0 => RiskTier::Low,
+            1..=2 => RiskTier::High,
+            _ => RiskTier::Critical,
+        };
+
+        let (tier, rationale) = if finding_tier.level() > score_tier.level() {
+            (
+                finding_tier,
+                format!(
+                    "{} critical finding(s) escalate the overall score of {:.1}/10",
+                    critical_findings, overall_risk
+                ),
+            )
+        } else {
+            (
+                score_tier,
+                format!(
+                    "Overall score {:.1}/10 with {} critical finding(s)",
+                    overall_risk, critical_findings
+                ),
+            )
+        };
+
+        RiskTierSummary {
+            tier,
+            overall_risk,
+            critical_findings,
+            rationale,
+        }
+    }
+
+    fn critical_finding_count(&self) -> usize {
+        self.vulnerabilities
+            .iter()
+            .filter(|v| {
+                v.patterns_matched
+                    .iter()
+                    .any(|p| matches!(p.severity, Severity::Critical))
+            })
+            .count()
+    }
+
+    fn calculate_git_risks(&self) -> RiskComponent {
+        // Empty repositories have no files to take a share of
+        let total_files = self.git_stats.total_files.max(1) as f64;
+
+        RiskComponent::from_contributors(
+            "Git history",
+            vec![
+                RiskContributor {
+                    label: format!(
+                        "{} single-author file(s)",
+                        self.git_stats.single_author_files.len()
+                    ),
+                    points: self.git_stats.single_author_files.len() as f64 / total_files * 2.0,
+                },
+                RiskContributor {
+                    label: format!("{} stale file(s)", self.git_stats.stale_files.len()),
+                    points: self.git_stats.stale_files.len() as f64 / total_files * 1.5,
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} high-churn file(s)",
+                        self.git_stats.high_churn_files.len()
+                    ),
+                    points: self.git_stats.high_churn_files.len() as f64 / total_files * 1.0,
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} single-commit file(s)",
+                        self.git_stats.single_commit_files.len()
+                    ),
+                    points: self.git_stats.single_commit_files.len() as f64 / total_files * 0.5,
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} large blob(s) in history",
+                        self.git_stats.large_blobs.len()
+                    ),
+                    // Empty unless large blob detection is enabled
+                    points: (self.git_stats.large_blobs.len() as f64 * 0.1).min(1.0),
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} churning binary file(s)",
+                        self.git_stats.binary_churn_files.len()
+                    ),
+                    points: (self.git_stats.binary_churn_files.len() as f64 * 0.2).min(1.0),
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} scattered commit(s)",
+                        self.git_stats.scattered_commits.len()
+                    ),
+                    points: self.git_stats.scattered_commits.len() as f64
+                        / self.git_stats.total_commits.max(1) as f64
+                        * self.config.risk.scattered_commit_weight,
+                },
+            ],
+        )
+    }
+
+    fn calculate_code_risks(&self) -> RiskComponent {
         let high_complexity_count = self
             .code_stats
             .file_complexity
             .values()
             .filter(|c| c.cyclomatic_complexity > 10.0)
-            .count() as f64;
-        score += (high_complexity_count / self.code_stats.total_files as f64) * 2.0;
+            .count();
+        let dependencies = &self.code_stats.dependency_analysis;
 
-        // Outdated dependencies
-        score += (self
-            .code_stats
-            .dependency_analysis
-            .outdated_dependencies
-            .len() as f64
-            * 0.1)
-            .min(1.0);
-
-        // Vulnerable dependencies
-        score += self
-            .code_stats
-            .dependency_analysis
-            .vulnerable_dependencies
-            .len() as f64
-            * 0.5;
+        RiskComponent::from_contributors(
+            "Code",
+            vec![
+                RiskContributor {
+                    label: format!("{} high-complexity file(s)", high_complexity_count),
+                    points: (high_complexity_count as f64
+                        / self.code_stats.total_files.max(1) as f64)
+                        * 2.0,
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} outdated dependenc(ies)",
+                        dependencies.outdated_dependencies.len()
+                    ),
+                    points: (dependencies.outdated_dependencies.len() as f64 * 0.1).min(1.0),
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} vulnerable dependenc(ies)",
+                        dependencies.vulnerable_dependencies.len()
+                    ),
+                    points: dependencies.vulnerable_dependencies.len() as f64 * 0.5,
+                },
+                RiskContributor {
+                    label: format!(
+                        "{} suspicious dependenc(ies)",
+                        dependencies.suspicious_dependencies.len()
+                    ),
+                    points: dependencies.suspicious_dependencies.len() as f64 * 0.5,
+                },
+            ],
+        )
+    }
 
-        score
+    fn calculate_vulnerability_risks(&self) -> RiskComponent {
+        // Each finding contributes its normalized (0-1) score; the component is capped at 5 points
+        let contributors = self
+            .vulnerabilities
+            .iter()
+            .map(|v| RiskContributor {
+                label: format!(
+                    "{} {}",
+                    &v.commit_id[..v.commit_id.len().min(8)],
+                    v.commit_message.lines().next().unwrap_or_default()
+                ),
+                points: v.risk_score / 10.0,
+            })
+            .collect();
+
+        let mut component = RiskComponent::from_contributors("Vulnerabilities", contributors);
+        component.points = component.points.min(5.0);
+        component
     }
 
-    fn calculate_vulnerability_risks(&self) -> f64 {
+    /// Vulnerability component with each finding weighted by its highest match confidence,
+    /// under the same 5 point cap
+    fn calculate_confidence_weighted_vulnerability_risk(&self) -> f64 {
         self.vulnerabilities
             .iter()
-            .map(|v| v.risk_score / 10.0) // Normalize to 0-1 scale
+            .map(|v| v.risk_score / 10.0 * v.confidence().weight())
             .sum::<f64>()
-            .min(5.0) // Cap at 5 points
+            .min(5.0)
+    }
+}
+
+/// One input to a risk component and the points it added
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskContributor {
+    pub label: String,
+    pub points: f64,
+}
+
+/// Points from one area of the overall score and the inputs that drove them
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskComponent {
+    pub name: String,
+    pub points: f64,
+    /// Contributors ordered by points, largest first
+    pub contributors: Vec<RiskContributor>,
+}
+
+impl RiskComponent {
+    fn from_contributors(name: &str, mut contributors: Vec<RiskContributor>) -> Self {
+        contributors.sort_by(|a, b| {
+            b.points
+                .partial_cmp(&a.points)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Self {
+            name: name.to_string(),
+            points: contributors.iter().map(|c| c.points).sum(),
+            contributors,
+        }
+    }
+
+    /// The `limit` largest contributors that added any points
+    pub fn top_contributors(&self, limit: usize) -> impl Iterator<Item = &RiskContributor> {
+        self.contributors
+            .iter()
+            .filter(|c| c.points > 0.0)
+            .take(limit)
+    }
+}
+
+/// Decomposition of the overall risk score into its git, code and vulnerability parts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskBreakdown {
+    pub git: RiskComponent,
+    pub code: RiskComponent,
+    pub vulnerabilities: RiskComponent,
+    /// Sum of all components, capped at 10
+    pub total: f64,
+    /// Total with each finding's vulnerability points scaled by its confidence
+    pub confidence_weighted_total: f64,
+}
+
+impl RiskBreakdown {
+    pub fn components(&self) -> [&RiskComponent; 3] {
+        [&self.git, &self.code, &self.vulnerabilities]
+    }
+}
+
+/// A single repository's findings within a multi-repository run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryFindings {
+    pub name: String,
+    pub report_path: Option<String>,
+    pub findings: CombinedFindings,
+}
+
+/// Aggregated findings for several repositories analyzed in one run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MultiRepoFindings {
+    pub repositories: Vec<RepositoryFindings>,
+}
+
+impl MultiRepoFindings {
+    /// Repositories ordered by overall risk, riskiest first
+    pub fn ranked(&self) -> Vec<&RepositoryFindings> {
+        let mut ranked: Vec<_> = self.repositories.iter().collect();
+        ranked.sort_by(|a, b| {
+            b.findings
+                .calculate_overall_risk()
+                .partial_cmp(&a.findings.calculate_overall_risk())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        ranked
+    }
+
+    /// Highest risk tier across all repositories
+    pub fn highest_tier(&self) -> RiskTier {
+        self.repositories
+            .iter()
+            .map(|r| r.findings.risk_tier())
+            .fold(RiskTier::Low, |highest, tier| {
+                if tier.is_at_least(highest) {
+                    tier
+                } else {
+                    highest
+                }
+            })
     }
 }
 
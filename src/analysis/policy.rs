@@ -0,0 +1,237 @@
+use super::CombinedFindings;
+use crate::error::{self, CommitRaiderError};
+use crate::patterns::Severity;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Exit status when a policy condition fails and the condition sets none, as for --fail-on-tier
+pub const DEFAULT_POLICY_EXIT_CODE: i32 = 2;
+
+/// Measurable property of a scan that a policy condition bounds
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyMetric {
+    /// Findings with at least one critical match
+    CriticalFindings,
+    /// Findings with at least one high or critical match
+    HighFindings,
+    Findings,
+    /// Distinct CVE ids referenced by the findings
+    CveReferences,
+    /// Overall 0-10 risk score
+    OverallRisk,
+    /// Overall risk with each finding weighted by its confidence
+    ConfidenceWeightedRisk,
+    VulnerableDependencies,
+    /// Likely typosquats and dependency confusion candidates
+    SuspiciousDependencies,
+    /// Packages pulled in only through other dependencies, per the lockfiles
+    TransitiveDependencies,
+    /// Fewest authors who together made more than half of the commits
+    BusFactor,
+    SingleAuthorFiles,
+    /// Files removed from the tip whose commits had security findings
+    RemovedFilesWithFindings,
+}
+
+impl PolicyMetric {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PolicyMetric::CriticalFindings => "critical_findings",
+            PolicyMetric::HighFindings => "high_findings",
+            PolicyMetric::Findings => "findings",
+            PolicyMetric::CveReferences => "cve_references",
+            PolicyMetric::OverallRisk => "overall_risk",
+            PolicyMetric::ConfidenceWeightedRisk => "confidence_weighted_risk",
+            PolicyMetric::VulnerableDependencies => "vulnerable_dependencies",
+            PolicyMetric::SuspiciousDependencies => "suspicious_dependencies",
+            PolicyMetric::TransitiveDependencies => "transitive_dependencies",
+            PolicyMetric::BusFactor => "bus_factor",
+            PolicyMetric::SingleAuthorFiles => "single_author_files",
+            PolicyMetric::RemovedFilesWithFindings => "removed_files_with_findings",
+        }
+    }
+
+    /// Value of this metric for a scan
+    pub fn measure(&self, findings: &CombinedFindings) -> f64 {
+        let with_severity = |accept: fn(&Severity) -> bool| {
+            findings
+                .vulnerabilities
+                .iter()
+                .filter(|v| v.patterns_matched.iter().any(|m| accept(&m.severity)))
+                .count()
+        };
+        let count = match self {
+            PolicyMetric::CriticalFindings => with_severity(|s| matches!(s, Severity::Critical)),
+            PolicyMetric::HighFindings => {
+                with_severity(|s| matches!(s, Severity::Critical | Severity::High))
+            }
+            PolicyMetric::Findings => findings.vulnerabilities.len(),
+            PolicyMetric::CveReferences => {
+                let mut cves: Vec<&String> = findings
+                    .vulnerabilities
+                    .iter()
+                    .flat_map(|v| &v.cve_references)
+                    .collect();
+                cves.sort();
+                cves.dedup();
+                cves.len()
+            }
+            PolicyMetric::OverallRisk => return findings.calculate_overall_risk(),
+            PolicyMetric::ConfidenceWeightedRisk => {
+                return findings.calculate_confidence_weighted_risk()
+            }
+            PolicyMetric::VulnerableDependencies => findings
+                .code_stats
+                .dependency_analysis
+                .vulnerable_dependencies
+                .len(),
+            PolicyMetric::SuspiciousDependencies => findings
+                .code_stats
+                .dependency_analysis
+                .suspicious_dependencies
+                .len(),
+            PolicyMetric::TransitiveDependencies => {
+                findings
+                    .code_stats
+                    .dependency_analysis
+                    .transitive_dependencies
+            }
+            PolicyMetric::BusFactor => bus_factor(findings),
+            PolicyMetric::SingleAuthorFiles => findings.git_stats.single_author_files.len(),
+            PolicyMetric::RemovedFilesWithFindings => findings
+                .removed_file_findings()
+                .iter()
+                .filter(|f| f.findings > 0)
+                .count(),
+        };
+        count as f64
+    }
+}
+
+impl fmt::Display for PolicyMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Fewest authors whose commits add up to more than half of the history; 0 without commits
+fn bus_factor(findings: &CombinedFindings) -> usize {
+    let mut commits: Vec<usize> = findings
+        .git_stats
+        .author_stats
+        .values()
+        .map(|a| a.commits)
+        .collect();
+    commits.sort_unstable_by(|a, b| b.cmp(a));
+    let total: usize = commits.iter().sum();
+    let mut covered = 0;
+    for (i, count) in commits.iter().enumerate() {
+        covered += count;
+        if covered * 2 > total {
+            return i + 1;
+        }
+    }
+    0
+}
+
+/// One fail condition: the scan fails when `metric` is above `max` or below `min`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyCondition {
+    /// Shown when the condition fails; defaults to the bound itself, e.g. "bus_factor >= 2"
+    #[serde(default)]
+    pub name: Option<String>,
+    pub metric: PolicyMetric,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// Exit status when this condition fails [default: 2]
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+impl PolicyCondition {
+    fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("{} <= {} <= {}", min, self.metric, max),
+            (Some(min), None) => format!("{} >= {}", self.metric, min),
+            (None, Some(max)) => format!("{} <= {}", self.metric, max),
+            (None, None) => self.metric.to_string(),
+        }
+    }
+}
+
+/// A condition that failed, with the measured value
+#[derive(Debug, Clone)]
+pub struct PolicyViolation {
+    pub condition: String,
+    pub metric: PolicyMetric,
+    pub value: f64,
+    pub exit_code: i32,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Two decimals are enough for the risk score, and counts print as integers
+        let value = (self.value * 100.0).round() / 100.0;
+        write!(
+            f,
+            "Policy condition \"{}\" failed: {} is {}",
+            self.condition, self.metric, value
+        )
+    }
+}
+
+/// Fail conditions a scan must pass, evaluated in file order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    pub conditions: Vec<PolicyCondition>,
+}
+
+impl Policy {
+    /// Read a JSON policy file, rejecting conditions without a bound
+    pub fn load(path: &Path) -> error::Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            CommitRaiderError::Config(format!(
+                "Failed to read policy file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let policy: Policy = serde_json::from_str(&content).map_err(|e| {
+            CommitRaiderError::Config(format!("Invalid policy file {}: {}", path.display(), e))
+        })?;
+        if let Some(condition) = policy
+            .conditions
+            .iter()
+            .find(|c| c.min.is_none() && c.max.is_none())
+        {
+            return Err(CommitRaiderError::Config(format!(
+                "Policy condition \"{}\" in {} needs a min or max",
+                condition.label(),
+                path.display()
+            )));
+        }
+        Ok(policy)
+    }
+
+    /// The first condition the scan fails, `None` when it passes them all
+    pub fn evaluate(&self, findings: &CombinedFindings) -> Option<PolicyViolation> {
+        self.conditions.iter().find_map(|condition| {
+            let value = condition.metric.measure(findings);
+            let fails = condition.max.is_some_and(|max| value > max)
+                || condition.min.is_some_and(|min| value < min);
+            fails.then(|| PolicyViolation {
+                condition: condition.label(),
+                metric: condition.metric,
+                value,
+                exit_code: condition.exit_code.unwrap_or(DEFAULT_POLICY_EXIT_CODE),
+            })
+        })
+    }
+}
@@ -0,0 +1,160 @@
+use crate::git::{CommitInfo, RepositoryStats};
+use crate::patterns::{
+    age_in_days, Category, Confidence, PatternMatch, Severity, VulnerabilityFinding,
+};
+use chrono::{DateTime, Duration, Timelike, Utc};
+use std::collections::HashMap;
+use tracing::info;
+
+/// Pattern name of timing anomaly matches
+pub const TIMING_ANOMALY_PATTERN: &str = "Timing Anomaly";
+
+/// Authors need this many commits before their hours and timezones form a baseline
+const MIN_AUTHOR_COMMITS: usize = 20;
+
+/// Local hours on either side of a commit searched for the author's other commits
+const HOUR_WINDOW: u32 = 2;
+
+/// Risk score of a finding that carries nothing but timing anomalies
+const ANOMALY_RISK_SCORE: f64 = 2.0;
+
+/// An author's commits per local hour of day and per timezone offset
+#[derive(Default)]
+struct AuthorProfile {
+    commits: usize,
+    hours: [usize; 24],
+    offsets: HashMap<i32, usize>,
+}
+
+/// Flag commits whose local time or timezone is unusual for their author. Anomalies are added
+/// as low-confidence matches to the commit's finding, creating one when the commit has none;
+/// the ages of created findings are measured from `reference_date`.
+pub fn annotate(
+    findings: &mut Vec<VulnerabilityFinding>,
+    git_stats: &RepositoryStats,
+    reference_date: DateTime<Utc>,
+) {
+    let mut profiles: HashMap<&str, AuthorProfile> = HashMap::new();
+    for commit in &git_stats.commit_history {
+        let profile = profiles.entry(commit.author_email.as_str()).or_default();
+        profile.commits += 1;
+        profile.hours[local_hour(commit.authored_date, commit.author_tz_offset) as usize] += 1;
+        *profile.offsets.entry(commit.author_tz_offset).or_insert(0) += 1;
+    }
+
+    let mut finding_index: HashMap<String, usize> = findings
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (f.commit_id.clone(), i))
+        .collect();
+
+    let mut anomalous_commits = 0;
+    for commit in &git_stats.commit_history {
+        let reasons = anomaly_reasons(commit, &profiles[commit.author_email.as_str()]);
+        if reasons.is_empty() {
+            continue;
+        }
+        anomalous_commits += 1;
+
+        let matches = reasons.into_iter().map(anomaly_match);
+        match finding_index.get(&commit.id) {
+            Some(&i) => findings[i].patterns_matched.extend(matches),
+            None => {
+                finding_index.insert(commit.id.clone(), findings.len());
+                findings.push(VulnerabilityFinding {
+                    commit_id: commit.id.clone(),
+                    commit_message: commit.message.clone(),
+                    author: commit.author.clone(),
+                    date: commit.authored_date,
+                    age_days: age_in_days(commit.authored_date, reference_date),
+                    files_changed: commit.files_changed.clone(),
+                    patterns_matched: matches.collect(),
+                    risk_score: ANOMALY_RISK_SCORE,
+                    cve_references: Vec::new(),
+                    trailers: commit.trailers.clone(),
+                    related_commits: Vec::new(),
+                });
+            }
+        }
+    }
+
+    info!("Found {} commits with timing anomalies", anomalous_commits);
+}
+
+fn anomaly_reasons(commit: &CommitInfo, profile: &AuthorProfile) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    // The same person authoring and committing from two timezones at once is suspicious
+    if commit.author_email == commit.committer_email
+        && commit.author_tz_offset != commit.committer_tz_offset
+    {
+        reasons.push(format!(
+            "Author timezone {} differs from committer timezone {}",
+            format_offset(commit.author_tz_offset),
+            format_offset(commit.committer_tz_offset)
+        ));
+    }
+
+    if profile.commits < MIN_AUTHOR_COMMITS {
+        return reasons;
+    }
+    let other_commits = profile.commits - 1;
+
+    if profile.offsets[&commit.author_tz_offset] == 1 {
+        reasons.push(format!(
+            "Timezone {} is not used in any of the author's {} other commits",
+            format_offset(commit.author_tz_offset),
+            other_commits
+        ));
+    }
+
+    let hour = local_hour(commit.authored_date, commit.author_tz_offset);
+    let nearby: usize = (0..24)
+        .filter(|&h| hour_distance(h, hour) <= HOUR_WINDOW)
+        .map(|h| profile.hours[h as usize])
+        .sum();
+    if nearby == 1 {
+        reasons.push(format!(
+            "Authored at {:02}:00 local time, outside the hours of the author's {} other commits",
+            hour, other_commits
+        ));
+    }
+
+    reasons
+}
+
+fn anomaly_match(reason: String) -> PatternMatch {
+    PatternMatch {
+        pattern_name: TIMING_ANOMALY_PATTERN.to_string(),
+        matched_text: reason.clone(),
+        severity: Severity::Low,
+        category: Category::Generic,
+        file_path: "commit_metadata".to_string(),
+        line_number: None,
+        context: reason,
+        cve_references: Vec::new(),
+        cwe: None,
+        remediation: None,
+        confidence: Confidence::Low,
+    }
+}
+
+fn local_hour(date: DateTime<Utc>, offset_minutes: i32) -> u32 {
+    (date + Duration::minutes(offset_minutes as i64)).hour()
+}
+
+/// Distance between two hours of the day, wrapping around midnight
+fn hour_distance(a: u32, b: u32) -> u32 {
+    let distance = a.abs_diff(b);
+    distance.min(24 - distance)
+}
+
+fn format_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!(
+        "UTC{}{:02}:{:02}",
+        sign,
+        minutes.abs() / 60,
+        minutes.abs() % 60
+    )
+}
@@ -0,0 +1,25 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+
+/// Exit status after an interrupted scan, as a shell reports for a process killed by SIGINT
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Listen for Ctrl-C: the first one asks the running phases to stop so a partial report can
+/// still be written, a second one exits immediately
+pub fn listen() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if CANCELLED.swap(true, Ordering::SeqCst) {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+            warn!("Interrupted, stopping the current phase to write a partial report (press Ctrl-C again to abort)");
+        }
+    });
+}
+
+/// Whether the scan was interrupted; long-running phases check this between units of work
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::Relaxed)
+}
@@ -1,8 +1,14 @@
+use crate::analysis::{ChurnBucketing, SeverityScale};
+use crate::error::{self, CommitRaiderError};
+use crate::git::{FormattingCommits, SamplingStrategy};
+use crate::patterns::Category;
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     pub patterns: PatternConfig,
     pub analysis: AnalysisConfig,
@@ -10,45 +16,192 @@ pub struct Config {
     pub risk: RiskConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PatternConfig {
     pub custom_patterns: Vec<CustomPattern>,
+    /// JSON file with more custom patterns; its patterns override same-named ones
+    pub pattern_file: Option<PathBuf>,
+    /// Categories whose patterns are loaded, including custom category names
     pub enabled_categories: Vec<String>,
     pub severity_weights: HashMap<String, f64>,
+    /// Phrases that, when found as whole words near a match, indicate it is probably not a
+    /// real fix
+    pub negative_keywords: Vec<String>,
+    /// Number of characters on each side of a match searched for negative keywords
+    pub negative_context_window: usize,
+    /// Multiplier applied to a match's score when negative context is found (0.0 drops the match)
+    pub negative_context_penalty: f64,
+    /// Remediation text per CWE id, overriding or extending the built-in table
+    pub cwe_remediation: HashMap<String, String>,
+    /// Commit messages are scanned up to this many bytes; 0 scans them whole
+    pub max_message_length: usize,
+    /// CVE references with an earlier year are treated as false positives
+    pub min_cve_year: i32,
+    /// Severity override per pattern name, e.g. `"Cross-Site Scripting": "high"`
+    pub severity_map: HashMap<String, String>,
+    /// Names of patterns dropped from the effective set, e.g. a noisy built-in
+    pub disabled_patterns: Vec<String>,
+    /// Threads of a pool reserved for the pattern scan; 0 scans on the global pool
+    pub scan_threads: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CustomPattern {
     pub name: String,
     pub pattern: String,
     pub severity: String,
+    /// Built-in category name or any custom name to group findings under
     pub category: String,
     pub description: String,
+    /// Regex flags such as "is" or "m"; see `VulnerabilityPattern::flags`
+    #[serde(default)]
+    pub flags: Option<String>,
+    /// Require standalone tokens; see `VulnerabilityPattern::standalone`
+    #[serde(default)]
+    pub standalone: bool,
+    /// Ignore shorter matches; see `VulnerabilityPattern::min_match_length`
+    #[serde(default)]
+    pub min_match_length: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AnalysisConfig {
     pub max_commits: Option<usize>,
     pub include_merge_commits: bool,
     pub stale_threshold_days: u64,
     pub complexity_threshold: f64,
     pub parallel_processing: bool,
+    /// Inspect branch reflogs for non-fast-forward updates (force pushes, rebases, resets)
+    pub detect_history_rewrites: bool,
+    /// File extensions analyzed for complexity; unknown languages use generic heuristics
+    pub complexity_extensions: Vec<String>,
+    /// Base names of CommitRaider reports, excluded so committed reports don't produce findings
+    pub report_artifact_names: Vec<String>,
+    /// Gitignore-style patterns left out of line and file counts (lock files, vendored code);
+    /// dependency analysis still reads them
+    pub line_count_exclusions: Vec<String>,
+    /// Commit trailers kept on each commit, matched case-insensitively; empty keeps all
+    pub trailer_keys: Vec<String>,
+    /// Dependencies never flagged as typosquats or confusion candidates, e.g. internal
+    /// packages; a trailing `*` matches any suffix (`acme-*`)
+    pub dependency_allowlist: Vec<String>,
+    /// SPDX expression of the project's license, against which dependency licenses are
+    /// checked; read from Cargo.toml or package.json when unset
+    pub project_license: Option<String>,
+    /// Files touched by at most this many commits are reported as single-commit files
+    pub single_commit_threshold: usize,
+    /// Commits changing at least this many files are reported as scattered changes
+    pub scattered_commit_threshold: usize,
+    /// Maximum number of scattered commits kept in the report
+    pub scattered_commit_limit: usize,
+    /// Binary files changed at least this many times are reported as churning binaries
+    pub binary_churn_threshold: usize,
+    /// Globs of CI pipelines and security policy files (workflows, CODEOWNERS, SECURITY.md,
+    /// dependabot config); commits changing them are reported for supply-chain review
+    pub meta_file_globs: Vec<String>,
+    /// Walk commits reachable from tags instead of HEAD
+    pub scan_tags: bool,
+    /// Restrict tag scanning to this tag
+    pub tag: Option<String>,
+    /// Analyze only this commit (full or abbreviated id, or any revision expression)
+    pub commit: Option<String>,
+    /// Walk history from this branch, tag or commit instead of HEAD
+    pub revision: Option<String>,
+    /// Git directory to open instead of discovering one from the repository path
+    pub git_dir: Option<PathBuf>,
+    /// Working tree paired with `git_dir`; defaults to the repository path
+    pub work_tree: Option<PathBuf>,
+    /// Walk history for blobs at or above `large_blob_threshold_bytes`
+    pub detect_large_blobs: bool,
+    pub large_blob_threshold_bytes: u64,
+    /// Maximum number of large blobs kept in the report
+    pub large_blob_limit: usize,
+    /// Flag commits authored at hours or in timezones unusual for their author
+    pub detect_timing_anomalies: bool,
+    /// Collapse findings sharing a pattern and files within `duplicate_window_days` into one
+    pub group_duplicates: bool,
+    pub duplicate_window_days: i64,
+    /// List changed files with the `git` binary instead of in-process libgit2 diffs
+    pub use_git_subprocess: bool,
+    /// Scan commits batch by batch and drop them afterwards instead of keeping the full
+    /// history, bounding memory at the cost of history-wide views (timing, heatmap, trend)
+    pub streaming: bool,
+    /// Maximum number of concurrent `git` subprocesses used to list changed files
+    pub git_concurrency: usize,
+    /// Timeout in seconds for each `git` subprocess
+    pub git_timeout_secs: u64,
+    /// Directory for caches reused across runs, such as per-file complexity metrics
+    pub cache_dir: Option<PathBuf>,
+    /// Which commits to keep when the history exceeds the full-analysis limit
+    pub sampling: SamplingStrategy,
+    /// How whitespace-only commits count toward churn and authorship; detecting them costs
+    /// a second diff per commit
+    pub formatting_commits: FormattingCommits,
+    /// Case-insensitive regexes matched against commit author names and emails; matching
+    /// authors are bots whose commits are scanned but left out of authorship and churn stats
+    pub bot_author_patterns: Vec<String>,
+    /// Count bot commits in authorship and churn stats like any other
+    pub include_bots: bool,
+    /// Columns a leading tab advances to when measuring Python indentation
+    pub python_tab_width: usize,
+    /// Skip ignored files when walking the working tree: `.gitignore` files at any depth,
+    /// `.git/info/exclude`, the global `core.excludesFile` and `.ignore` files
+    pub respect_gitignore: bool,
+    /// Languages (as tokei names them) counted as documentation or plain text rather than code
+    pub prose_languages: Vec<String>,
+    /// With `--stats`, repositories whose lines are more than this share prose are not
+    /// pattern scanned, since matches in prose are mostly noise; 1.0 always scans
+    pub max_prose_share: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OutputConfig {
     pub default_format: String,
     pub include_stats: bool,
     pub max_items_per_section: usize,
     pub color_output: bool,
+    /// Directory with custom Tera templates overriding the embedded ones
+    pub template_dir: Option<PathBuf>,
+    /// Serialize JSON output on a single line instead of pretty-printing it
+    pub compact_json: bool,
+    /// Heatmap churn buckets as fifths of the busiest file or as quantiles of all files
+    pub heatmap_bucketing: ChurnBucketing,
+    /// Path components down to which findings and complexity are rolled up per directory
+    pub directory_depth: usize,
+    /// IANA timezone dates are displayed in, e.g. "Europe/Berlin"
+    pub timezone: String,
+    /// Findings from commits at most this many days old are highlighted as recent
+    pub recent_days: i64,
+    /// Highest-risk findings rendered in the HTML report; 0 renders them all
+    pub max_report_findings: usize,
+    /// Fixed generation date of reports, as Unix seconds (like `SOURCE_DATE_EPOCH`), RFC 3339
+    /// or YYYY-MM-DD, so identical scans produce identical reports; the current time if unset
+    pub report_date: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RiskConfig {
     pub single_author_weight: f64,
     pub stale_file_weight: f64,
     pub complexity_weight: f64,
     pub vulnerability_weight: f64,
+    /// Per-finding score multiplier is `files_changed^file_count_exponent * size_factor *
+    /// sensitivity`, where `size_factor` is `small_commit_boost` for commits changing at most
+    /// `small_commit_lines` lines, `large_commit_penalty` for commits changing at least
+    /// `large_commit_lines` lines, and 1.0 otherwise, and `sensitivity` is the highest
+    /// `path_sensitivity` multiplier among the changed files (1.0 when none match).
+    pub file_count_exponent: f64,
+    pub small_commit_lines: usize,
+    pub small_commit_boost: f64,
+    pub large_commit_lines: usize,
+    pub large_commit_penalty: f64,
+    /// Score multiplier per path glob, e.g. `"**/auth/**": 2.0` or `"docs/**": 0.5`
+    pub path_sensitivity: HashMap<String, f64>,
+    /// Git history points per share of commits that are scattered changes; 0.0 reports them
+    /// without affecting the score
+    pub scattered_commit_weight: f64,
+    /// Score thresholds of the severity labels and risk tiers
+    pub severity_scale: SeverityScale,
 }
 
 impl Default for Config {
@@ -63,13 +216,27 @@ impl Default for Config {
         Self {
             patterns: PatternConfig {
                 custom_patterns: Vec::new(),
-                enabled_categories: vec![
-                    "MemorySafety".to_string(),
-                    "WebSecurity".to_string(),
-                    "Cryptography".to_string(),
-                    "CodeInjection".to_string(),
-                ],
+                pattern_file: None,
+                enabled_categories: Category::BUILTIN
+                    .iter()
+                    .map(|c| c.name().to_string())
+                    .collect(),
                 severity_weights,
+                negative_keywords: vec![
+                    "not a vulnerability".to_string(),
+                    "false positive".to_string(),
+                    "revert".to_string(),
+                    "typo".to_string(),
+                    "test".to_string(),
+                ],
+                negative_context_window: 40,
+                negative_context_penalty: 0.5,
+                cwe_remediation: HashMap::new(),
+                max_message_length: 64 * 1024,
+                min_cve_year: 1999,
+                severity_map: HashMap::new(),
+                disabled_patterns: Vec::new(),
+                scan_threads: 0,
             },
             analysis: AnalysisConfig {
                 max_commits: None,
@@ -77,23 +244,229 @@ impl Default for Config {
                 stale_threshold_days: 365,
                 complexity_threshold: 10.0,
                 parallel_processing: true,
+                detect_history_rewrites: false,
+                complexity_extensions: [
+                    "rs", "py", "js", "ts", "java", "cpp", "c", "h", "hpp", "go", "rb", "php", "cs",
+                ]
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+                report_artifact_names: vec!["report_commit_raider".to_string()],
+                line_count_exclusions: [
+                    "Cargo.lock",
+                    "package-lock.json",
+                    "yarn.lock",
+                    "pnpm-lock.yaml",
+                    "Gemfile.lock",
+                    "poetry.lock",
+                    "composer.lock",
+                    "go.sum",
+                    "vendor",
+                    "node_modules",
+                    "third_party",
+                ]
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+                trailer_keys: [
+                    "Signed-off-by",
+                    "Reviewed-by",
+                    "Acked-by",
+                    "Tested-by",
+                    "Reported-by",
+                    "Co-authored-by",
+                    "Fixes",
+                    "CVE",
+                ]
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+                dependency_allowlist: Vec::new(),
+                project_license: None,
+                single_commit_threshold: 1,
+                scattered_commit_threshold: 50,
+                scattered_commit_limit: 25,
+                binary_churn_threshold: 3,
+                meta_file_globs: [
+                    ".github/workflows/**",
+                    ".github/actions/**",
+                    ".github/dependabot.yml",
+                    ".github/dependabot.yaml",
+                    "**/CODEOWNERS",
+                    "**/SECURITY.md",
+                    ".gitlab-ci.yml",
+                    ".gitlab/**",
+                    ".circleci/**",
+                    ".buildkite/**",
+                    ".travis.yml",
+                    "azure-pipelines.yml",
+                    "bitbucket-pipelines.yml",
+                    "**/Jenkinsfile",
+                    ".pre-commit-config.yaml",
+                ]
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect(),
+                scan_tags: false,
+                tag: None,
+                commit: None,
+                revision: None,
+                git_dir: None,
+                work_tree: None,
+                detect_large_blobs: false,
+                large_blob_threshold_bytes: 1024 * 1024,
+                large_blob_limit: 25,
+                detect_timing_anomalies: false,
+                group_duplicates: false,
+                duplicate_window_days: 30,
+                use_git_subprocess: false,
+                streaming: false,
+                git_concurrency: 32,
+                git_timeout_secs: 30,
+                cache_dir: None,
+                sampling: SamplingStrategy::Recent,
+                formatting_commits: FormattingCommits::Count,
+                bot_author_patterns: [
+                    r"\[bot\]",
+                    r"^dependabot",
+                    r"^renovate",
+                    r"^github-actions",
+                    r"^greenkeeper",
+                    r"^snyk-bot",
+                    r"^pre-commit-ci",
+                    r"^mergify",
+                    r"@renovateapp\.com$",
+                    r"^noreply@github\.com$",
+                ]
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+                include_bots: false,
+                python_tab_width: 8,
+                respect_gitignore: true,
+                prose_languages: [
+                    "Markdown",
+                    "Text",
+                    "ReStructuredText",
+                    "AsciiDoc",
+                    "Org",
+                    "Tex",
+                ]
+                .iter()
+                .map(|language| language.to_string())
+                .collect(),
+                max_prose_share: 0.9,
             },
             output: OutputConfig {
                 default_format: "html".to_string(),
                 include_stats: true,
                 max_items_per_section: 50,
                 color_output: true,
+                template_dir: None,
+                compact_json: false,
+                heatmap_bucketing: ChurnBucketing::Linear,
+                directory_depth: 2,
+                timezone: "UTC".to_string(),
+                recent_days: 30,
+                max_report_findings: 0,
+                report_date: None,
             },
             risk: RiskConfig {
                 single_author_weight: 2.0,
                 stale_file_weight: 1.5,
                 complexity_weight: 2.0,
                 vulnerability_weight: 3.0,
+                file_count_exponent: 0.5,
+                small_commit_lines: 20,
+                small_commit_boost: 1.5,
+                large_commit_lines: 1000,
+                large_commit_penalty: 0.5,
+                path_sensitivity: HashMap::new(),
+                scattered_commit_weight: 0.0,
+                severity_scale: SeverityScale::default(),
             },
         }
     }
 }
 
+impl AnalysisConfig {
+    /// Whether a path looks like a report generated by this tool
+    pub fn is_report_artifact(&self, path: &str) -> bool {
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(path);
+
+        let is_report_extension = [".html", ".json", ".db", ".md"]
+            .iter()
+            .any(|ext| file_name.ends_with(ext));
+
+        is_report_extension
+            && self
+                .report_artifact_names
+                .iter()
+                .any(|base| !base.is_empty() && file_name.starts_with(base.as_str()))
+    }
+
+    /// Walker over the files below `root`, skipping hidden files and, with
+    /// `respect_gitignore`, every ignore source
+    pub fn file_walker(&self, root: &Path) -> ignore::WalkBuilder {
+        let mut walker = ignore::WalkBuilder::new(root);
+        walker
+            .git_ignore(self.respect_gitignore)
+            .git_exclude(self.respect_gitignore)
+            .git_global(self.respect_gitignore)
+            .ignore(self.respect_gitignore)
+            .parents(self.respect_gitignore);
+        walker
+    }
+}
+
+impl OutputConfig {
+    /// The display timezone, rejecting names outside the IANA database
+    pub fn timezone(&self) -> error::Result<chrono_tz::Tz> {
+        self.timezone.parse().map_err(|_| {
+            CommitRaiderError::Config(format!(
+                "Unknown timezone '{}', expected an IANA name such as Europe/Berlin",
+                self.timezone
+            ))
+        })
+    }
+
+    /// The fixed report generation date, if any
+    pub fn report_date(&self) -> error::Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let Some(date) = self.report_date.as_deref().map(str::trim) else {
+            return Ok(None);
+        };
+        let parsed = if let Ok(seconds) = date.parse::<i64>() {
+            chrono::DateTime::from_timestamp(seconds, 0)
+        } else if let Ok(date) = chrono::DateTime::parse_from_rfc3339(date) {
+            Some(date.with_timezone(&chrono::Utc))
+        } else {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .map(|midnight| midnight.and_utc())
+        };
+        parsed.map(Some).ok_or_else(|| {
+            CommitRaiderError::Config(format!(
+                "Invalid report date '{}', expected Unix seconds, RFC 3339 or YYYY-MM-DD",
+                date
+            ))
+        })
+    }
+
+    /// The moment a scan is reported at: `report_date` when set, otherwise now. Finding ages
+    /// and stored run timestamps are measured from it, so a pinned date reproduces them.
+    /// An invalid `report_date` is rejected at startup and falls back to now here.
+    pub fn reference_date(&self) -> chrono::DateTime<chrono::Utc> {
+        self.report_date()
+            .ok()
+            .flatten()
+            .unwrap_or_else(chrono::Utc::now)
+    }
+}
+
 impl Config {
     pub fn load() -> Result<Self> {
         // Load config from yaml/toml whatever file
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+/// Errors returned by the public analysis and reporting APIs
+#[derive(Debug, thiserror::Error)]
+pub enum CommitRaiderError {
+    #[error("Failed to open repository at {path}\n Is it really a git repo?")]
+    RepoOpen {
+        path: PathBuf,
+        #[source]
+        source: git2::Error,
+    },
+
+    #[error("Failed to compile pattern: {name}")]
+    PatternCompile {
+        name: String,
+        #[source]
+        source: Box<fancy_regex::Error>,
+    },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to render report: {0}")]
+    Render(String),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, CommitRaiderError>;
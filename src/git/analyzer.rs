@@ -1,69 +1,162 @@
 use super::*;
-use anyhow::{Context, Result};
+use crate::analysis::code_analyzer::{is_binary_content, is_binary_path};
+use crate::cancel;
+use crate::config::AnalysisConfig;
+use crate::error::{self, CommitRaiderError};
+use crate::profile;
+use anyhow::Result;
 use chrono::{TimeZone, Utc};
 use git2::{Repository, Sort};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use indicatif::{ProgressBar, ProgressStyle};
+use regex::{RegexSet, RegexSetBuilder};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 pub struct GitAnalyzer {
     repo: Repository,
     path: PathBuf,
+    config: AnalysisConfig,
+    /// Compiled `meta_file_globs`
+    meta_files: GlobSet,
+    /// Compiled `bot_author_patterns`, `None` with `include_bots`
+    bot_authors: Option<RegexSet>,
 }
 
 const MAX_COMMITS_FOR_FULL_ANALYSIS: usize = 20000;
 
+/// Untracked files larger than this are assumed to be data or build output and not scanned
+const MAX_UNTRACKED_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Files touched by a commit plus its line counts
+#[derive(Debug, Clone, Default)]
+struct ChangedFiles {
+    files: Vec<String>,
+    insertions: usize,
+    deletions: usize,
+}
+
 impl GitAnalyzer {
-    pub fn new(path: &Path) -> Result<Self> {
-        let repo = Repository::open(path).with_context(|| {
-            format!(
-                "Failed to open repository at {}\n Is it really a git repo?",
-                path.display()
-            )
-        })?;
+    pub fn new(path: &Path, config: &AnalysisConfig) -> error::Result<Self> {
+        let repo = Self::open_repository(path, config)?;
+
+        info!(
+            "Opened Git repository at {} (git dir {})",
+            path.display(),
+            repo.path().display()
+        );
 
-        info!("Opened Git repository at {}", path.display());
+        let mut meta_files = GlobSetBuilder::new();
+        for glob in &config.meta_file_globs {
+            meta_files.add(Glob::new(glob).map_err(|e| {
+                CommitRaiderError::Config(format!("meta file glob '{}': {}", glob, e))
+            })?);
+        }
+        let meta_files = meta_files
+            .build()
+            .map_err(|e| CommitRaiderError::Config(format!("meta file globs: {}", e)))?;
+
+        let bot_authors = if config.include_bots {
+            None
+        } else {
+            Some(
+                RegexSetBuilder::new(&config.bot_author_patterns)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| {
+                        CommitRaiderError::Config(format!("bot author patterns: {}", e))
+                    })?,
+            )
+        };
 
         Ok(Self {
             repo,
             path: path.to_path_buf(),
+            config: config.clone(),
+            meta_files,
+            bot_authors,
         })
     }
 
-    pub async fn analyze(&self) -> Result<RepositoryStats> {
-        let mut stats = RepositoryStats {
-            path: self.path.display().to_string(),
-            total_commits: 0,
-            total_files: 0,
-            total_authors: 0,
-            first_commit: Utc::now(),
-            last_commit: Utc.timestamp_opt(0, 0).single().unwrap(),
-            branches: Vec::new(),
-            commit_history: Vec::new(),
-            file_history: HashMap::new(),
-            author_stats: HashMap::new(),
-            single_author_files: Vec::new(),
-            stale_files: Vec::new(),
-            high_churn_files: Vec::new(),
-            remote_url: None,
-            repository_type: RepositoryType::Local,
-            test_analysis: TestAnalysis {
-                total_test_files: 0,
-                test_directories: Vec::new(),
-                test_frameworks: HashSet::new(),
-                has_regression_tests: false,
-                test_patterns_found: Vec::new(),
-                test_coverage_indicators: Vec::new(),
-            },
+    /// Open the repository at `path`, or the configured git dir with `work_tree` (or `path`)
+    /// as its working tree. Without a git dir, discovery from `path` finds the repository of
+    /// a subdirectory or linked worktree when opening `path` directly fails.
+    fn open_repository(path: &Path, config: &AnalysisConfig) -> error::Result<Repository> {
+        let Some(git_dir) = &config.git_dir else {
+            return Repository::open(path)
+                .or_else(|_| Repository::discover(path))
+                .map_err(|source| CommitRaiderError::RepoOpen {
+                    path: path.to_path_buf(),
+                    source,
+                });
         };
 
+        let repo_open_error = |source| CommitRaiderError::RepoOpen {
+            path: git_dir.clone(),
+            source,
+        };
+        let repo = Repository::open_bare(git_dir).map_err(repo_open_error)?;
+        let work_tree = config.work_tree.as_deref().unwrap_or(path);
+        // Scanning the git dir itself means there is no work tree to attach
+        if work_tree != git_dir.as_path() {
+            repo.set_workdir(work_tree, false)
+                .map_err(repo_open_error)?;
+        }
+        Ok(repo)
+    }
+
+    /// Working tree whose files are analyzed, `None` for bare repositories
+    pub fn work_tree(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    pub async fn analyze(&self) -> Result<RepositoryStats> {
+        self.analyze_streaming(|_| {}).await
+    }
+
+    /// Analyze the history, handing each batch of commits to `on_batch` as it is read.
+    /// With `streaming` enabled batches are dropped afterwards instead of being kept in
+    /// `commit_history`, so memory stays bounded by the aggregate stats.
+    pub async fn analyze_streaming(
+        &self,
+        mut on_batch: impl FnMut(&[CommitInfo]),
+    ) -> Result<RepositoryStats> {
+        let mut stats = self.empty_stats();
+
+        if stats.is_shallow {
+            warn!(
+                "{} is a shallow clone: history is incomplete, first-commit and stale file metrics are unreliable",
+                self.path.display()
+            );
+        }
+
         self.analyze_branches(&mut stats)?;
-        self.analyze_commits(&mut stats).await?;
+        if self.is_empty() {
+            warn!("{} has no commits to analyze", self.path.display());
+            // Keep the timeline ordered instead of running from now back to the epoch
+            stats.last_commit = stats.first_commit;
+            return Ok(stats);
+        }
+        self.analyze_commits(&mut stats, &mut on_batch).await?;
         self.calculate_derived_stats(&mut stats)?;
+        // These walk the history or object database again, too slow to wait for once interrupted
+        if !cancel::is_cancelled() {
+            // A single commit's history says nothing about which files were removed
+            if self.config.commit.is_none() {
+                self.detect_removed_files(&mut stats)?;
+            }
+            stats.test_analysis = test_detection::analyze_tests(&self.repo)?;
+            if self.config.detect_history_rewrites {
+                self.detect_history_rewrites(&mut stats)?;
+            }
+            if self.config.detect_large_blobs {
+                self.detect_large_blobs(&mut stats)?;
+            }
+        }
         stats.remote_url = self.detect_remote_url();
         stats.repository_type = self.detect_repository_type(&stats.remote_url);
 
@@ -75,7 +168,104 @@ impl GitAnalyzer {
         Ok(stats)
     }
 
+    /// Stats for uncommitted changes recorded as a single pseudo-commit, plus the lines they add.
+    /// `staged_only` diffs HEAD against the index, otherwise against the working tree.
+    /// Untracked files are not part of either diff; `include_untracked` scans their contents
+    /// too, attributing their lines to `untracked: <path>`.
+    pub fn analyze_working_tree(
+        &self,
+        staged_only: bool,
+        include_untracked: bool,
+    ) -> Result<(RepositoryStats, Vec<AddedLine>)> {
+        let mut stats = self.empty_stats();
+        self.analyze_branches(&mut stats)?;
+
+        // With an unborn HEAD everything in the index or working tree is new
+        let head_tree = self.repo.head().and_then(|head| head.peel_to_tree()).ok();
+        let mut options = git2::DiffOptions::new();
+        let diff = if staged_only {
+            self.repo
+                .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))?
+        } else {
+            self.repo
+                .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))?
+        };
+        let diff_stats = diff.stats()?;
+        let (mut files_changed, mut added_lines) = self.diff_added_lines(&diff)?;
+        let mut insertions = diff_stats.insertions();
+        if include_untracked {
+            let (untracked_files, untracked_lines) = self.untracked_added_lines()?;
+            insertions += untracked_lines.len();
+            files_changed.extend(untracked_files);
+            added_lines.extend(untracked_lines);
+        }
+
+        let (author, author_email) = match self.repo.signature() {
+            Ok(signature) => (
+                normalize::normalize_signature_name(
+                    signature.name_bytes(),
+                    signature.email_bytes(),
+                ),
+                String::from_utf8_lossy(signature.email_bytes()).to_string(),
+            ),
+            Err(_) => (UNCOMMITTED_COMMIT_ID.to_string(), String::new()),
+        };
+        let now = Utc::now();
+        let tz_offset = chrono::Local::now().offset().local_minus_utc() / 60;
+        let commit = CommitInfo {
+            id: UNCOMMITTED_COMMIT_ID.to_string(),
+            message: if staged_only {
+                "Staged changes".to_string()
+            } else {
+                "Uncommitted working tree changes".to_string()
+            },
+            author: author.clone(),
+            author_email: author_email.clone(),
+            committer: author,
+            committer_email: author_email,
+            authored_date: now,
+            committed_date: now,
+            author_tz_offset: tz_offset,
+            committer_tz_offset: tz_offset,
+            files_changed,
+            insertions,
+            deletions: diff_stats.deletions(),
+            branch: stats.head_branch.clone(),
+            tag: None,
+            trailers: HashMap::new(),
+            formatting_only: false,
+        };
+
+        stats.first_commit = now;
+        stats.last_commit = now;
+        stats.update_author_stats(&commit);
+        stats.update_file_history(&commit);
+        stats.commit_history.push(commit);
+        stats.total_commits = 1;
+        stats.total_authors = stats.author_stats.len();
+        stats.total_files = stats.file_history.len();
+        stats.remote_url = self.detect_remote_url();
+        stats.repository_type = self.detect_repository_type(&stats.remote_url);
+
+        info!(
+            "Working tree analysis complete: {} files changed, {} lines added",
+            stats.total_files,
+            added_lines.len()
+        );
+
+        Ok((stats, added_lines))
+    }
+
+    /// Stats with no history recorded yet
+    fn empty_stats(&self) -> RepositoryStats {
+        RepositoryStats {
+            is_shallow: self.repo.is_shallow(),
+            ..RepositoryStats::empty(self.path.display().to_string())
+        }
+    }
+
     fn analyze_branches(&self, stats: &mut RepositoryStats) -> Result<()> {
+        let started = Instant::now();
         let branches = self.repo.branches(Some(BranchType::Local))?;
 
         for branch in branches {
@@ -85,14 +275,85 @@ impl GitAnalyzer {
             }
         }
 
+        // A configured revision replaces HEAD, so its branch is the one being analyzed
+        let head = match &self.config.revision {
+            Some(spec) => self.repo.resolve_reference_from_short_name(spec).ok(),
+            None => self.repo.head().ok(),
+        };
+        stats.head_branch = head
+            .filter(|head| head.is_branch() || head.is_remote())
+            .and_then(|head| head.shorthand().map(str::to_string));
+
         debug!("Found {} branches", stats.branches.len());
+        profile::record(
+            "Branch analysis",
+            "branches",
+            started.elapsed(),
+            stats.branches.len(),
+        );
         Ok(())
     }
 
-    async fn analyze_commits(&self, stats: &mut RepositoryStats) -> Result<()> {
+    /// Whether a commit was authored by a bot, per `bot_author_patterns`
+    fn is_bot(&self, commit: &CommitInfo) -> bool {
+        self.bot_authors.as_ref().is_some_and(|bots| {
+            bots.is_match(&commit.author) || bots.is_match(&commit.author_email)
+        })
+    }
+
+    /// Whether the repository has no commits yet (freshly initialized, HEAD unborn)
+    pub fn is_empty(&self) -> bool {
+        self.repo.is_empty().unwrap_or(false)
+    }
+
+    /// Number of commits an analysis would process, after sampling large histories
+    pub fn count_commits(&self) -> Result<usize> {
+        if self.is_empty() {
+            return Ok(0);
+        }
+        let mut total = 0;
+        for oid in self.commit_revwalk()? {
+            oid?;
+            total += 1;
+        }
+        Ok(total.min(MAX_COMMITS_FOR_FULL_ANALYSIS))
+    }
+
+    /// Revwalk over the commits selected for analysis: tag targets when tag scanning, else the
+    /// configured revision or HEAD
+    fn commit_revwalk(&self) -> Result<git2::Revwalk<'_>> {
         let mut revwalk = self.repo.revwalk()?;
 
-        if let Ok(head) = self.repo.head() {
+        if let Some(spec) = &self.config.commit {
+            let commit = self.resolve_commit(spec)?;
+            revwalk.push(commit.id())?;
+            // Hiding the parents leaves exactly this commit in the walk
+            for parent in commit.parent_ids() {
+                revwalk.hide(parent)?;
+            }
+            info!("Analyzing single commit {}", commit.id());
+        } else if self.config.scan_tags {
+            let tags = self.release_tags()?;
+            let selected: Vec<_> = match &self.config.tag {
+                Some(tag) => tags.iter().filter(|(name, _)| name == tag).collect(),
+                None => tags.iter().collect(),
+            };
+            if selected.is_empty() {
+                anyhow::bail!(
+                    "No matching tags found in {} (requested: {})",
+                    self.path.display(),
+                    self.config.tag.as_deref().unwrap_or("any")
+                );
+            }
+            for (_, target) in &selected {
+                revwalk.push(*target)?;
+            }
+            info!("Analyzing commits reachable from {} tag(s)", selected.len());
+        } else if let Some(spec) = &self.config.revision {
+            let commit = self.resolve_commit(spec)?;
+            revwalk.push(commit.id())?;
+            info!("Analyzing commits from {}", spec);
+        } else if let Ok(head) = self.repo.head() {
             if let Some(target) = head.target() {
                 revwalk.push(target)?;
                 info!(
@@ -106,23 +367,193 @@ impl GitAnalyzer {
         }
 
         revwalk.set_sorting(Sort::TIME)?;
+        Ok(revwalk)
+    }
+
+    /// Tip of the analyzed history: the configured revision, HEAD by default
+    fn tip_commit(&self) -> Result<git2::Commit<'_>> {
+        match &self.config.revision {
+            Some(revision) => self.resolve_commit(revision),
+            None => Ok(self.repo.head()?.peel_to_commit()?),
+        }
+    }
+
+    /// Resolve a full or abbreviated commit id (or revision expression) to a commit
+    fn resolve_commit(&self, spec: &str) -> Result<git2::Commit<'_>> {
+        let object = self
+            .repo
+            .revparse_single(spec)
+            .map_err(|e| match e.code() {
+                git2::ErrorCode::Ambiguous => anyhow::anyhow!(
+                    "Commit id '{}' is ambiguous in {}; use more characters",
+                    spec,
+                    self.path.display()
+                ),
+                git2::ErrorCode::NotFound | git2::ErrorCode::InvalidSpec => anyhow::anyhow!(
+                    "No branch, tag or commit '{}' found in {}",
+                    spec,
+                    self.path.display()
+                ),
+                _ => anyhow::Error::from(e),
+            })?;
+        object
+            .peel_to_commit()
+            .map_err(|_| anyhow::anyhow!("'{}' does not name a commit", spec))
+    }
+
+    /// Lines a commit adds relative to its first parent
+    pub fn commit_added_lines(&self, commit_id: &str) -> Result<Vec<AddedLine>> {
+        let commit = self.repo.find_commit(git2::Oid::from_str(commit_id)?)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let (_, added_lines) = self.diff_added_lines(&diff)?;
+        Ok(added_lines)
+    }
+
+    /// Every line of the files in the working tree that are neither in the index nor ignored
+    /// (unless `respect_gitignore` is off), plus their paths. Hidden files are included, since
+    /// `.env` files are a common leak.
+    fn untracked_added_lines(&self) -> Result<(Vec<String>, Vec<AddedLine>)> {
+        let Some(work_tree) = self.repo.workdir() else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+        let index = self.repo.index()?;
+
+        let mut files = Vec::new();
+        let mut added_lines = Vec::new();
+        for entry in self
+            .config
+            .file_walker(work_tree)
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build()
+        {
+            let entry = entry?;
+            if !entry.file_type().is_some_and(|t| t.is_file())
+                || entry
+                    .metadata()
+                    .is_ok_and(|m| m.len() > MAX_UNTRACKED_FILE_BYTES)
+            {
+                continue;
+            }
+            let relative_path = normalize::normalize_path(
+                entry.path().strip_prefix(work_tree).unwrap_or(entry.path()),
+            );
+            if index.get_path(Path::new(&relative_path), 0).is_some()
+                || self.config.is_report_artifact(&relative_path)
+            {
+                continue;
+            }
+            // Binary and non-UTF-8 files cannot hold the textual patterns being scanned for
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if content.contains('\0') {
+                continue;
+            }
+
+            let attributed_path = format!("untracked: {}", relative_path);
+            added_lines.extend(content.lines().enumerate().map(|(i, line)| AddedLine {
+                path: attributed_path.clone(),
+                line_number: i + 1,
+                content: line.trim_end().to_string(),
+            }));
+            files.push(relative_path);
+        }
 
+        info!("Scanning {} untracked file(s)", files.len());
+        Ok((files, added_lines))
+    }
+
+    /// Changed files (in diff order) and added lines of a diff, skipping report artifacts
+    fn diff_added_lines(&self, diff: &git2::Diff<'_>) -> Result<(Vec<String>, Vec<AddedLine>)> {
+        let mut files_changed = Vec::new();
+        let mut added_lines = Vec::new();
+        diff.print(git2::DiffFormat::Patch, |delta, _, line| {
+            let Some(path) = delta.new_file().path().map(normalize::normalize_path) else {
+                return true;
+            };
+            if self.config.is_report_artifact(&path) {
+                return true;
+            }
+            if !files_changed.contains(&path) {
+                files_changed.push(path.clone());
+            }
+            if line.origin() == '+' {
+                added_lines.push(AddedLine {
+                    path,
+                    line_number: line.new_lineno().unwrap_or_default() as usize,
+                    content: String::from_utf8_lossy(line.content())
+                        .trim_end()
+                        .to_string(),
+                });
+            }
+            true
+        })?;
+        Ok((files_changed, added_lines))
+    }
+
+    async fn analyze_commits(
+        &self,
+        stats: &mut RepositoryStats,
+        on_batch: &mut impl FnMut(&[CommitInfo]),
+    ) -> Result<()> {
+        let mut release_of = HashMap::new();
+        if self.config.scan_tags {
+            let tags = self.release_tags()?;
+            release_of = self.attribute_releases(&tags)?;
+            stats.release_tags = tags.iter().map(|(name, _)| name.clone()).collect();
+        }
+
+        let walk = Instant::now();
         let mut commit_oids = Vec::new();
-        for oid in revwalk {
+        for oid in self.commit_revwalk()? {
             commit_oids.push(oid?);
         }
+        // Reading commit metadata counts toward the walk, computing their diffs does not
+        let mut walk_time = walk.elapsed();
+        let mut diff_time = Duration::ZERO;
+        let mut walked = 0;
+        let mut diffed_files = 0;
 
         info!("Found {} commits to analyze", commit_oids.len());
 
         let commit_oids = if commit_oids.len() > MAX_COMMITS_FOR_FULL_ANALYSIS {
+            let strategy = self.config.sampling;
             info!(
-                "Large repository detected, sampling {} most recent commits for performance",
-                MAX_COMMITS_FOR_FULL_ANALYSIS
+                "Large repository detected, sampling {} of {} commits ({}) for performance",
+                MAX_COMMITS_FOR_FULL_ANALYSIS,
+                commit_oids.len(),
+                strategy
             );
-            commit_oids
-                .into_iter()
-                .take(MAX_COMMITS_FOR_FULL_ANALYSIS)
-                .collect()
+            let times = if strategy == SamplingStrategy::Stratified {
+                commit_oids
+                    .iter()
+                    .map(|&oid| Ok(self.repo.find_commit(oid)?.time().seconds()))
+                    .collect::<Result<Vec<_>>>()?
+            } else {
+                Vec::new()
+            };
+            let sample = sampling::sample_indices(
+                strategy,
+                commit_oids.len(),
+                MAX_COMMITS_FOR_FULL_ANALYSIS,
+                &times,
+            );
+            stats.sampling = Some(CommitSample {
+                strategy,
+                sampled: sample.len(),
+                total: commit_oids.len(),
+            });
+            sample.into_iter().map(|i| commit_oids[i]).collect()
         } else {
             commit_oids
         };
@@ -136,18 +567,37 @@ impl GitAnalyzer {
             .progress_chars("#>-")
         );
 
-        // Process commits sequentially (git2 is not Send+Sync)
+        // Process commits sequentially (git2 is not Send+Sync); changed files come from
+        // in-process libgit2 diffs unless the git subprocess fallback is enabled
         // But use async yielding and efficient batching for better performance
         let batch_size = 50; // Smaller batches for more frequent progress updates
 
         for batch in commit_oids.chunks(batch_size) {
+            if cancel::is_cancelled() {
+                warn!(
+                    "Interrupted after {} of {} commits, the history is incomplete",
+                    stats.total_commits,
+                    commit_oids.len()
+                );
+                break;
+            }
+
             // Extract commit basic info (metadata) sequentially using libgit2
+            let walk = Instant::now();
             let mut partial_commits = Vec::with_capacity(batch.len());
 
             for &oid in batch {
                 let commit = self.repo.find_commit(oid)?;
                 let id = commit.id().to_string();
-                let message = commit.message().unwrap_or("").to_string();
+                let message = normalize::normalize_commit_message(
+                    commit.message_bytes(),
+                    commit.message_encoding(),
+                );
+                let trailers = normalize::extract_trailers(
+                    commit.message_bytes(),
+                    commit.message_encoding(),
+                    &self.config.trailer_keys,
+                );
                 let author = commit.author();
                 let committer = commit.committer();
                 let authored_date = Utc
@@ -162,52 +612,40 @@ impl GitAnalyzer {
                 partial_commits.push((
                     id,
                     message,
-                    String::from_utf8_lossy(author.name_bytes()).to_string(),
+                    normalize::normalize_signature_name(author.name_bytes(), author.email_bytes()),
                     String::from_utf8_lossy(author.email_bytes()).to_string(),
-                    String::from_utf8_lossy(committer.name_bytes()).to_string(),
+                    normalize::normalize_signature_name(
+                        committer.name_bytes(),
+                        committer.email_bytes(),
+                    ),
                     String::from_utf8_lossy(committer.email_bytes()).to_string(),
                     authored_date,
                     committed_date,
+                    author.when().offset_minutes(),
+                    committer.when().offset_minutes(),
+                    trailers,
                 ));
             }
 
-            // Now get changed files concurrently with controlled concurrency
-            let repo_path = self.path.clone();
-            let semaphore = Arc::new(Semaphore::new(32)); // Limit concurrent git commands
-            let mut join_set = JoinSet::new();
-
-            for (commit_id, _, _, _, _, _, _, _) in &partial_commits {
-                let commit_id = commit_id.clone();
-                let repo_path = repo_path.clone();
-                let permit = Arc::clone(&semaphore);
-
-                join_set.spawn(async move {
-                    let _permit = permit.acquire().await.unwrap();
-
-                    // Add timeout to prevent hanging git commands
-                    tokio::time::timeout(
-                        Duration::from_secs(30),
-                        Self::get_changed_files_concurrent(&repo_path, &commit_id),
-                    )
-                    .await
-                    .unwrap_or_else(|_| {
-                        debug!("Git command timeout for commit {}", commit_id);
-                        Ok(Vec::new()) // Return empty on timeout
-                    })
-                });
-            }
+            walk_time += walk.elapsed();
+            walked += batch.len();
 
-            // Collect results maintaining order
-            let mut file_results = Vec::with_capacity(partial_commits.len());
-            while let Some(result) = join_set.join_next().await {
-                match result {
-                    Ok(files_result) => file_results.push(files_result),
-                    Err(e) => {
-                        debug!("Task join error: {}", e);
-                        file_results.push(Ok(Vec::new())); // Fallback to empty
-                    }
-                }
-            }
+            let diffs = Instant::now();
+            let file_results = if self.config.use_git_subprocess {
+                let commit_ids: Vec<String> = partial_commits.iter().map(|c| c.0.clone()).collect();
+                self.changed_files_via_subprocess(&commit_ids).await
+            } else {
+                batch
+                    .iter()
+                    .map(|&oid| self.changed_files_via_libgit2(oid))
+                    .collect()
+            };
+            diff_time += diffs.elapsed();
+            diffed_files += file_results
+                .iter()
+                .filter_map(|changed| changed.as_ref().ok())
+                .map(|changed| changed.files.len())
+                .sum::<usize>();
 
             // Combine metadata with file change results
             let mut commit_infos = Vec::with_capacity(batch.len());
@@ -222,13 +660,22 @@ impl GitAnalyzer {
                     committer_email,
                     authored_date,
                     committed_date,
+                    author_tz_offset,
+                    committer_tz_offset,
+                    trailers,
                 ),
             ) in partial_commits.into_iter().enumerate()
             {
-                let files_changed = file_results[i]
+                let mut changed = file_results[i]
                     .as_ref()
                     .map_err(|e| anyhow::anyhow!("Failed to get changed files for {}: {}", id, e))?
                     .clone();
+                // Committed CommitRaider reports would otherwise show up as hot files
+                changed
+                    .files
+                    .retain(|file| !self.config.is_report_artifact(file));
+                let formatting_only = self.config.formatting_commits != FormattingCommits::Count
+                    && self.is_formatting_only(batch[i])?;
 
                 commit_infos.push(CommitInfo {
                     id,
@@ -239,10 +686,15 @@ impl GitAnalyzer {
                     committer_email,
                     authored_date,
                     committed_date,
-                    files_changed,
-                    insertions: 0,
-                    deletions: 0,
+                    author_tz_offset,
+                    committer_tz_offset,
+                    files_changed: changed.files,
+                    insertions: changed.insertions,
+                    deletions: changed.deletions,
                     branch: None,
+                    tag: release_of.get(&batch[i]).cloned(),
+                    trailers,
+                    formatting_only,
                 });
 
                 // Update progress bar
@@ -250,7 +702,7 @@ impl GitAnalyzer {
             }
 
             // Apply updates sequentially (git2 and mutable stats require this)
-            for commit_info in commit_infos {
+            for commit_info in &commit_infos {
                 // Update global stats
                 if commit_info.authored_date < stats.first_commit {
                     stats.first_commit = commit_info.authored_date;
@@ -259,149 +711,310 @@ impl GitAnalyzer {
                     stats.last_commit = commit_info.authored_date;
                 }
 
-                // Update author statistics
-                self.update_author_stats(stats, &commit_info);
+                if commit_info.formatting_only {
+                    stats.formatting_commits += 1;
+                    if self.config.formatting_commits == FormattingCommits::Downweight {
+                        stats.update_file_timeline(commit_info);
+                    }
+                } else if self.is_bot(commit_info) {
+                    stats.bot_commits += 1;
+                    stats.update_file_timeline(commit_info);
+                } else {
+                    // Update author statistics
+                    stats.update_author_stats(commit_info);
+
+                    // Update file history
+                    stats.update_file_history(commit_info);
+                }
 
-                // Update file history
-                self.update_file_history(stats, &commit_info);
+                // Collected here rather than from the history, which streaming does not keep
+                if commit_info.files_changed.len() >= self.config.scattered_commit_threshold {
+                    stats.scattered_commits.push(ScatteredCommit {
+                        commit_id: commit_info.id.clone(),
+                        subject: commit_info
+                            .message
+                            .lines()
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                        author: commit_info.author.clone(),
+                        date: commit_info.authored_date,
+                        files_changed: commit_info.files_changed.len(),
+                    });
+                }
+
+                let meta_files: Vec<String> = commit_info
+                    .files_changed
+                    .iter()
+                    .filter(|file| self.meta_files.is_match(file.as_str()))
+                    .cloned()
+                    .collect();
+                if !meta_files.is_empty() {
+                    stats.meta_file_changes.push(MetaFileChange {
+                        commit_id: commit_info.id.clone(),
+                        subject: commit_info
+                            .message
+                            .lines()
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                        author: commit_info.author.clone(),
+                        date: commit_info.authored_date,
+                        files: meta_files,
+                        is_revert: commit_info.message.starts_with("Revert ")
+                            || commit_info.message.contains("This reverts commit "),
+                    });
+                }
 
-                stats.commit_history.push(commit_info);
                 stats.total_commits += 1;
             }
+            self.keep_widest_scattered_commits(stats);
+
+            on_batch(&commit_infos);
+            if !self.config.streaming {
+                stats.commit_history.extend(commit_infos);
+            }
 
             // Yield control periodically for better async behavior
             tokio::time::sleep(Duration::from_millis(1)).await;
         }
 
         pb.finish_with_message("Commit analysis complete");
+        profile::record("Commit walk", "commits", walk_time, walked);
+        profile::record("File diffs", "files", diff_time, diffed_files);
 
         Ok(())
     }
 
+    /// Tags pointing at commits, ordered by the commit time of their targets
+    fn release_tags(&self) -> Result<Vec<(String, git2::Oid)>> {
+        let mut tags = Vec::new();
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            let target = self
+                .repo
+                .revparse_single(&format!("refs/tags/{}", name))
+                .and_then(|object| object.peel_to_commit());
+            match target {
+                Ok(commit) => tags.push((name.to_string(), commit.id(), commit.time().seconds())),
+                Err(e) => debug!("Skipping tag {} without a commit target: {}", name, e),
+            }
+        }
+
+        tags.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.cmp(&b.0)));
+        Ok(tags.into_iter().map(|(name, oid, _)| (name, oid)).collect())
+    }
+
+    /// Map each tagged commit to the earliest tag that contains it
+    fn attribute_releases(
+        &self,
+        tags: &[(String, git2::Oid)],
+    ) -> Result<HashMap<git2::Oid, String>> {
+        let mut release_of = HashMap::new();
+
+        for (i, (name, target)) in tags.iter().enumerate() {
+            // Commits already shipped in an earlier release are hidden
+            let mut revwalk = self.repo.revwalk()?;
+            revwalk.push(*target)?;
+            for (_, earlier) in &tags[..i] {
+                revwalk.hide(*earlier)?;
+            }
+            for oid in revwalk {
+                release_of.entry(oid?).or_insert_with(|| name.clone());
+            }
+        }
+
+        Ok(release_of)
+    }
+
+    /// Changed files for a commit, diffing its tree against the first parent in-process
+    fn changed_files_via_libgit2(&self, oid: git2::Oid) -> Result<ChangedFiles> {
+        const MAX_FILES_PER_COMMIT: usize = 20;
+
+        let commit = self.repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        // Root commits (and shallow boundaries) diff against the empty tree
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let diff_stats = diff.stats()?;
+
+        let files = diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())
+                    .map(normalize::normalize_path)
+            })
+            .take(MAX_FILES_PER_COMMIT)
+            .collect();
+
+        Ok(ChangedFiles {
+            files,
+            insertions: diff_stats.insertions(),
+            deletions: diff_stats.deletions(),
+        })
+    }
+
+    /// Whether a commit only changes whitespace: every file it modifies differs from its
+    /// first parent in whitespace alone. Added, deleted, renamed and binary files are real
+    /// changes, as is any root commit.
+    fn is_formatting_only(&self, oid: git2::Oid) -> Result<bool> {
+        let commit = self.repo.find_commit(oid)?;
+        let Ok(parent) = commit.parent(0) else {
+            return Ok(false);
+        };
+        let mut options = git2::DiffOptions::new();
+        options.ignore_whitespace(true);
+        let diff = self.repo.diff_tree_to_tree(
+            Some(&parent.tree()?),
+            Some(&commit.tree()?),
+            Some(&mut options),
+        )?;
+        if diff.deltas().len() == 0 {
+            return Ok(false);
+        }
+
+        for index in 0..diff.deltas().len() {
+            let Some(patch) = git2::Patch::from_diff(&diff, index)? else {
+                return Ok(false);
+            };
+            let delta = patch.delta();
+            if delta.status() != git2::Delta::Modified || delta.flags().is_binary() {
+                return Ok(false);
+            }
+            let (_, additions, deletions) = patch.line_stats()?;
+            if additions + deletions > 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Changed files for several commits using concurrent `git` subprocesses
+    async fn changed_files_via_subprocess(
+        &self,
+        commit_ids: &[String],
+    ) -> Vec<Result<ChangedFiles>> {
+        // Get changed files concurrently with controlled concurrency
+        // The git dir rather than the repository path, which may be a separate work tree
+        let repo_path = self.repo.path().to_path_buf();
+        let semaphore = Arc::new(Semaphore::new(self.config.git_concurrency.max(1))); // Limit concurrent git commands
+        let git_timeout = Duration::from_secs(self.config.git_timeout_secs);
+        let mut join_set = JoinSet::new();
+
+        for (index, commit_id) in commit_ids.iter().enumerate() {
+            let is_root = self.is_root_commit(commit_id);
+            let commit_id = commit_id.clone();
+            let repo_path = repo_path.clone();
+            let permit = Arc::clone(&semaphore);
+
+            join_set.spawn(async move {
+                let _permit = permit.acquire().await.unwrap();
+
+                // Add timeout to prevent hanging git commands
+                let result = tokio::time::timeout(
+                    git_timeout,
+                    Self::get_changed_files_concurrent(&repo_path, &commit_id, is_root),
+                )
+                .await
+                .unwrap_or_else(|_| {
+                    debug!("Git command timeout for commit {}", commit_id);
+                    Ok(ChangedFiles::default()) // Return empty on timeout
+                });
+                (index, result)
+            });
+        }
+
+        // Collect results maintaining order (tasks complete out of order)
+        let mut file_results: Vec<Result<ChangedFiles>> = (0..commit_ids.len())
+            .map(|_| Ok(ChangedFiles::default()))
+            .collect();
+        while let Some(result) = join_set.join_next().await {
+            match result {
+                Ok((index, files_result)) => file_results[index] = files_result,
+                Err(e) => {
+                    debug!("Task join error: {}", e); // Slot keeps the empty fallback
+                }
+            }
+        }
+
+        file_results
+    }
+
+    /// Whether a commit has no parents, decided from the object rather than from diff output
+    fn is_root_commit(&self, commit_id: &str) -> bool {
+        git2::Oid::from_str(commit_id)
+            .and_then(|oid| self.repo.find_commit(oid))
+            .map(|commit| commit.parent_count() == 0)
+            .unwrap_or(false)
+    }
+
     // Concurrent version for parallel processing with enhanced tokio usage
     async fn get_changed_files_concurrent(
         repo_path: &std::path::Path,
         commit_id: &str,
-    ) -> Result<Vec<String>> {
-        const MAX_FILES_PER_COMMIT: usize = 20;
-
+        is_root: bool,
+    ) -> Result<ChangedFiles> {
         // Use tokio::process for async git command execution with better error handling
         let mut cmd = tokio::process::Command::new("git");
-        cmd.args(&[
-            "-C",
-            repo_path.to_str().unwrap_or("."),
+        cmd.args([
+            "--git-dir",
+            repo_path.to_str().unwrap_or(".git"),
             "diff-tree",
             "--no-commit-id",
-            "--name-only",
+            "--numstat",
             "-r",
-            &format!("{}~1", commit_id), // parent
-            commit_id,
         ]);
+        if is_root {
+            // Root commits are diffed against the empty tree
+            cmd.args(["--root", commit_id]);
+        } else {
+            // Against the first parent, like the in-process diff
+            cmd.arg(format!("{}~1", commit_id)).arg(commit_id);
+        }
 
         // Set up proper process isolation
         cmd.kill_on_drop(true);
 
-        let output = cmd.output().await;
-
-        match output {
-            Ok(output) if output.status.success() => {
-                let files: Vec<String> = String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .take(MAX_FILES_PER_COMMIT)
-                    .map(|s| s.to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-
-                // For initial commits (no parent), use git show
-                if files.is_empty() {
-                    let mut initial_cmd = tokio::process::Command::new("git");
-                    initial_cmd.args(&[
-                        "-C",
-                        repo_path.to_str().unwrap_or("."),
-                        "show",
-                        "--pretty=format:",
-                        "--name-only",
-                        commit_id,
-                    ]);
-                    initial_cmd.kill_on_drop(true);
-
-                    let initial_output = initial_cmd.output().await;
-
-                    if let Ok(output) = initial_output {
-                        if output.status.success() {
-                            return Ok(String::from_utf8_lossy(&output.stdout)
-                                .lines()
-                                .take(MAX_FILES_PER_COMMIT)
-                                .map(|s| s.to_string())
-                                .filter(|s| !s.is_empty())
-                                .collect());
-                        }
-                    }
-                }
-                Ok(files)
-            }
+        match cmd.output().await {
+            Ok(output) if output.status.success() => Ok(Self::parse_numstat(&output.stdout)),
             _ => {
                 // Fallback: return empty list rather than failing
-                Ok(Vec::new())
+                Ok(ChangedFiles::default())
             }
         }
     }
 
-    fn update_author_stats(&self, stats: &mut RepositoryStats, commit: &CommitInfo) {
-        let author_key = format!("{}:{}", commit.author, commit.author_email);
-
-        let author_stats = stats.author_stats.entry(author_key).or_insert(AuthorStats {
-            name: commit.author.clone(),
-            email: commit.author_email.clone(),
-            commits: 0,
-            files_touched: HashSet::new(),
-            first_commit: commit.authored_date,
-            last_commit: commit.authored_date,
-            lines_added: 0,
-            lines_removed: 0,
-        });
-
-        author_stats.commits += 1;
-        author_stats.lines_added += commit.insertions;
-        author_stats.lines_removed += commit.deletions;
-
-        if commit.authored_date < author_stats.first_commit {
-            author_stats.first_commit = commit.authored_date;
-        }
-        if commit.authored_date > author_stats.last_commit {
-            author_stats.last_commit = commit.authored_date;
-        }
-
-        for file in &commit.files_changed {
-            author_stats.files_touched.insert(file.clone());
-        }
-    }
-
-    fn update_file_history(&self, stats: &mut RepositoryStats, commit: &CommitInfo) {
-        for file_path in &commit.files_changed {
-            let file_history = stats
-                .file_history
-                .entry(file_path.clone())
-                .or_insert(FileHistory {
-                    path: file_path.clone(),
-                    commits: Vec::new(),
-                    authors: HashSet::new(),
-                    first_commit: commit.authored_date,
-                    last_commit: commit.authored_date,
-                    total_changes: 0,
-                });
-
-            file_history.commits.push(commit.id.clone());
-            file_history.authors.insert(commit.author.clone());
-            file_history.total_changes += 1;
+    /// Parse `--numstat` output ("added<TAB>deleted<TAB>path", "-" for binary files)
+    fn parse_numstat(stdout: &[u8]) -> ChangedFiles {
+        const MAX_FILES_PER_COMMIT: usize = 20;
 
-            if commit.authored_date < file_history.first_commit {
-                file_history.first_commit = commit.authored_date;
+        let mut changed = ChangedFiles::default();
+        for line in String::from_utf8_lossy(stdout).lines() {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(added), Some(deleted), Some(path)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            if path.is_empty() {
+                continue;
             }
-            if commit.authored_date > file_history.last_commit {
-                file_history.last_commit = commit.authored_date;
+
+            changed.insertions += added.parse::<usize>().unwrap_or(0);
+            changed.deletions += deleted.parse::<usize>().unwrap_or(0);
+            if changed.files.len() < MAX_FILES_PER_COMMIT {
+                changed.files.push(path.to_string());
             }
         }
+        changed
     }
 
     fn calculate_derived_stats(&self, stats: &mut RepositoryStats) -> Result<()> {
@@ -432,12 +1045,250 @@ impl GitAnalyzer {
             stats.high_churn_files.push(path.to_string());
         }
 
+        stats.binary_churn_files = self.binary_churn_files(stats);
+
+        // Find files that were committed once (or a handful of times) and never revisited
+        for (path, history) in &stats.file_history {
+            if history.commits.len() <= self.config.single_commit_threshold {
+                stats.single_commit_files.push(path.clone());
+            }
+        }
+        stats.single_commit_files.sort();
+
         info!(
-            "Derived stats: {} single-author files, {} stale files, {} high-churn files",
+            "Derived stats: {} single-author files, {} stale files, {} high-churn files ({} binary), {} single-commit files, {} scattered commits",
             stats.single_author_files.len(),
             stats.stale_files.len(),
-            stats.high_churn_files.len()
+            stats.high_churn_files.len(),
+            stats.binary_churn_files.len(),
+            stats.single_commit_files.len(),
+            stats.scattered_commits.len()
+        );
+
+        Ok(())
+    }
+
+    /// Files changed at least `binary_churn_threshold` times that are binary by extension or,
+    /// when still present, by content at the analyzed revision (HEAD by default)
+    fn binary_churn_files(&self, stats: &RepositoryStats) -> Vec<BinaryChurnFile> {
+        let tip_tree = self.tip_commit().ok().and_then(|tip| tip.tree().ok());
+        let is_binary_at_tip = |path: &str| {
+            tip_tree
+                .as_ref()
+                .and_then(|tree| tree.get_path(Path::new(path)).ok())
+                .and_then(|entry| entry.to_object(&self.repo).ok())
+                .and_then(|object| {
+                    object
+                        .as_blob()
+                        .map(|blob| is_binary_content(blob.content()))
+                })
+                .unwrap_or(false)
+        };
+
+        let mut files: Vec<BinaryChurnFile> = stats
+            .file_history
+            .values()
+            .filter(|history| history.total_changes >= self.config.binary_churn_threshold)
+            .filter(|history| {
+                is_binary_path(Path::new(&history.path)) || is_binary_at_tip(&history.path)
+            })
+            .map(|history| BinaryChurnFile {
+                path: history.path.clone(),
+                changes: history.total_changes,
+                authors: history.authors.len(),
+                last_commit: history.last_commit,
+            })
+            .collect();
+        files.sort_by(|a, b| b.changes.cmp(&a.changes).then_with(|| a.path.cmp(&b.path)));
+        files
+    }
+
+    /// Keep only the commits touching the most files among those too broad to have been
+    /// reviewed carefully
+    fn keep_widest_scattered_commits(&self, stats: &mut RepositoryStats) {
+        stats
+            .scattered_commits
+            .sort_by_key(|c| std::cmp::Reverse(c.files_changed));
+        stats
+            .scattered_commits
+            .truncate(self.config.scattered_commit_limit);
+    }
+
+    /// Files in the accumulated history that are missing from the tree of the analyzed
+    /// revision (HEAD by default). Renamed files show up under their old path.
+    fn detect_removed_files(&self, stats: &mut RepositoryStats) -> Result<()> {
+        let tree = self.tip_commit()?.tree()?;
+
+        for (path, history) in &stats.file_history {
+            if tree.get_path(Path::new(path)).is_err() {
+                stats.removed_files.push(RemovedFile {
+                    path: path.clone(),
+                    commits: history.commits.len(),
+                    first_commit: history.first_commit,
+                    last_commit: history.last_commit,
+                });
+            }
+        }
+        stats
+            .removed_files
+            .sort_by(|a, b| b.last_commit.cmp(&a.last_commit).then(a.path.cmp(&b.path)));
+
+        info!(
+            "Found {} files in history that are absent from the tip",
+            stats.removed_files.len()
         );
+        Ok(())
+    }
+
+    /// Non-fast-forward updates in the branch reflogs. HEAD's reflog is not scanned: it records
+    /// every checkout of another branch, and a rewrite of the checked-out branch is already
+    /// in that branch's reflog.
+    fn detect_history_rewrites(&self, stats: &mut RepositoryStats) -> Result<()> {
+        let references: Vec<String> = stats
+            .branches
+            .iter()
+            .map(|b| format!("refs/heads/{}", b))
+            .collect();
+
+        for reference in references {
+            // Reflogs are often missing in CI clones; skip refs without one
+            let reflog = match self.repo.reflog(&reference) {
+                Ok(reflog) => reflog,
+                Err(e) => {
+                    debug!("No reflog for {}: {}", reference, e);
+                    continue;
+                }
+            };
+
+            for entry in reflog.iter() {
+                let old = entry.id_old();
+                let new = entry.id_new();
+                if old.is_zero() || new.is_zero() || old == new {
+                    continue;
+                }
+
+                // Fast-forward updates keep the old commit in the new history. An old commit
+                // that can no longer be found, typically pruned by gc after a force-push, is
+                // not part of it either.
+                let fast_forward = match self.repo.graph_descendant_of(new, old) {
+                    Ok(fast_forward) => fast_forward,
+                    Err(e) => {
+                        debug!(
+                            "Cannot relate {} to {} in the reflog of {}: {}",
+                            old, new, reference, e
+                        );
+                        false
+                    }
+                };
+                if fast_forward {
+                    continue;
+                }
+
+                let committer = entry.committer();
+                stats.history_rewrites.push(HistoryRewrite {
+                    reference: reference.clone(),
+                    old_commit: old.to_string(),
+                    new_commit: new.to_string(),
+                    message: entry.message().unwrap_or("").to_string(),
+                    committer: normalize::normalize_signature_name(
+                        committer.name_bytes(),
+                        committer.email_bytes(),
+                    ),
+                    date: Utc
+                        .timestamp_opt(committer.when().seconds(), 0)
+                        .single()
+                        .unwrap_or_else(Utc::now),
+                });
+            }
+        }
+
+        if !stats.history_rewrites.is_empty() {
+            info!(
+                "Detected {} non-fast-forward reflog updates (rewritten history)",
+                stats.history_rewrites.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn detect_large_blobs(&self, stats: &mut RepositoryStats) -> Result<()> {
+        let odb = self.repo.odb()?;
+        let threshold = self.config.large_blob_threshold_bytes;
+
+        // Blobs still in the tree of the analyzed revision (HEAD by default)
+        let tip = self.tip_commit()?;
+        let mut head_blobs = HashSet::new();
+        if let Ok(head) = tip.tree() {
+            head.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+                if entry.kind() == Some(git2::ObjectType::Blob) {
+                    head_blobs.insert(entry.id());
+                }
+                git2::TreeWalkResult::Ok
+            })?;
+        }
+
+        // Walk oldest first so the first commit adding a blob is the one that introduced it
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(tip.id())?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+        let mut seen = HashSet::new();
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let diff = self
+                .repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+            for delta in diff.deltas() {
+                let file = delta.new_file();
+                let blob_id = file.id();
+                if blob_id.is_zero() || !seen.insert(blob_id) {
+                    continue;
+                }
+
+                let size = match odb.read_header(blob_id) {
+                    Ok((size, git2::ObjectType::Blob)) => size as u64,
+                    _ => continue,
+                };
+                if size < threshold {
+                    continue;
+                }
+
+                stats.large_blobs.push(LargeBlob {
+                    blob_id: blob_id.to_string(),
+                    path: file
+                        .path()
+                        .map(normalize::normalize_path)
+                        .unwrap_or_default(),
+                    size,
+                    introduced_in: commit.id().to_string(),
+                    introduced_date: Utc
+                        .timestamp_opt(commit.time().seconds(), 0)
+                        .single()
+                        .unwrap_or_else(Utc::now),
+                    in_head: head_blobs.contains(&blob_id),
+                });
+            }
+        }
+
+        stats
+            .large_blobs
+            .sort_by_key(|blob| std::cmp::Reverse(blob.size));
+        stats.large_blobs.truncate(self.config.large_blob_limit);
+
+        if !stats.large_blobs.is_empty() {
+            info!(
+                "Found {} blobs of at least {} bytes in history",
+                stats.large_blobs.len(),
+                threshold
+            );
+        }
 
         Ok(())
     }
@@ -473,6 +1324,12 @@ impl GitAnalyzer {
                 RepositoryType::GitHub
             } else if url_lower.contains("bitbucket") {
                 RepositoryType::Bitbucket
+            } else if url_lower.contains("gitea") || url_lower.contains("codeberg") {
+                RepositoryType::Gitea
+            } else if url_lower.contains("gogs") {
+                RepositoryType::Gogs
+            } else if url_lower.contains("sr.ht") {
+                RepositoryType::Sourcehut
             } else {
                 RepositoryType::Other
             }
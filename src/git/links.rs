@@ -1,4 +1,4 @@
-use super::{RepositoryStats, RepositoryType};
+use super::{RepositoryStats, RepositoryType, UNCOMMITTED_COMMIT_ID};
 use regex::Regex;
 
 pub struct RepositoryLinker<'a> {
@@ -11,18 +11,52 @@ impl<'a> RepositoryLinker<'a> {
     }
 
     pub fn get_commit_url(&self, commit_id: &str) -> Option<String> {
+        if commit_id == UNCOMMITTED_COMMIT_ID {
+            return None;
+        }
         let base_url = self.get_base_url()?;
 
         match self.stats.repository_type {
             RepositoryType::GitHub => Some(format!("{}/commit/{}", base_url, commit_id)),
             RepositoryType::GitLab => Some(format!("{}/-/commit/{}", base_url, commit_id)),
             RepositoryType::Bitbucket => Some(format!("{}/commits/{}", base_url, commit_id)),
+            RepositoryType::Gitea | RepositoryType::Gogs | RepositoryType::Sourcehut => {
+                Some(format!("{}/commit/{}", base_url, commit_id))
+            }
             _ => None,
         }
     }
 
-    pub fn get_file_url(&self, file_path: &str, commit_id: Option<&str>) -> Option<String> {
+    /// Link to a file at `commit_id` (or the default branch). On GitHub and GitLab a `lines`
+    /// range of 1-based line numbers becomes an anchor highlighting exactly those lines.
+    pub fn get_file_url(
+        &self,
+        file_path: &str,
+        commit_id: Option<&str>,
+        lines: Option<(usize, usize)>,
+    ) -> Option<String> {
+        let url = self.get_plain_file_url(file_path, commit_id)?;
+        // Lines of uncommitted changes do not line up with the default branch
+        let lines = lines.filter(|_| commit_id != Some(UNCOMMITTED_COMMIT_ID));
+        let anchor = match (lines, &self.stats.repository_type) {
+            (Some((start, end)), RepositoryType::GitHub) if end > start => {
+                format!("#L{}-L{}", start, end)
+            }
+            (Some((start, end)), RepositoryType::GitLab) if end > start => {
+                format!("#L{}-{}", start, end)
+            }
+            (Some((start, _)), RepositoryType::GitHub | RepositoryType::GitLab) => {
+                format!("#L{}", start)
+            }
+            _ => String::new(),
+        };
+        Some(url + &anchor)
+    }
+
+    fn get_plain_file_url(&self, file_path: &str, commit_id: Option<&str>) -> Option<String> {
         let base_url = self.get_base_url()?;
+        // Uncommitted files are not on the remote yet; link the default branch instead
+        let commit_id = commit_id.filter(|id| *id != UNCOMMITTED_COMMIT_ID);
 
         match self.stats.repository_type {
             RepositoryType::GitHub => {
@@ -46,17 +80,45 @@ impl<'a> RepositoryLinker<'a> {
                     Some(format!("{}/src/main/{}", base_url, file_path))
                 }
             }
+            RepositoryType::Gitea => {
+                if let Some(commit) = commit_id {
+                    Some(format!("{}/src/commit/{}/{}", base_url, commit, file_path))
+                } else {
+                    Some(format!("{}/src/branch/main/{}", base_url, file_path))
+                }
+            }
+            RepositoryType::Gogs => {
+                if let Some(commit) = commit_id {
+                    Some(format!("{}/src/{}/{}", base_url, commit, file_path))
+                } else {
+                    Some(format!("{}/src/main/{}", base_url, file_path))
+                }
+            }
+            RepositoryType::Sourcehut => {
+                if let Some(commit) = commit_id {
+                    Some(format!("{}/tree/{}/item/{}", base_url, commit, file_path))
+                } else {
+                    Some(format!("{}/tree/HEAD/item/{}", base_url, file_path))
+                }
+            }
             _ => None,
         }
     }
 
     pub fn get_diff_url(&self, commit_id: &str) -> Option<String> {
+        if commit_id == UNCOMMITTED_COMMIT_ID {
+            return None;
+        }
         let base_url = self.get_base_url()?;
 
         match self.stats.repository_type {
             RepositoryType::GitHub => Some(format!("{}/commit/{}.diff", base_url, commit_id)),
             RepositoryType::GitLab => Some(format!("{}/-/commit/{}.diff", base_url, commit_id)),
             RepositoryType::Bitbucket => Some(format!("{}/commits/{}/raw", base_url, commit_id)),
+            RepositoryType::Gitea | RepositoryType::Gogs => {
+                Some(format!("{}/commit/{}.diff", base_url, commit_id))
+            }
+            RepositoryType::Sourcehut => Some(format!("{}/commit/{}.patch", base_url, commit_id)),
             _ => None,
         }
     }
@@ -66,12 +128,29 @@ impl<'a> RepositoryLinker<'a> {
             RepositoryType::GitHub => "GitHub",
             RepositoryType::GitLab => "GitLab",
             RepositoryType::Bitbucket => "Bitbucket",
+            RepositoryType::Gitea => "Gitea",
+            RepositoryType::Gogs => "Gogs",
+            RepositoryType::Sourcehut => "SourceHut",
             RepositoryType::Other => "Git Repository",
             RepositoryType::Local => "Local Repository",
         }
         .to_string()
     }
 
+    /// Project name from the remote URL, falling back to the repository directory name
+    pub fn get_project_name(&self) -> String {
+        self.get_base_url()
+            .and_then(|url| url.rsplit('/').next().map(str::to_string))
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                std::path::Path::new(&self.stats.path)
+                    .canonicalize()
+                    .ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            })
+            .unwrap_or_else(|| "repository".to_string())
+    }
+
     pub fn get_base_url(&self) -> Option<String> {
         let remote_url = self.stats.remote_url.as_ref()?;
 
@@ -147,6 +226,15 @@ impl<'a> RepositoryLinker<'a> {
             RepositoryType::GitHub => Some(format!("{}/issues/{}", base_url, issue_number)),
             RepositoryType::GitLab => Some(format!("{}/-/issues/{}", base_url, issue_number)),
             RepositoryType::Bitbucket => Some(format!("{}/issues/{}", base_url, issue_number)),
+            RepositoryType::Gitea | RepositoryType::Gogs => {
+                Some(format!("{}/issues/{}", base_url, issue_number))
+            }
+            // Trackers live on todo.sr.ht under the same ~owner/name path
+            RepositoryType::Sourcehut => Some(format!(
+                "{}/{}",
+                base_url.replacen("://git.sr.ht/", "://todo.sr.ht/", 1),
+                issue_number
+            )),
             _ => None,
         }
     }
@@ -1,17 +1,36 @@
 use chrono::{DateTime, Utc};
 use git2::BranchType;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 
 pub mod analyzer;
 pub mod links;
+pub mod normalize;
+pub mod patch;
+pub mod sampling;
 pub mod stats;
+pub mod test_detection;
 
 pub use analyzer::GitAnalyzer;
 pub use links::RepositoryLinker;
+pub use sampling::{CommitSample, SamplingStrategy};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Commit id of the pseudo-commit holding uncommitted changes in working tree scans
+pub const UNCOMMITTED_COMMIT_ID: &str = "uncommitted";
+
+/// A line added by uncommitted changes
+#[derive(Debug, Clone)]
+pub struct AddedLine {
+    pub path: String,
+    pub line_number: usize,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CommitInfo {
     pub id: String,
     pub message: String,
@@ -21,13 +40,68 @@ pub struct CommitInfo {
     pub committer_email: String,
     pub authored_date: DateTime<Utc>,
     pub committed_date: DateTime<Utc>,
+    /// Author's timezone offset from UTC in minutes, as recorded in the commit
+    #[serde(default)]
+    pub author_tz_offset: i32,
+    /// Committer's timezone offset from UTC in minutes
+    #[serde(default)]
+    pub committer_tz_offset: i32,
     pub files_changed: Vec<String>,
     pub insertions: usize,
     pub deletions: usize,
     pub branch: Option<String>,
+    /// Earliest release tag containing this commit (tag scanning only)
+    pub tag: Option<String>,
+    /// Values of the configured trailers (`Signed-off-by:`, `CVE:`, ...) by trailer key
+    #[serde(default)]
+    pub trailers: HashMap<String, Vec<String>>,
+    /// The commit only changes whitespace (empty diff under `git diff -w`); detected unless
+    /// formatting commits are counted like any other
+    #[serde(default)]
+    pub formatting_only: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How whitespace-only commits, such as mechanical reformatting, count toward churn and
+/// authorship
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FormattingCommits {
+    /// Like any other commit, without detecting them
+    #[default]
+    Count,
+    /// Kept in file timelines, but add no churn and make no one an author of the files
+    Downweight,
+    /// Left out of file and author statistics altogether
+    Exclude,
+}
+
+impl fmt::Display for FormattingCommits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FormattingCommits::Count => "count",
+            FormattingCommits::Downweight => "downweight",
+            FormattingCommits::Exclude => "exclude",
+        })
+    }
+}
+
+impl FromStr for FormattingCommits {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "count" => Ok(FormattingCommits::Count),
+            "downweight" => Ok(FormattingCommits::Downweight),
+            "exclude" => Ok(FormattingCommits::Exclude),
+            _ => Err(anyhow::anyhow!(
+                "Unknown formatting commit handling '{}' (expected count, downweight or exclude)",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FileHistory {
     pub path: String,
     pub commits: Vec<String>,
@@ -37,7 +111,7 @@ pub struct FileHistory {
     pub total_changes: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AuthorStats {
     pub name: String,
     pub email: String,
@@ -49,7 +123,7 @@ pub struct AuthorStats {
     pub lines_removed: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RepositoryStats {
     pub path: String,
     pub total_commits: usize,
@@ -58,27 +132,129 @@ pub struct RepositoryStats {
     pub first_commit: DateTime<Utc>,
     pub last_commit: DateTime<Utc>,
     pub branches: Vec<String>,
+    /// Branch checked out at HEAD, `None` when detached
+    pub head_branch: Option<String>,
     pub commit_history: Vec<CommitInfo>,
     pub file_history: HashMap<String, FileHistory>,
     pub author_stats: HashMap<String, AuthorStats>,
     pub single_author_files: Vec<String>,
     pub stale_files: Vec<String>,
     pub high_churn_files: Vec<String>,
+    /// Files touched by no more than `single_commit_threshold` commits, often forgotten code
+    pub single_commit_files: Vec<String>,
     pub remote_url: Option<String>,
     pub repository_type: RepositoryType,
     pub test_analysis: TestAnalysis,
+    /// Release tags in chronological order of their target commits (tag scanning only)
+    pub release_tags: Vec<String>,
+    pub history_rewrites: Vec<HistoryRewrite>,
+    /// Largest blobs above the configured size threshold, biggest first
+    pub large_blobs: Vec<LargeBlob>,
+    /// Commits changing at least `scattered_commit_threshold` files, most files first
+    pub scattered_commits: Vec<ScatteredCommit>,
+    /// Commits changing CI pipelines or security policy files, newest first
+    #[serde(default)]
+    pub meta_file_changes: Vec<MetaFileChange>,
+    /// Files with history that are absent from the analyzed tip, most recently touched first
+    pub removed_files: Vec<RemovedFile>,
+    /// Binary files changed at least `binary_churn_threshold` times, most changes first
+    #[serde(default)]
+    pub binary_churn_files: Vec<BinaryChurnFile>,
+    /// Whitespace-only commits detected, when formatting commits are not counted
+    #[serde(default)]
+    pub formatting_commits: usize,
+    /// Commits by bot authors, scanned but left out of authorship and churn
+    #[serde(default)]
+    pub bot_commits: usize,
+    /// How the history was sampled when it exceeded the full-analysis limit
+    #[serde(default)]
+    pub sampling: Option<CommitSample>,
+    /// History is truncated (shallow clone), so first-commit and stale metrics are unreliable
+    pub is_shallow: bool,
+}
+
+/// A reflog entry where a ref moved to a commit that does not descend from its previous value
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryRewrite {
+    pub reference: String,
+    pub old_commit: String,
+    pub new_commit: String,
+    pub message: String,
+    pub committer: String,
+    pub date: DateTime<Utc>,
+}
+
+/// A blob above the size threshold and the commit that first added it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LargeBlob {
+    pub blob_id: String,
+    pub path: String,
+    pub size: u64,
+    pub introduced_in: String,
+    pub introduced_date: DateTime<Utc>,
+    /// The blob is still in the tree of the analyzed revision (HEAD by default)
+    pub in_head: bool,
+}
+
+/// A file that was committed at some point but no longer exists at the analyzed tip.
+/// Its contents, including anything "deleted" such as secrets, remain in history.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RemovedFile {
+    pub path: String,
+    pub commits: usize,
+    pub first_commit: DateTime<Utc>,
+    pub last_commit: DateTime<Utc>,
+}
+
+/// A binary file committed again and again, such as a rebuilt artifact checked in with each
+/// change, or a binary being tampered with
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BinaryChurnFile {
+    pub path: String,
+    pub changes: usize,
+    pub authors: usize,
+    pub last_commit: DateTime<Utc>,
+}
+
+/// A commit changing CI pipelines or security policy files, where a malicious or careless
+/// edit can weaken the checks guarding everything else
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetaFileChange {
+    pub commit_id: String,
+    pub subject: String,
+    pub author: String,
+    pub date: DateTime<Utc>,
+    /// Changed files matching `meta_file_globs`
+    pub files: Vec<String>,
+    /// The commit reverts an earlier one, possibly undoing a security check
+    pub is_revert: bool,
+}
+
+/// A commit touching so many files that it was unlikely to be reviewed line by line
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScatteredCommit {
+    pub commit_id: String,
+    pub subject: String,
+    pub author: String,
+    pub date: DateTime<Utc>,
+    pub files_changed: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum RepositoryType {
     GitHub,
     GitLab,
     Bitbucket,
+    /// Gitea and its forks (Forgejo, Codeberg)
+    Gitea,
+    Gogs,
+    /// git.sr.ht, with issues on todo.sr.ht
+    Sourcehut,
     Other,
     Local,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TestAnalysis {
     pub total_test_files: usize,
     pub test_directories: Vec<String>,
@@ -0,0 +1,120 @@
+use encoding_rs::Encoding;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name shown for signatures that carry neither a name nor an email
+const UNKNOWN_AUTHOR: &str = "Unknown";
+
+/// Key for a repository-relative path: its components joined with `/` on every platform, so
+/// paths from git and from walking the working tree (backslashes on Windows) compare equal
+pub fn normalize_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Decode and clean up a raw commit message so display and pattern matching see the same text.
+///
+/// Honors the commit's `encoding` header (falling back to lossy UTF-8), converts CRLF/CR line
+/// endings to LF, strips trailing whitespace, and removes a trailing block of git trailers
+/// such as `Signed-off-by:`.
+pub fn normalize_commit_message(raw: &[u8], encoding: Option<&str>) -> String {
+    let unified = decode_message(raw, encoding);
+    let mut lines: Vec<&str> = unified.lines().map(str::trim_end).collect();
+
+    strip_trailers(&mut lines);
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+/// Values of the git trailers (`Key: value` lines of the final paragraph) that
+/// `normalize_commit_message` strips, keyed by the matching entry of `keys` compared
+/// case-insensitively. An empty `keys` keeps every trailer under its own spelling.
+pub fn extract_trailers(
+    raw: &[u8],
+    encoding: Option<&str>,
+    keys: &[String],
+) -> HashMap<String, Vec<String>> {
+    let unified = decode_message(raw, encoding);
+    let mut lines: Vec<&str> = unified.lines().map(str::trim_end).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    let mut trailers: HashMap<String, Vec<String>> = HashMap::new();
+    let Some(paragraph_start) = trailer_paragraph_start(&lines) else {
+        return trailers;
+    };
+    for line in &lines[paragraph_start..] {
+        let Some((key, value)) = line.split_once(": ") else {
+            continue;
+        };
+        let key = if keys.is_empty() {
+            Some(key.to_string())
+        } else {
+            keys.iter().find(|k| k.eq_ignore_ascii_case(key)).cloned()
+        };
+        if let Some(key) = key {
+            trailers
+                .entry(key)
+                .or_default()
+                .push(value.trim().to_string());
+        }
+    }
+    trailers
+}
+
+/// Message decoded per its `encoding` header with line endings unified to LF
+fn decode_message(raw: &[u8], encoding: Option<&str>) -> String {
+    let decoded = match encoding.and_then(|label| Encoding::for_label(label.as_bytes())) {
+        Some(encoding) => encoding.decode(raw).0.into_owned(),
+        None => String::from_utf8_lossy(raw).into_owned(),
+    };
+    decoded.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Decode a signature's name, falling back to the local part of its email (or `Unknown`)
+/// when the name is empty, as in some imported histories
+pub fn normalize_signature_name(name: &[u8], email: &[u8]) -> String {
+    let name = String::from_utf8_lossy(name);
+    if !name.trim().is_empty() {
+        return name.trim().to_string();
+    }
+
+    let email = String::from_utf8_lossy(email);
+    match email.trim().split('@').next() {
+        Some(local) if !local.is_empty() => local.to_string(),
+        _ => UNKNOWN_AUTHOR.to_string(),
+    }
+}
+
+/// Remove the final paragraph when every line in it is a `Key: value` trailer
+fn strip_trailers(lines: &mut Vec<&str>) {
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    if let Some(paragraph_start) = trailer_paragraph_start(lines) {
+        lines.truncate(paragraph_start);
+    }
+}
+
+/// Start of the final paragraph of `lines` (without trailing blank lines) when every line
+/// in it is a trailer. A message that is only trailers has no body, so it has none.
+fn trailer_paragraph_start(lines: &[&str]) -> Option<usize> {
+    static TRAILER_RE: OnceLock<Regex> = OnceLock::new();
+    let trailer_re = TRAILER_RE.get_or_init(|| Regex::new(r"^[A-Za-z][A-Za-z0-9-]*: .+").unwrap());
+
+    let paragraph_start = lines.iter().rposition(|line| line.is_empty())? + 1;
+    lines[paragraph_start..]
+        .iter()
+        .all(|line| trailer_re.is_match(line))
+        .then_some(paragraph_start)
+}
@@ -0,0 +1,273 @@
+use super::normalize;
+use super::{AddedLine, CommitInfo, RepositoryStats};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Commit id prefix for patches that do not name the commit they were made from
+pub const PATCH_COMMIT_ID_PREFIX: &str = "patch-";
+
+/// One patch of a patch or mbox file, as a synthetic commit plus the lines it adds
+#[derive(Debug, Clone)]
+pub struct PatchCommit {
+    pub commit: CommitInfo,
+    pub added_lines: Vec<AddedLine>,
+}
+
+/// Split a `git format-patch` file, a mailbox of such patches or a plain `git diff` into
+/// patches. Trailers matching `trailer_keys` are extracted as for commits.
+pub fn parse_patches(content: &[u8], trailer_keys: &[String]) -> Vec<PatchCommit> {
+    let content = String::from_utf8_lossy(content).replace("\r\n", "\n");
+    let mut patches = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut current_id = None;
+
+    for line in content.lines() {
+        if let Some(id) = mbox_separator_commit(line) {
+            if !current.is_empty() {
+                patches.push(parse_patch(
+                    &current,
+                    current_id.take(),
+                    patches.len(),
+                    trailer_keys,
+                ));
+            }
+            current.clear();
+            current_id = Some(id.to_string());
+        } else {
+            current.push(line);
+        }
+    }
+    if current.iter().any(|line| !line.trim().is_empty()) {
+        patches.push(parse_patch(
+            &current,
+            current_id,
+            patches.len(),
+            trailer_keys,
+        ));
+    }
+
+    // Mail without a diff is cover letters and replies, not changes
+    patches.retain(|patch| !patch.commit.files_changed.is_empty());
+    patches
+}
+
+/// Commit id of a `From <sha> Mon Sep 17 00:00:00 2001` line starting a format-patch mail
+fn mbox_separator_commit(line: &str) -> Option<&str> {
+    let id = line.strip_prefix("From ")?.split(' ').next()?;
+    (id.len() == 40 && id.chars().all(|c| c.is_ascii_hexdigit())).then_some(id)
+}
+
+fn parse_patch(
+    lines: &[&str],
+    id: Option<String>,
+    index: usize,
+    trailer_keys: &[String],
+) -> PatchCommit {
+    // Mail headers up to the first blank line, absent in a plain diff
+    let has_headers = lines.first().is_some_and(|line| {
+        line.split_once(':')
+            .is_some_and(|(key, _)| !key.is_empty() && !key.contains(' '))
+    });
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut body_start = 0;
+    if has_headers {
+        let mut last_key: Option<String> = None;
+        for (i, line) in lines.iter().enumerate() {
+            body_start = i + 1;
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with([' ', '\t']) {
+                // Folded header continues the previous one
+                if let Some(value) = last_key.as_ref().and_then(|key| headers.get_mut(key)) {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((key, value)) = line.split_once(':') {
+                let key = key.trim().to_lowercase();
+                headers.insert(key.clone(), value.trim().to_string());
+                last_key = Some(key);
+            }
+        }
+    }
+
+    let diff_start = lines[body_start..]
+        .iter()
+        .position(|line| line.starts_with("diff --git ") || line.starts_with("--- "))
+        .map_or(lines.len(), |i| body_start + i);
+    // The diffstat follows a `---` line that ends the message
+    let message_end = lines[body_start..diff_start]
+        .iter()
+        .position(|line| *line == "---")
+        .map_or(diff_start, |i| body_start + i);
+
+    let subject = headers
+        .get("subject")
+        .map(|subject| strip_patch_prefix(subject))
+        .unwrap_or_default();
+    let raw_message = format!(
+        "{}\n\n{}",
+        subject,
+        lines[body_start..message_end].join("\n")
+    );
+    // The file was decoded as a whole, so the mail's charset no longer applies
+    let message = normalize::normalize_commit_message(raw_message.trim().as_bytes(), None);
+    let trailers = normalize::extract_trailers(raw_message.as_bytes(), None, trailer_keys);
+
+    let (author, author_email) = headers
+        .get("from")
+        .map(|from| parse_address(from))
+        .unwrap_or_default();
+    let author = normalize::normalize_signature_name(author.as_bytes(), author_email.as_bytes());
+    let date = headers
+        .get("date")
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok());
+    let tz_offset = date.map_or(0, |date| date.offset().local_minus_utc() / 60);
+    let date = date.map_or_else(Utc::now, |date| date.with_timezone(&Utc));
+
+    let (files_changed, added_lines, insertions, deletions) = parse_diff(&lines[diff_start..]);
+
+    PatchCommit {
+        commit: CommitInfo {
+            id: id.unwrap_or_else(|| format!("{}{}", PATCH_COMMIT_ID_PREFIX, index + 1)),
+            message,
+            author: author.clone(),
+            author_email: author_email.clone(),
+            committer: author,
+            committer_email: author_email,
+            authored_date: date,
+            committed_date: date,
+            author_tz_offset: tz_offset,
+            committer_tz_offset: tz_offset,
+            files_changed,
+            insertions,
+            deletions,
+            branch: None,
+            tag: None,
+            trailers,
+            formatting_only: false,
+        },
+        added_lines,
+    }
+}
+
+/// Subject without the `[PATCH v2 1/3]` style tags that format-patch and mailing lists add
+fn strip_patch_prefix(subject: &str) -> String {
+    let mut subject = subject.trim();
+    while let Some(rest) = subject.strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((_, rest)) => subject = rest.trim_start(),
+            None => break,
+        }
+    }
+    subject.to_string()
+}
+
+/// Name and email of a `Name <email>` address, or of a bare email
+fn parse_address(address: &str) -> (String, String) {
+    match address.rsplit_once('<') {
+        Some((name, email)) => (
+            name.trim().trim_matches('"').to_string(),
+            email.trim_end_matches('>').trim().to_string(),
+        ),
+        None => (String::new(), address.trim().to_string()),
+    }
+}
+
+/// Changed files in diff order, added lines with their new line numbers, and the numbers
+/// of inserted and deleted lines of a unified diff
+fn parse_diff(lines: &[&str]) -> (Vec<String>, Vec<AddedLine>, usize, usize) {
+    let mut files_changed: Vec<String> = Vec::new();
+    let mut added_lines = Vec::new();
+    let (mut insertions, mut deletions) = (0, 0);
+    let mut old_path: Option<String> = None;
+    let mut path = String::new();
+    // Lines left in the current hunk on the old and new side, and the next new line number
+    let (mut old_left, mut new_left, mut line_number) = (0usize, 0usize, 0usize);
+
+    for line in lines {
+        if old_left > 0 || new_left > 0 {
+            match line.as_bytes().first() {
+                Some(b'+') => {
+                    added_lines.push(AddedLine {
+                        path: path.clone(),
+                        line_number,
+                        content: line[1..].to_string(),
+                    });
+                    insertions += 1;
+                    line_number += 1;
+                    new_left = new_left.saturating_sub(1);
+                }
+                Some(b'-') => {
+                    deletions += 1;
+                    old_left = old_left.saturating_sub(1);
+                }
+                // `\ No newline at end of file`
+                Some(b'\\') => {}
+                _ => {
+                    line_number += 1;
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+            }
+            continue;
+        }
+
+        if let Some(old) = line.strip_prefix("--- ") {
+            old_path = diff_path(old, "a/");
+        } else if let Some(new) = line.strip_prefix("+++ ") {
+            // Deleted files keep their old path
+            path = diff_path(new, "b/")
+                .or_else(|| old_path.clone())
+                .unwrap_or_default();
+            if !path.is_empty() && !files_changed.contains(&path) {
+                files_changed.push(path.clone());
+            }
+        } else if let Some(ranges) = line.strip_prefix("@@ ") {
+            let mut ranges = ranges.split(' ');
+            let old = ranges.next().and_then(|r| r.strip_prefix('-'));
+            let new = ranges.next().and_then(|r| r.strip_prefix('+'));
+            let (_, old_count) = old.map_or((0, 0), hunk_range);
+            let (new_start, new_count) = new.map_or((0, 0), hunk_range);
+            old_left = old_count;
+            new_left = new_count;
+            line_number = new_start;
+        }
+    }
+
+    (files_changed, added_lines, insertions, deletions)
+}
+
+/// Path of a `---`/`+++` header without its `a/` or `b/` prefix; `None` for /dev/null
+fn diff_path(header: &str, prefix: &str) -> Option<String> {
+    // Git appends a tab when the path contains spaces
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// Start and line count of a hunk range such as `12,7`; the count defaults to 1
+fn hunk_range(range: &str) -> (usize, usize) {
+    let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+    (start.parse().unwrap_or(0), count.parse().unwrap_or(0))
+}
+
+/// Repository stats for the patches of `path`, one commit per patch
+pub fn patch_stats(path: &Path, patches: &[PatchCommit]) -> RepositoryStats {
+    let mut stats = RepositoryStats::empty(path.display().to_string());
+    for patch in patches {
+        let commit = &patch.commit;
+        stats.first_commit = stats.first_commit.min(commit.authored_date);
+        stats.last_commit = stats.last_commit.max(commit.authored_date);
+        stats.update_author_stats(commit);
+        stats.update_file_history(commit);
+        stats.commit_history.push(commit.clone());
+    }
+    stats.total_commits = stats.commit_history.len();
+    stats.total_authors = stats.author_stats.len();
+    stats.total_files = stats.file_history.len();
+    stats
+}
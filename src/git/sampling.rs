@@ -0,0 +1,121 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Time buckets of a stratified sample
+const STRATIFIED_BUCKETS: usize = 20;
+
+/// Which commits of a history too large to analyze in full are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SamplingStrategy {
+    /// The most recent commits
+    #[default]
+    Recent,
+    /// Every k-th commit across the whole history
+    Uniform,
+    /// Commits from equal time spans in proportion to their activity, each active span
+    /// contributing at least one
+    Stratified,
+}
+
+impl fmt::Display for SamplingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SamplingStrategy::Recent => "recent",
+            SamplingStrategy::Uniform => "uniform",
+            SamplingStrategy::Stratified => "stratified",
+        })
+    }
+}
+
+impl FromStr for SamplingStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "recent" => Ok(SamplingStrategy::Recent),
+            "uniform" => Ok(SamplingStrategy::Uniform),
+            "stratified" => Ok(SamplingStrategy::Stratified),
+            _ => Err(anyhow::anyhow!(
+                "Unknown sampling strategy '{}' (expected recent, uniform or stratified)",
+                s
+            )),
+        }
+    }
+}
+
+/// How a history was sampled, recorded for the report summary
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CommitSample {
+    pub strategy: SamplingStrategy,
+    /// Commits analyzed
+    pub sampled: usize,
+    /// Commits in the history
+    pub total: usize,
+}
+
+/// Ascending indices of the at most `limit` commits to keep out of `total` in walk order
+/// (newest first). `times` holds each commit's time and is only read by stratified sampling.
+pub fn sample_indices(
+    strategy: SamplingStrategy,
+    total: usize,
+    limit: usize,
+    times: &[i64],
+) -> Vec<usize> {
+    if total <= limit {
+        return (0..total).collect();
+    }
+    match strategy {
+        SamplingStrategy::Recent => (0..limit).collect(),
+        SamplingStrategy::Uniform => evenly_spaced(&(0..total).collect::<Vec<_>>(), limit),
+        SamplingStrategy::Stratified => stratified(times, limit),
+    }
+}
+
+/// `count` items spread evenly over `items`, starting with the first
+fn evenly_spaced(items: &[usize], count: usize) -> Vec<usize> {
+    (0..count.min(items.len()))
+        .map(|i| items[i * items.len() / count])
+        .collect()
+}
+
+fn stratified(times: &[i64], limit: usize) -> Vec<usize> {
+    let (Some(&oldest), Some(&newest)) = (times.iter().min(), times.iter().max()) else {
+        return Vec::new();
+    };
+    let span = (newest - oldest).max(1) as i128;
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); STRATIFIED_BUCKETS];
+    for (i, &time) in times.iter().enumerate() {
+        let bucket = ((time - oldest) as i128 * STRATIFIED_BUCKETS as i128 / span) as usize;
+        buckets[bucket.min(STRATIFIED_BUCKETS - 1)].push(i);
+    }
+
+    // Every active span gets one commit, the rest of the budget goes by largest remainder
+    let active = buckets.iter().filter(|b| !b.is_empty()).count();
+    let budget = limit.saturating_sub(active);
+    let total = times.len();
+    let mut shares: Vec<(usize, usize, usize)> = buckets
+        .iter()
+        .enumerate()
+        .filter(|(_, bucket)| !bucket.is_empty())
+        .map(|(i, bucket)| {
+            let exact = bucket.len() * budget;
+            (i, 1 + exact / total, exact % total)
+        })
+        .collect();
+    let assigned: usize = shares.iter().map(|(_, share, _)| share).sum();
+    shares.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    for share in shares.iter_mut().take(limit.saturating_sub(assigned)) {
+        share.1 += 1;
+    }
+
+    let mut sample: Vec<usize> = shares
+        .iter()
+        .flat_map(|&(bucket, share, _)| evenly_spaced(&buckets[bucket], share))
+        .collect();
+    sample.sort_unstable();
+    sample.truncate(limit);
+    sample
+}
@@ -1,8 +1,136 @@
 use super::*;
+use chrono::TimeZone;
 
 // Additional statistical analysis functions for Git data
 
 impl RepositoryStats {
+    /// Stats with no history recorded yet
+    pub fn empty(path: String) -> Self {
+        RepositoryStats {
+            path,
+            total_commits: 0,
+            total_files: 0,
+            total_authors: 0,
+            first_commit: Utc::now(),
+            last_commit: Utc.timestamp_opt(0, 0).single().unwrap(),
+            branches: Vec::new(),
+            head_branch: None,
+            commit_history: Vec::new(),
+            file_history: HashMap::new(),
+            author_stats: HashMap::new(),
+            single_author_files: Vec::new(),
+            stale_files: Vec::new(),
+            high_churn_files: Vec::new(),
+            single_commit_files: Vec::new(),
+            remote_url: None,
+            repository_type: RepositoryType::Local,
+            test_analysis: TestAnalysis {
+                total_test_files: 0,
+                test_directories: Vec::new(),
+                test_frameworks: HashSet::new(),
+                has_regression_tests: false,
+                test_patterns_found: Vec::new(),
+                test_coverage_indicators: Vec::new(),
+            },
+            release_tags: Vec::new(),
+            history_rewrites: Vec::new(),
+            large_blobs: Vec::new(),
+            scattered_commits: Vec::new(),
+            meta_file_changes: Vec::new(),
+            removed_files: Vec::new(),
+            binary_churn_files: Vec::new(),
+            formatting_commits: 0,
+            bot_commits: 0,
+            sampling: None,
+            is_shallow: false,
+        }
+    }
+
+    /// Credit a commit to its author
+    pub fn update_author_stats(&mut self, commit: &CommitInfo) {
+        let author_key = format!("{}:{}", commit.author, commit.author_email);
+
+        let author_stats = self.author_stats.entry(author_key).or_insert(AuthorStats {
+            name: commit.author.clone(),
+            email: commit.author_email.clone(),
+            commits: 0,
+            files_touched: HashSet::new(),
+            first_commit: commit.authored_date,
+            last_commit: commit.authored_date,
+            lines_added: 0,
+            lines_removed: 0,
+        });
+
+        author_stats.commits += 1;
+        author_stats.lines_added += commit.insertions;
+        author_stats.lines_removed += commit.deletions;
+
+        if commit.authored_date < author_stats.first_commit {
+            author_stats.first_commit = commit.authored_date;
+        }
+        if commit.authored_date > author_stats.last_commit {
+            author_stats.last_commit = commit.authored_date;
+        }
+
+        for file in &commit.files_changed {
+            author_stats.files_touched.insert(file.clone());
+        }
+    }
+
+    /// Record a commit in the history of every file it changes
+    pub fn update_file_history(&mut self, commit: &CommitInfo) {
+        for file_path in &commit.files_changed {
+            let file_history = self
+                .file_history
+                .entry(file_path.clone())
+                .or_insert(FileHistory {
+                    path: file_path.clone(),
+                    commits: Vec::new(),
+                    authors: HashSet::new(),
+                    first_commit: commit.authored_date,
+                    last_commit: commit.authored_date,
+                    total_changes: 0,
+                });
+
+            file_history.commits.push(commit.id.clone());
+            file_history.authors.insert(commit.author.clone());
+            file_history.total_changes += 1;
+
+            if commit.authored_date < file_history.first_commit {
+                file_history.first_commit = commit.authored_date;
+            }
+            if commit.authored_date > file_history.last_commit {
+                file_history.last_commit = commit.authored_date;
+            }
+        }
+    }
+
+    /// Record a whitespace-only commit in the timeline of every file it changes, without
+    /// counting it as churn or crediting its author
+    pub fn update_file_timeline(&mut self, commit: &CommitInfo) {
+        for file_path in &commit.files_changed {
+            let file_history = self
+                .file_history
+                .entry(file_path.clone())
+                .or_insert(FileHistory {
+                    path: file_path.clone(),
+                    commits: Vec::new(),
+                    authors: HashSet::new(),
+                    first_commit: commit.authored_date,
+                    last_commit: commit.authored_date,
+                    total_changes: 0,
+                });
+
+            file_history.commits.push(commit.id.clone());
+            if commit.authored_date < file_history.first_commit {
+                file_history.first_commit = commit.authored_date;
+            }
+            if commit.authored_date > file_history.last_commit {
+                file_history.last_commit = commit.authored_date;
+            }
+        }
+    }
+
     /// Get top contributors by various metrics
     pub fn get_top_contributors(&self, limit: usize) -> Vec<(&String, &AuthorStats)> {
         let mut authors: Vec<_> = self.author_stats.iter().collect();
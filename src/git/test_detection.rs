@@ -0,0 +1,182 @@
+use super::TestAnalysis;
+use anyhow::Result;
+use git2::{ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use regex::Regex;
+use std::collections::{BTreeSet, HashSet};
+
+/// Directory names that conventionally hold tests
+const TEST_DIR_NAMES: &[&str] = &["test", "tests", "__tests__", "spec", "specs", "testing"];
+
+/// Vendored or generated trees whose tests are not the project's own
+const SKIPPED_DIRS: &[&str] = &["node_modules", "vendor", "third_party", "target", "dist"];
+
+/// Manifests larger than this are not read
+const MAX_MANIFEST_BYTES: usize = 1024 * 1024;
+
+/// (framework, manifest file name, substring that must appear in it)
+const MANIFEST_SIGNALS: &[(&str, &str, &str)] = &[
+    ("pytest", "requirements.txt", "pytest"),
+    ("pytest", "requirements-dev.txt", "pytest"),
+    ("pytest", "pyproject.toml", "pytest"),
+    ("pytest", "setup.cfg", "pytest"),
+    ("pytest", "setup.py", "pytest"),
+    ("pytest", "tox.ini", "pytest"),
+    ("jest", "package.json", "\"jest\""),
+    ("mocha", "package.json", "\"mocha\""),
+    ("vitest", "package.json", "\"vitest\""),
+    ("junit", "pom.xml", "junit"),
+    ("junit", "build.gradle", "junit"),
+    ("junit", "build.gradle.kts", "junit"),
+    ("rspec", "Gemfile", "rspec"),
+];
+
+/// (tool, file name) whose presence indicates coverage reporting
+const COVERAGE_FILES: &[(&str, &str)] = &[
+    ("coverage.py", ".coveragerc"),
+    ("codecov", "codecov.yml"),
+    ("codecov", ".codecov.yml"),
+    ("coveralls", ".coveralls.yml"),
+    ("tarpaulin", "tarpaulin.toml"),
+    ("simplecov", ".simplecov"),
+    ("nyc", ".nycrc"),
+    ("nyc", ".nycrc.json"),
+];
+
+/// (tool, manifest file name, substring that must appear in it)
+const COVERAGE_SIGNALS: &[(&str, &str, &str)] = &[
+    ("jacoco", "pom.xml", "jacoco"),
+    ("jacoco", "build.gradle", "jacoco"),
+    ("nyc", "package.json", "\"nyc\""),
+    ("c8", "package.json", "\"c8\""),
+    ("pytest-cov", "requirements.txt", "pytest-cov"),
+    ("pytest-cov", "pyproject.toml", "pytest-cov"),
+    ("simplecov", "Gemfile", "simplecov"),
+];
+
+/// Detect test files, directories, frameworks and coverage tooling from the HEAD tree.
+/// Reads the committed tree rather than the working directory so bare clones work too.
+pub fn analyze_tests(repo: &Repository) -> Result<TestAnalysis> {
+    let mut analysis = TestAnalysis {
+        total_test_files: 0,
+        test_directories: Vec::new(),
+        test_frameworks: HashSet::new(),
+        has_regression_tests: false,
+        test_patterns_found: Vec::new(),
+        test_coverage_indicators: Vec::new(),
+    };
+
+    let tree = match repo.head().and_then(|head| head.peel_to_tree()) {
+        Ok(tree) => tree,
+        // Empty repositories have nothing to inspect
+        Err(_) => return Ok(analysis),
+    };
+
+    let test_file = Regex::new(
+        r"(?i)(^|/)(test_[^/]+\.py|[^/]+_test\.(py|go|rb|exs?)|[^/]+\.(test|spec)\.[jt]sx?|[^/]+Tests?\.(java|kt|cs)|[^/]+_spec\.rb)$",
+    )?;
+    let regression = Regex::new(r"(?i)(regress|cve[-_]?\d|issue[-_]?\d+|bug[-_]?\d+)")?;
+
+    let mut directories = BTreeSet::new();
+    let mut coverage = BTreeSet::new();
+    let mut has_go_mod = false;
+    let mut has_cargo_toml = false;
+    let mut has_go_tests = false;
+    let mut has_rust_tests = false;
+
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let name = entry.name().unwrap_or_default();
+        if entry.kind() == Some(ObjectType::Tree) {
+            return if SKIPPED_DIRS.contains(&name) {
+                TreeWalkResult::Skip
+            } else {
+                TreeWalkResult::Ok
+            };
+        }
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+
+        let path = format!("{}{}", dir, name);
+        let test_dir = dir
+            .trim_end_matches('/')
+            .split('/')
+            .position(|component| TEST_DIR_NAMES.contains(&component.to_lowercase().as_str()));
+
+        if test_dir.is_some() || test_file.is_match(&path) {
+            analysis.total_test_files += 1;
+            if let Some(depth) = test_dir {
+                let components: Vec<_> = dir.trim_end_matches('/').split('/').collect();
+                directories.insert(components[..=depth].join("/"));
+            }
+            if regression.is_match(&path) {
+                analysis.test_patterns_found.push(path.clone());
+            }
+            has_go_tests |= name.ends_with("_test.go");
+            has_rust_tests |= name.ends_with(".rs");
+        }
+
+        match name {
+            "go.mod" => has_go_mod = true,
+            "Cargo.toml" => has_cargo_toml = true,
+            "conftest.py" | "pytest.ini" => {
+                analysis.test_frameworks.insert("pytest".to_string());
+            }
+            ".rspec" => {
+                analysis.test_frameworks.insert("rspec".to_string());
+            }
+            _ => {}
+        }
+        for (tool, file) in COVERAGE_FILES {
+            if name == *file {
+                coverage.insert(tool.to_string());
+            }
+        }
+
+        let manifest_signals = MANIFEST_SIGNALS
+            .iter()
+            .map(|(framework, file, needle)| (true, *framework, *file, *needle))
+            .chain(
+                COVERAGE_SIGNALS
+                    .iter()
+                    .map(|(tool, file, needle)| (false, *tool, *file, *needle)),
+            )
+            .filter(|(_, _, file, _)| *file == name)
+            .collect::<Vec<_>>();
+        if !manifest_signals.is_empty() {
+            if let Some(content) = read_manifest(repo, entry.id()) {
+                for (is_framework, label, _, needle) in manifest_signals {
+                    if content.contains(needle) {
+                        if is_framework {
+                            analysis.test_frameworks.insert(label.to_string());
+                        } else {
+                            coverage.insert(label.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        TreeWalkResult::Ok
+    })?;
+
+    if has_go_mod && has_go_tests {
+        analysis.test_frameworks.insert("go test".to_string());
+    }
+    if has_cargo_toml && has_rust_tests {
+        analysis.test_frameworks.insert("cargo test".to_string());
+    }
+
+    analysis.test_directories = directories.into_iter().collect();
+    analysis.test_coverage_indicators = coverage.into_iter().collect();
+    analysis.has_regression_tests = !analysis.test_patterns_found.is_empty();
+
+    Ok(analysis)
+}
+
+fn read_manifest(repo: &Repository, id: git2::Oid) -> Option<String> {
+    let blob = repo.find_blob(id).ok()?;
+    if blob.size() > MAX_MANIFEST_BYTES {
+        return None;
+    }
+    Some(String::from_utf8_lossy(blob.content()).to_string())
+}
@@ -1,41 +1,82 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use colored::*;
-use std::path::PathBuf;
-use tracing::{info, Level};
-use tracing_subscriber;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn, Level};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 mod analysis;
+mod cancel;
 mod config;
+mod error;
 mod git;
 mod output;
 mod patterns;
+mod profile;
 
-use analysis::CodeAnalyzer;
+use analysis::{
+    Anonymizer, BranchComparison, ChurnBucketing, CodeAnalyzer, CombinedFindings, LicensePolicy,
+    MultiRepoFindings, PhaseTimings, Policy, RepositoryFindings, RiskTier,
+};
 use config::Config;
-use git::GitAnalyzer;
+use git::{FormattingCommits, GitAnalyzer, SamplingStrategy};
 use output::Reporter;
-use patterns::PatternEngine;
+use patterns::{Confidence, PatternEngine, RemediationGuide};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Repository path to analyze
-    #[arg(short, long)]
-    repo: PathBuf,
+    /// Repository path(s) to analyze
+    #[arg(short, long, num_args = 1.., required_unless_present_any = ["repos_file", "git_dir", "patch", "emit_schema", "list_patterns"])]
+    repo: Vec<PathBuf>,
+
+    /// File listing repository paths to analyze, one per line
+    #[arg(long)]
+    repos_file: Option<PathBuf>,
+
+    /// Git directory of the repository, for layouts where it is not `<repo>/.git`
+    #[arg(long, value_name = "DIR", conflicts_with = "repos_file")]
+    git_dir: Option<PathBuf>,
+
+    /// Working tree to pair with --git-dir [default: the --repo path]
+    #[arg(long, value_name = "DIR", requires = "git_dir")]
+    work_tree: Option<PathBuf>,
 
-    /// Pattern set to use (vuln, memory, crypto, all)
+    /// Pattern set to use (vuln, memory, crypto, all, debt, regression). Regression patterns
+    /// match added diff lines, so they only apply to --commit, --working-tree and --patch scans
     #[arg(short, long, default_value = "vuln")]
     patterns: String,
 
-    /// Output format (html, json)
+    /// JSON file with custom patterns; overrides same-named config and built-in patterns
+    #[arg(long, value_name = "PATH")]
+    pattern_file: Option<PathBuf>,
+
+    /// Override a pattern's severity, e.g. "Cross-Site Scripting=high" (repeatable)
+    #[arg(long, value_name = "PATTERN=SEVERITY")]
+    severity_map: Vec<String>,
+
+    /// Drop a pattern by name from the effective set, e.g. "Security Fix" (repeatable)
+    #[arg(long, value_name = "NAME")]
+    disable_pattern: Vec<String>,
+
+    /// Print the effective pattern set after merging all sources, then exit; with --output json
+    /// as a machine-readable catalog
+    #[arg(long)]
+    list_patterns: bool,
+
+    /// Output format (html, json, findings-json, matches, sqlite, checklist, gitlab)
     #[arg(short, long, default_value = "html")]
     output: String,
 
-    /// Output file (report.html|json)
+    /// Output file (report.html|json|db); may contain {repo}, {date} and {branch} placeholders
     #[arg(long, default_value = "report_commit_raider")]
     output_file: String,
 
+    /// Write JSON output (reports, matches, --emit-schema) on a single line instead of pretty-printed
+    #[arg(long)]
+    compact: bool,
+
     /// Show only CVE references
     #[arg(short, long)]
     cve_only: bool,
@@ -44,6 +85,10 @@ struct Cli {
     #[arg(short, long)]
     stats: bool,
 
+    /// Scan for patterns even when --stats finds the repository is mostly documentation or text
+    #[arg(long)]
+    force: bool,
+
     /// Minimum days since last commit to flag as stale
     #[arg(long, default_value = "365")]
     stale_days: u64,
@@ -55,55 +100,867 @@ struct Cli {
     /// Number of threads for Rayon parallel vulnerability scanning (0 = auto-detect CPU cores)
     #[arg(short, long, default_value = "0")]
     threads: usize,
+
+    /// Threads for the pattern scan, in a pool of their own; 0 scans on the --threads pool [default: 0]
+    #[arg(long, value_name = "N")]
+    scan_threads: Option<usize>,
+
+    /// Restrict complexity analysis to these file extensions (comma-separated, e.g. rs,py)
+    #[arg(long, value_delimiter = ',')]
+    languages: Vec<String>,
+
+    /// List changed files by spawning the git binary instead of using libgit2 in-process
+    #[arg(long)]
+    git_subprocess: bool,
+
+    /// Scan commits in batches without retaining the history, bounding memory on huge repositories
+    #[arg(long, conflicts_with_all = ["commit", "compare", "working_tree", "staged"])]
+    streaming: bool,
+
+    /// Maximum concurrent git subprocesses for changed-file lookups (default: 2x --threads, or 32)
+    #[arg(long)]
+    git_concurrency: Option<usize>,
+
+    /// Timeout in seconds for each git subprocess
+    #[arg(long)]
+    git_timeout: Option<u64>,
+
+    /// Commits kept when the history exceeds 20000: recent (the newest), uniform (every k-th across the full history) or stratified (proportional per time span) [default: recent]
+    #[arg(long, value_name = "STRATEGY")]
+    sample: Option<String>,
+
+    /// Whitespace-only commits: count (like any other), downweight (no churn or authorship) or exclude (left out of file and author stats) [default: count]
+    #[arg(long, value_name = "MODE")]
+    formatting_commits: Option<String>,
+
+    /// Count commits by bots (dependabot, renovate, [bot] authors, ...) in authorship, churn and bus factor
+    #[arg(long)]
+    include_bots: bool,
+
+    /// Scan at most this many bytes of each commit message, 0 for no limit [default: 65536]
+    #[arg(long, value_name = "BYTES")]
+    max_message_length: Option<usize>,
+
+    /// Report files touched by at most this many commits as single-commit files [default: 1]
+    #[arg(long, value_name = "N")]
+    single_commit_threshold: Option<usize>,
+
+    /// Report commits changing at least this many files as scattered commits [default: 50]
+    #[arg(long, value_name = "N")]
+    scattered_commit_threshold: Option<usize>,
+
+    /// Report binary files changed at least this many times as churning binaries [default: 3]
+    #[arg(long, value_name = "N")]
+    binary_churn_threshold: Option<usize>,
+
+    /// Commit trailers to extract (comma-separated, e.g. Reviewed-by,CVE); replaces the default set
+    #[arg(long, value_delimiter = ',', value_name = "KEYS")]
+    trailers: Vec<String>,
+
+    /// Dependency names never flagged as typosquats or confusion candidates (comma-separated,
+    /// a trailing * matches any suffix, e.g. acme-*,@acme/*)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    dependency_allowlist: Vec<String>,
+
+    /// Additional globs of CI and security policy files whose changes are reported, on top of
+    /// workflows, CODEOWNERS, SECURITY.md and dependabot config (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "GLOBS")]
+    meta_files: Vec<String>,
+
+    /// SPDX expression of the project license that dependency licenses must be compatible with,
+    /// e.g. "MIT OR Apache-2.0" (default: the license in Cargo.toml or package.json)
+    #[arg(long, value_name = "SPDX")]
+    project_license: Option<String>,
+
+    /// Skip files excluded by .gitignore (at any depth), .git/info/exclude, the global excludes
+    /// file and .ignore files when walking the working tree (the default)
+    #[arg(long, overrides_with = "no_ignore")]
+    respect_gitignore: bool,
+
+    /// Walk ignored files too, e.g. a gitignored build/ directory that should be analyzed
+    #[arg(long, overrides_with = "respect_gitignore")]
+    no_ignore: bool,
+
+    /// Cache per-file complexity metrics in this directory, keyed by content, to speed up --stats reruns
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Inspect branch reflogs for rewritten history (force pushes, rebases, resets)
+    #[arg(long)]
+    detect_rewrites: bool,
+
+    /// Analyze commits reachable from tags instead of HEAD and group findings per release
+    #[arg(long)]
+    tags: bool,
+
+    /// Analyze commits reachable from this tag (implies --tags)
+    #[arg(long, value_name = "NAME")]
+    tag: Option<String>,
+
+    /// Report blobs in history at or above the size threshold and count them as a risk factor
+    #[arg(long)]
+    large_blobs: bool,
+
+    /// Size threshold in bytes for --large-blobs (implies --large-blobs)
+    #[arg(long, value_name = "BYTES")]
+    large_blob_threshold: Option<u64>,
+
+    /// Flag commits authored at unusual hours or timezones for their author as low-confidence findings
+    #[arg(long)]
+    timing_anomalies: bool,
+
+    /// Collapse findings that share a pattern and touch overlapping files within a time window into one
+    #[arg(long)]
+    group_duplicates: bool,
+
+    /// Days between commits whose findings --group-duplicates may collapse (implies --group-duplicates) [default: 30]
+    #[arg(long, value_name = "DAYS")]
+    duplicate_window: Option<i64>,
+
+    /// Exit with a non-zero status if the risk tier is at or above this level (critical, high, medium, low)
+    #[arg(long)]
+    fail_on_tier: Option<String>,
+
+    /// JSON policy file of fail conditions (e.g. no critical findings, bus factor of at least 2);
+    /// the first failing condition sets the exit status and message
+    #[arg(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+
+    /// Heatmap churn buckets: linear (fifths of the busiest file) or quantile (fifths of all files) [default: linear]
+    #[arg(long, value_name = "MODE")]
+    heatmap_buckets: Option<String>,
+
+    /// Roll up findings and complexity per directory down to N path components [default: 2]
+    #[arg(long, value_name = "N")]
+    directory_depth: Option<usize>,
+
+    /// Highlight findings from commits at most N days old as recent findings [default: 30]
+    #[arg(long, value_name = "N")]
+    recent_days: Option<u32>,
+
+    /// Render at most N findings, the highest-risk ones in their usual order, in the HTML report,
+    /// which notes how many of all findings are shown; 0 renders all [default: 0]
+    #[arg(long, value_name = "N")]
+    max_report_findings: Option<usize>,
+
+    /// Date reports, finding ages and trend/SQLite run timestamps at this date (Unix seconds,
+    /// RFC 3339 or YYYY-MM-DD) instead of the current time, for reproducible output
+    #[arg(long, value_name = "DATE", env = "SOURCE_DATE_EPOCH")]
+    report_date: Option<String>,
+
+    /// Display dates in reports in this IANA timezone, e.g. Europe/Berlin [default: UTC]
+    #[arg(long, value_name = "TZ")]
+    timezone: Option<String>,
+
+    /// Directory with custom Tera templates (must contain report.html); partials override the built-in ones
+    #[arg(long)]
+    template_dir: Option<PathBuf>,
+
+    /// Replace author names and emails with stable pseudonyms in the report
+    #[arg(long)]
+    anonymize: bool,
+
+    /// Validate configuration, patterns and repositories, print what would be scanned, then exit
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Append this run's overall risk to a trend file (.csv or JSON) and chart its history
+    #[arg(long, value_name = "PATH")]
+    trend_file: Option<PathBuf>,
+
+    /// Print how the overall risk score was computed
+    #[arg(long)]
+    explain: bool,
+
+    /// Print how often each loaded pattern matched, to spot dead or noisy rules
+    #[arg(long)]
+    pattern_stats: bool,
+
+    /// Print wall time, items processed and peak memory of each analysis phase
+    #[arg(long)]
+    profile: bool,
+
+    /// Only count matches at or above this confidence (high, medium, low) for --fail-on-tier
+    #[arg(long)]
+    min_confidence: Option<String>,
+
+    /// Analyze only this commit (full or abbreviated id), scanning its message and diff
+    #[arg(long, value_name = "SHA", conflicts_with_all = ["tags", "tag", "working_tree", "staged"])]
+    commit: Option<String>,
+
+    /// Analyze history reachable from this branch, remote branch, tag or commit instead of HEAD,
+    /// without checking it out
+    #[arg(
+        long = "ref",
+        value_name = "REF",
+        conflicts_with_all = ["commit", "compare", "tags", "tag", "working_tree", "staged"]
+    )]
+    git_ref: Option<String>,
+
+    /// Analyze the history of two branches (or any revisions) and compare their findings
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["BASE", "BRANCH"],
+        conflicts_with_all = ["commit", "tags", "tag", "working_tree", "staged"]
+    )]
+    compare: Option<Vec<String>>,
+
+    /// Scan uncommitted changes (staged and unstaged) to tracked files instead of the commit history
+    #[arg(long)]
+    working_tree: bool,
+
+    /// Scan only staged changes, e.g. from a pre-commit hook (implies --working-tree)
+    #[arg(long)]
+    staged: bool,
+
+    /// With --working-tree or --staged, also scan the contents of untracked, non-ignored files
+    /// (ignored ones as well with --no-ignore)
+    #[arg(long)]
+    include_untracked: bool,
+
+    /// Scan a patch or mbox file (git format-patch output, or a plain diff) without a repository,
+    /// one finding per patch
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["repo", "repos_file", "git_dir", "commit", "compare", "working_tree", "staged", "streaming", "tags", "tag", "stats"]
+    )]
+    patch: Option<PathBuf>,
+
+    /// Print the JSON Schema of the JSON report and exit
+    #[arg(long)]
+    emit_schema: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let started = Instant::now();
     let cli = Cli::parse();
 
+    // Schema goes to stdout untouched so it can be piped into validators
+    if cli.emit_schema {
+        println!("{}", output::findings_schema(cli.compact)?);
+        return Ok(());
+    }
+
+    // The JSON pattern catalog owns stdout so it can be piped
+    let pattern_catalog = cli.list_patterns
+        && matches!(
+            output::OutputFormat::from(cli.output.as_str()),
+            output::OutputFormat::Json
+        );
+
     // Initialize logging
     let level = if cli.verbose {
         Level::DEBUG
     } else {
         Level::INFO
     };
+    let log_writer = if pattern_catalog {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     tracing_subscriber::fmt()
         .with_max_level(level)
         .with_target(false)
+        .with_writer(log_writer)
         .init();
 
+    if cli.profile {
+        profile::enable();
+    }
+
     if cli.threads > 0 {
         rayon::ThreadPoolBuilder::new()
             .num_threads(cli.threads)
             .build_global()?;
     }
 
+    if !pattern_catalog {
+        println!(
+            "{}",
+            "CommitRaider - Git History Security Scanner"
+                .bright_cyan()
+                .bold()
+        );
+    }
+
+    let fail_on_tier = cli
+        .fail_on_tier
+        .as_deref()
+        .map(str::parse::<RiskTier>)
+        .transpose()?;
+    let min_confidence = cli
+        .min_confidence
+        .as_deref()
+        .map(str::parse::<Confidence>)
+        .transpose()?;
+    // Loaded before scanning so a broken policy fails fast
+    let policy = cli.policy.as_deref().map(Policy::load).transpose()?;
+    if cli.include_untracked && !(cli.working_tree || cli.staged) {
+        anyhow::bail!("--include-untracked requires --working-tree or --staged");
+    }
+
+    let repos = if cli.list_patterns || cli.patch.is_some() {
+        Vec::new()
+    } else {
+        collect_repositories(&cli)?
+    };
+
+    let mut config = Config::load()?;
+    if let Some(pattern_file) = &cli.pattern_file {
+        config.patterns.pattern_file = Some(pattern_file.clone());
+    }
+    for entry in &cli.severity_map {
+        let (name, severity) = entry.split_once('=').with_context(|| {
+            format!(
+                "Invalid --severity-map '{}', expected PATTERN=SEVERITY",
+                entry
+            )
+        })?;
+        config
+            .patterns
+            .severity_map
+            .insert(name.trim().to_string(), severity.trim().to_string());
+    }
+    config.patterns.disabled_patterns.extend(
+        cli.disable_pattern
+            .iter()
+            .map(|name| name.trim().to_string()),
+    );
+    config.analysis.detect_history_rewrites |= cli.detect_rewrites;
+    if let Some(threads) = cli.scan_threads {
+        config.patterns.scan_threads = threads;
+    }
+    if let Some(max_message_length) = cli.max_message_length {
+        config.patterns.max_message_length = max_message_length;
+    }
+    if let Some(threshold) = cli.single_commit_threshold {
+        config.analysis.single_commit_threshold = threshold;
+    }
+    if let Some(threshold) = cli.scattered_commit_threshold {
+        config.analysis.scattered_commit_threshold = threshold;
+    }
+    if let Some(threshold) = cli.binary_churn_threshold {
+        config.analysis.binary_churn_threshold = threshold;
+    }
+    if !cli.trailers.is_empty() {
+        config.analysis.trailer_keys = cli.trailers.clone();
+    }
+    config
+        .analysis
+        .dependency_allowlist
+        .extend(cli.dependency_allowlist.iter().cloned());
+    config
+        .analysis
+        .meta_file_globs
+        .extend(cli.meta_files.iter().cloned());
+    if let Some(license) = &cli.project_license {
+        // Reject a malformed expression before spending time on the analysis
+        LicensePolicy::parse(license).map_err(|e| anyhow::anyhow!("--project-license: {}", e))?;
+        config.analysis.project_license = Some(license.clone());
+    }
+    config.analysis.scan_tags |= cli.tags || cli.tag.is_some();
+    config.analysis.tag = cli.tag.clone();
+    config.analysis.commit = cli.commit.clone();
+    config.analysis.revision = cli.git_ref.clone();
+    config.analysis.git_dir = cli.git_dir.clone();
+    config.analysis.work_tree = cli.work_tree.clone();
+    if let Some(threshold) = cli.large_blob_threshold {
+        config.analysis.large_blob_threshold_bytes = threshold;
+    }
+    config.analysis.detect_large_blobs |= cli.large_blobs || cli.large_blob_threshold.is_some();
+    config.analysis.detect_timing_anomalies |= cli.timing_anomalies;
+    if let Some(days) = cli.duplicate_window {
+        config.analysis.duplicate_window_days = days;
+    }
+    config.analysis.group_duplicates |= cli.group_duplicates || cli.duplicate_window.is_some();
+    config.analysis.use_git_subprocess |= cli.git_subprocess;
+    config.analysis.streaming |= cli.streaming;
+    if let Some(concurrency) = cli.git_concurrency {
+        config.analysis.git_concurrency = concurrency;
+    } else if cli.threads > 0 {
+        // An explicit thread budget signals a constrained (or generous) machine
+        config.analysis.git_concurrency = cli.threads * 2;
+    }
+    if let Some(timeout) = cli.git_timeout {
+        config.analysis.git_timeout_secs = timeout;
+    }
+    if let Some(strategy) = &cli.sample {
+        config.analysis.sampling = strategy.parse::<SamplingStrategy>()?;
+    }
+    if let Some(mode) = &cli.formatting_commits {
+        config.analysis.formatting_commits = mode.parse::<FormattingCommits>()?;
+    }
+    config.analysis.include_bots |= cli.include_bots;
+    if cli.no_ignore {
+        config.analysis.respect_gitignore = false;
+    } else if cli.respect_gitignore {
+        config.analysis.respect_gitignore = true;
+    }
+    if let Some(cache_dir) = &cli.cache_dir {
+        config.analysis.cache_dir = Some(cache_dir.clone());
+    }
+    config.output.compact_json |= cli.compact;
+    if let Some(bucketing) = &cli.heatmap_buckets {
+        config.output.heatmap_bucketing = bucketing.parse::<ChurnBucketing>()?;
+    }
+    if let Some(depth) = cli.directory_depth {
+        if depth == 0 {
+            anyhow::bail!("--directory-depth must be at least 1");
+        }
+        config.output.directory_depth = depth;
+    }
+    if let Some(days) = cli.recent_days {
+        config.output.recent_days = days.into();
+    }
+    if let Some(limit) = cli.max_report_findings {
+        config.output.max_report_findings = limit;
+    }
+    if let Some(timezone) = &cli.timezone {
+        config.output.timezone = timezone.clone();
+    }
+    // Reject unknown timezones before spending time on the analysis
+    config.output.timezone()?;
+    if let Some(date) = &cli.report_date {
+        config.output.report_date = Some(date.clone());
+    }
+    config.output.report_date()?;
+    if let Some(template_dir) = &cli.template_dir {
+        // Fail fast on a broken template directory instead of after the analysis
+        output::html::HtmlGenerator::new(Some(template_dir))?;
+        config.output.template_dir = Some(template_dir.clone());
+    }
+    // Reject unknown placeholders before spending time on the analysis
+    output::expand_output_path(&cli.output_file, None, None)?;
+    if let Some(output_name) = Path::new(&cli.output_file).file_name() {
+        let output_name = output_name.to_string_lossy();
+        let output_base = output_name
+            .strip_suffix(".html")
+            .or_else(|| output_name.strip_suffix(".json"))
+            .or_else(|| output_name.strip_suffix(".db"))
+            .or_else(|| output_name.strip_suffix(".md"))
+            .unwrap_or(&output_name);
+        // Only the literal prefix before any placeholder identifies our reports
+        let output_base = output_base
+            .split('{')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if !output_base.is_empty() && !config.analysis.report_artifact_names.contains(&output_base)
+        {
+            config.analysis.report_artifact_names.push(output_base);
+        }
+    }
+    if !cli.languages.is_empty() {
+        let requested: Vec<String> = cli
+            .languages
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+            .collect();
+        for ext in &requested {
+            if !config.analysis.complexity_extensions.contains(ext) {
+                warn!(
+                    "Extension '{}' is not enabled in analysis.complexity_extensions and will be ignored",
+                    ext
+                );
+            }
+        }
+        config
+            .analysis
+            .complexity_extensions
+            .retain(|ext| requested.contains(ext));
+    }
+
+    let pattern_engine = PatternEngine::new(&cli.patterns, &config)?;
+    let remediation = RemediationGuide::load(&config.patterns.cwe_remediation)?;
+
+    if pattern_catalog {
+        let patterns: Vec<_> = pattern_engine
+            .patterns()
+            .map(|(pattern, _)| pattern)
+            .collect();
+        println!("{}", output::to_json(&patterns, cli.compact)?);
+        return Ok(());
+    }
+    if cli.list_patterns {
+        print_patterns(&pattern_engine);
+        return Ok(());
+    }
+    if cli.dry_run {
+        return print_dry_run(&cli, &config, &pattern_engine, &repos);
+    }
+    if let Some(revisions) = &cli.compare {
+        if repos.len() > 1 {
+            anyhow::bail!("--compare analyzes a single repository");
+        }
+        let comparison = compare_revisions(
+            &cli,
+            &config,
+            &pattern_engine,
+            &remediation,
+            &repos[0],
+            (revisions[0].as_str(), revisions[1].as_str()),
+        )
+        .await?;
+        print_comparison(&comparison);
+        return Ok(());
+    }
+
+    // From here on an interrupt still leaves a (partial) report behind
+    cancel::listen();
+
+    let (highest_tier, timings, policy_violation) = if cli.patch.is_some() || repos.len() == 1 {
+        let source = cli.patch.as_deref().unwrap_or_else(|| repos[0].as_path());
+        let findings = if cli.patch.is_some() {
+            analyze_patch(&cli, &config, &pattern_engine, &remediation, source)?
+        } else {
+            analyze_repository(&cli, &config, &pattern_engine, &remediation, source).await?
+        };
+
+        let partial = cancel::is_cancelled();
+        let mut reporter = Reporter::new(
+            &cli.output,
+            &cli.output_file,
+            &config.output,
+            Some(&findings.git_stats),
+        )?;
+        reporter.set_partial(partial);
+        // An interrupted run would show up as a drop in risk
+        if let Some(trend_file) = cli.trend_file.as_ref().filter(|_| !partial) {
+            reporter.set_trend(output::trend::record(trend_file, &findings)?);
+        }
+        reporter
+            .generate_report(&findings, cli.cve_only, cli.stats)
+            .await?;
+
+        print_tier_summary(&findings);
+        if cli.explain {
+            print_risk_breakdown(&findings);
+        }
+        if cli.pattern_stats {
+            print_pattern_hits(&findings);
+        }
+        let policy_violation = policy.as_ref().and_then(|p| {
+            p.evaluate(&gate_findings(&findings, min_confidence))
+                .map(|v| (repository_name(source), v))
+        });
+        (
+            gate_tier(&findings, min_confidence),
+            findings.timings,
+            policy_violation,
+        )
+    } else {
+        let mut multi = MultiRepoFindings::default();
+        let mut highest_gate_tier = RiskTier::Low;
+        let mut timings = PhaseTimings::default();
+        let mut used_names = std::collections::HashSet::new();
+        let mut policy_violation = None;
+
+        for repo in &repos {
+            let findings =
+                analyze_repository(&cli, &config, &pattern_engine, &remediation, repo).await?;
+
+            // Suffix repeated names with the first free number; a repository may itself be
+            // called like a suffixed name, e.g. `api`, `api` and `api_2`
+            let base_name = repository_name(repo);
+            let mut name = base_name.clone();
+            let mut suffix = 1;
+            while !used_names.insert(name.clone()) {
+                suffix += 1;
+                name = format!("{}_{}", base_name, suffix);
+            }
+
+            // Use the de-duplicated name so clones of the same remote don't collide
+            let output_file = if cli.output_file.contains("{repo}") {
+                cli.output_file.replace("{repo}", &name)
+            } else {
+                format!("{}_{}", cli.output_file, name)
+            };
+            let partial = cancel::is_cancelled();
+            let mut reporter = Reporter::new(
+                &cli.output,
+                &output_file,
+                &config.output,
+                Some(&findings.git_stats),
+            )?;
+            reporter.set_partial(partial);
+            if let Some(trend_file) = cli.trend_file.as_ref().filter(|_| !partial) {
+                reporter.set_trend(output::trend::record(trend_file, &findings)?);
+            }
+            reporter
+                .generate_report(&findings, cli.cve_only, cli.stats)
+                .await?;
+
+            print_tier_summary(&findings);
+            if cli.explain {
+                print_risk_breakdown(&findings);
+            }
+            if cli.pattern_stats {
+                print_pattern_hits(&findings);
+            }
+            let tier = gate_tier(&findings, min_confidence);
+            if tier.is_at_least(highest_gate_tier) {
+                highest_gate_tier = tier;
+            }
+            if policy_violation.is_none() {
+                policy_violation = policy.as_ref().and_then(|p| {
+                    p.evaluate(&gate_findings(&findings, min_confidence))
+                        .map(|v| (name.clone(), v))
+                });
+            }
+            timings.add(&findings.timings);
+            multi.repositories.push(RepositoryFindings {
+                name,
+                report_path: Some(reporter.output_path().to_string()),
+                findings,
+            });
+            if partial {
+                warn!("Interrupted, skipping the remaining repositories");
+                break;
+            }
+        }
+
+        let mut reporter = Reporter::new(&cli.output, &cli.output_file, &config.output, None)?;
+        reporter.set_partial(cancel::is_cancelled());
+        reporter.generate_multi_repo_report(&multi).await?;
+
+        (highest_gate_tier, timings, policy_violation)
+    };
+
+    if cancel::is_cancelled() {
+        // Gating on partial results would pass scans that never finished
+        println!(
+            "\n{}",
+            "Analysis interrupted, the report is incomplete"
+                .bright_yellow()
+                .bold()
+        );
+        print_timing_summary(&timings, started.elapsed().as_secs_f64());
+        profile::print_summary();
+        std::process::exit(cancel::INTERRUPTED_EXIT_CODE);
+    }
+
+    println!("\n{}", "Analysis complete!".bright_green().bold());
+    print_timing_summary(&timings, started.elapsed().as_secs_f64());
+    profile::print_summary();
+
+    if let Some(threshold) = fail_on_tier {
+        if highest_tier.is_at_least(threshold) {
+            eprintln!(
+                "{}",
+                format!(
+                    "Risk tier {} meets --fail-on-tier threshold {}",
+                    highest_tier, threshold
+                )
+                .bright_red()
+                .bold()
+            );
+            std::process::exit(2);
+        }
+    }
+    if let Some((name, violation)) = policy_violation {
+        let message = if repos.len() > 1 {
+            format!("{}: {}", name, violation)
+        } else {
+            violation.to_string()
+        };
+        eprintln!("{}", message.bright_red().bold());
+        std::process::exit(violation.exit_code);
+    }
+
+    Ok(())
+}
+
+/// Effective pattern set, in scan order, with the source each pattern came from
+fn print_patterns(pattern_engine: &PatternEngine) {
+    println!(
+        "{}",
+        format!("{} patterns", pattern_engine.pattern_count()).bold()
+    );
+    for (pattern, source) in pattern_engine.patterns() {
+        println!(
+            "  {} [{:?}, {}] ({})",
+            pattern.name.bright_white(),
+            pattern.severity,
+            pattern.category,
+            source
+        );
+        println!("    {}", pattern.pattern.dimmed());
+    }
+}
+
+/// Summary of what a scan would do, without running it
+fn print_dry_run(
+    cli: &Cli,
+    config: &Config,
+    pattern_engine: &PatternEngine,
+    repos: &[PathBuf],
+) -> Result<()> {
     println!(
         "{}",
-        "CommitRaider - Git History Security Scanner"
-            .bright_cyan()
-            .bold()
+        "Dry run: configuration is valid".bright_green().bold()
     );
+
+    let categories: Vec<String> = pattern_engine
+        .categories()
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    println!(
+        "Patterns: {} compiled from set '{}' ({} custom)",
+        pattern_engine.pattern_count(),
+        cli.patterns,
+        config.patterns.custom_patterns.len()
+    );
+    println!("Categories: {}", categories.join(", "));
+
+    if let Some(patch) = &cli.patch {
+        println!("Patch: {}", patch.display().to_string().bright_white());
+    }
+    for repo in repos {
+        let git_analyzer = GitAnalyzer::new(repo, &config.analysis)?;
+        if cli.working_tree || cli.staged {
+            println!(
+                "Repository: {} ({} changes to analyze)",
+                repo.display().to_string().bright_white(),
+                if cli.staged { "staged" } else { "uncommitted" }
+            );
+            continue;
+        }
+        println!(
+            "Repository: {} ({} commits to analyze)",
+            repo.display().to_string().bright_white(),
+            git_analyzer.count_commits()?
+        );
+    }
+
+    let format = output::OutputFormat::from(cli.output.as_str());
     println!(
-        "Repository: {}",
-        cli.repo.display().to_string().bright_white()
+        "Output: {} report to {}",
+        cli.output,
+        output::add_file_extension(&cli.output_file, &format)
     );
+    if repos.len() > 1 {
+        println!("  (plus one report per repository)");
+    }
+
+    Ok(())
+}
+
+/// Gather repository paths from `--repo` and `--repos-file`
+fn collect_repositories(cli: &Cli) -> Result<Vec<PathBuf>> {
+    let mut repos = cli.repo.clone();
+
+    if let Some(repos_file) = &cli.repos_file {
+        let content = std::fs::read_to_string(repos_file).with_context(|| {
+            format!("Failed to read repositories file {}", repos_file.display())
+        })?;
+        repos.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(PathBuf::from),
+        );
+    }
+
+    if let Some(git_dir) = &cli.git_dir {
+        if repos.len() > 1 {
+            anyhow::bail!("--git-dir applies to a single repository");
+        }
+        if repos.is_empty() {
+            repos.push(cli.work_tree.clone().unwrap_or_else(|| git_dir.clone()));
+        }
+    }
+
+    if repos.is_empty() {
+        anyhow::bail!("No repositories to analyze");
+    }
+
+    Ok(repos)
+}
+
+/// Short name for a repository, used to label per-repo reports
+fn repository_name(repo: &Path) -> String {
+    repo.canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "repository".to_string())
+}
 
-    let config = Config::load()?;
-    let pattern_engine = PatternEngine::new(&cli.patterns)?;
+async fn analyze_repository(
+    cli: &Cli,
+    config: &Config,
+    pattern_engine: &PatternEngine,
+    remediation: &RemediationGuide,
+    repo: &Path,
+) -> Result<CombinedFindings> {
+    println!("Repository: {}", repo.display().to_string().bright_white());
 
-    let git_analyzer = GitAnalyzer::new(&cli.repo)?;
-    let code_analyzer = CodeAnalyzer::new();
-    let mut reporter = Reporter::new(&cli.output, &cli.output_file)?;
+    let git_analyzer = GitAnalyzer::new(repo, &config.analysis)?;
+    let code_analyzer =
+        CodeAnalyzer::new(&config.analysis).with_reference_date(config.output.reference_date());
 
     info!("Starting repository analysis...");
 
-    let git_stats = git_analyzer.analyze().await?;
+    let mut timings = PhaseTimings::default();
+
+    let working_tree = cli.working_tree || cli.staged;
+    let phase = Instant::now();
+    // Streaming scans each batch as it is read, so its findings exist before the history does
+    let mut streamed_findings = Vec::new();
+    let mut streamed_scan_secs = 0.0;
+    let (git_stats, added_lines) = if working_tree {
+        info!("Analyzing uncommitted changes...");
+        git_analyzer.analyze_working_tree(cli.staged, cli.include_untracked)?
+    } else if cli.commit.is_some() {
+        let git_stats = git_analyzer.analyze().await?;
+        let added_lines = match git_stats.commit_history.first() {
+            Some(commit) => git_analyzer.commit_added_lines(&commit.id)?,
+            None => Vec::new(),
+        };
+        (git_stats, added_lines)
+    } else if config.analysis.streaming {
+        info!("Streaming commits through the pattern scan...");
+        let git_stats = git_analyzer
+            .analyze_streaming(|batch| {
+                let scan = Instant::now();
+                streamed_findings.extend(pattern_engine.scan_commits(batch));
+                streamed_scan_secs += scan.elapsed().as_secs_f64();
+            })
+            .await?;
+        (git_stats, Vec::new())
+    } else {
+        (git_analyzer.analyze().await?, Vec::new())
+    };
+    timings.git_analysis_secs = phase.elapsed().as_secs_f64() - streamed_scan_secs;
     info!("Git analysis completed, preparing code analysis...");
 
-    let code_stats = if cli.stats {
+    let phase = Instant::now();
+    let code_stats = if cli.stats && cancel::is_cancelled() {
+        warn!("Interrupted, skipping code analysis");
+        analysis::CodeStats::default()
+    } else if cli.stats {
         info!("Stats requested, starting code analysis...");
-        code_analyzer.analyze(&cli.repo, cli.stale_days).await?
+        let code_stats = match git_analyzer.work_tree() {
+            Some(work_tree) => {
+                code_analyzer
+                    .analyze(work_tree, &git_stats, cli.stale_days)
+                    .await?
+            }
+            None => {
+                warn!(
+                    "{} has no working tree, skipping code analysis (pass --work-tree)",
+                    repo.display()
+                );
+                analysis::CodeStats::default()
+            }
+        };
+        timings.code_analysis_secs = Some(phase.elapsed().as_secs_f64());
+        code_stats
     } else {
         info!("Stats not requested, using default code stats");
         // Create minimal code stats when not requested
@@ -111,27 +968,315 @@ async fn main() -> Result<()> {
     };
     info!("Code analysis completed, preparing vulnerability scan...");
 
+    let prose_share = code_stats
+        .prose_share(&config.analysis.prose_languages)
+        .filter(|&share| share > config.analysis.max_prose_share);
+    let scan_skipped = match prose_share {
+        Some(share) if !cli.force => {
+            warn!(
+                "{} is {:.0}% documentation and text, skipping the pattern scan since matches in prose are mostly noise (pass --force to scan anyway)",
+                repo.display(),
+                share * 100.0
+            );
+            Some(format!(
+                "The repository is {:.0}% documentation and text, not code",
+                share * 100.0
+            ))
+        }
+        Some(share) => {
+            warn!(
+                "{} is {:.0}% documentation and text, expect pattern matches in prose",
+                repo.display(),
+                share * 100.0
+            );
+            None
+        }
+        None => None,
+    };
+
     info!("Starting vulnerability pattern scanning...");
-    let vulnerabilities = pattern_engine
-        .scan_repository(&cli.repo, &git_stats)
-        .await?;
+    let phase = Instant::now();
+    let mut vulnerabilities = if scan_skipped.is_some() {
+        Vec::new()
+    } else if working_tree || cli.commit.is_some() {
+        pattern_engine.scan_diff(&git_stats, &added_lines, !working_tree)
+    } else if config.analysis.streaming {
+        info!(
+            "Found {} potential vulnerabilities",
+            streamed_findings.len()
+        );
+        streamed_findings
+    } else {
+        pattern_engine.scan_repository(repo, &git_stats).await?
+    };
+    // Counted before timing anomalies, which are not produced by a loaded pattern
+    let pattern_hits =
+        pattern_engine.pattern_hits(&vulnerabilities, working_tree || cli.commit.is_some());
+    if config.analysis.detect_timing_anomalies && config.analysis.streaming {
+        warn!("Timing anomalies need the full commit history, skipping them in streaming mode");
+    } else if config.analysis.detect_timing_anomalies && !working_tree {
+        analysis::timing::annotate(
+            &mut vulnerabilities,
+            &git_stats,
+            config.output.reference_date(),
+        );
+    }
+    if config.analysis.group_duplicates {
+        analysis::grouping::group_duplicates(
+            &mut vulnerabilities,
+            config.analysis.duplicate_window_days,
+        );
+    }
+    remediation.annotate(&mut vulnerabilities);
+    // Debt markers live in file contents, which only the debt set scans
+    let marker_density = match git_analyzer.work_tree() {
+        Some(work_tree) if cli.patterns == "debt" && !cancel::is_cancelled() => {
+            pattern_engine.marker_density(work_tree, &config.analysis)?
+        }
+        _ => Vec::new(),
+    };
+    timings.pattern_scan_secs = phase.elapsed().as_secs_f64() + streamed_scan_secs;
+    if scan_skipped.is_none() {
+        profile::record(
+            "Pattern scanning",
+            "commits",
+            Duration::from_secs_f64(timings.pattern_scan_secs),
+            git_stats.total_commits,
+        );
+    }
     info!(
         "Pattern scanning complete, found {} vulnerabilities",
         vulnerabilities.len()
     );
 
-    let findings = analysis::CombinedFindings {
+    let mut findings = CombinedFindings {
         git_stats,
         code_stats,
         vulnerabilities,
         config: config.clone(),
+        timings,
+        marker_density,
+        pattern_hits,
+        scan_skipped,
     };
 
-    reporter
-        .generate_report(&findings, cli.cve_only, cli.stats)
-        .await?;
+    if cli.anonymize {
+        Anonymizer::new().anonymize(&mut findings);
+    }
 
-    println!("\n{}", "Analysis complete!".bright_green().bold());
+    Ok(findings)
+}
 
-    Ok(())
+/// Scan the patches of a patch or mbox file as commits, without a repository
+fn analyze_patch(
+    cli: &Cli,
+    config: &Config,
+    pattern_engine: &PatternEngine,
+    remediation: &RemediationGuide,
+    path: &Path,
+) -> Result<CombinedFindings> {
+    println!("Patch: {}", path.display().to_string().bright_white());
+
+    let mut timings = PhaseTimings::default();
+    let phase = Instant::now();
+    let content = std::fs::read(path)
+        .with_context(|| format!("Failed to read patch file {}", path.display()))?;
+    let patches = git::patch::parse_patches(&content, &config.analysis.trailer_keys);
+    if patches.is_empty() {
+        anyhow::bail!("{} contains no patches with a diff", path.display());
+    }
+    info!("Parsed {} patch(es)", patches.len());
+    let git_stats = git::patch::patch_stats(path, &patches);
+    timings.git_analysis_secs = phase.elapsed().as_secs_f64();
+
+    let phase = Instant::now();
+    let mut vulnerabilities = pattern_engine.scan_patches(&patches);
+    let pattern_hits = pattern_engine.pattern_hits(&vulnerabilities, true);
+    remediation.annotate(&mut vulnerabilities);
+    timings.pattern_scan_secs = phase.elapsed().as_secs_f64();
+
+    let mut findings = CombinedFindings {
+        git_stats,
+        code_stats: analysis::CodeStats::default(),
+        vulnerabilities,
+        config: config.clone(),
+        timings,
+        marker_density: Vec::new(),
+        pattern_hits,
+        scan_skipped: None,
+    };
+
+    if cli.anonymize {
+        Anonymizer::new().anonymize(&mut findings);
+    }
+
+    Ok(findings)
+}
+
+/// Analyze the histories of `base` and `branch` separately and compare their findings
+async fn compare_revisions(
+    cli: &Cli,
+    config: &Config,
+    pattern_engine: &PatternEngine,
+    remediation: &RemediationGuide,
+    repo: &Path,
+    (base, branch): (&str, &str),
+) -> Result<BranchComparison> {
+    let mut sides = Vec::new();
+    for revision in [base, branch] {
+        println!("Revision: {}", revision.bright_white());
+        let mut config = config.clone();
+        config.analysis.revision = Some(revision.to_string());
+        sides.push(analyze_repository(cli, &config, pattern_engine, remediation, repo).await?);
+    }
+    Ok(BranchComparison::new(base, &sides[0], branch, &sides[1]))
+}
+
+/// Number of findings listed for each side by --compare
+const COMPARE_FINDINGS: usize = 10;
+
+fn print_comparison(comparison: &BranchComparison) {
+    let (base, branch) = (&comparison.base, &comparison.branch);
+    println!("\n{}", "Branch comparison".bold());
+    println!("  {:<14} {:>16} {:>16}", "", base.revision, branch.revision);
+    println!(
+        "  {:<14} {:>16} {:>16}",
+        "Commits", base.commits, branch.commits
+    );
+    println!(
+        "  {:<14} {:>16} {:>16}",
+        "Findings", base.findings, branch.findings
+    );
+    println!(
+        "  {:<14} {:>16} {:>16}",
+        "Critical", base.critical_findings, branch.critical_findings
+    );
+    println!(
+        "  {:<14} {:>16} {:>16}",
+        "Overall risk",
+        format!("{:.1}/10", base.overall_risk),
+        format!("{:.1}/10", branch.overall_risk)
+    );
+    println!(
+        "  {:<14} {:>16} {:>16}",
+        "Risk tier",
+        base.tier.as_str(),
+        branch.tier.as_str()
+    );
+    println!(
+        "  {} finding(s) on commits both revisions contain",
+        comparison.shared_findings
+    );
+
+    for (revision, only) in [
+        (&branch.revision, &comparison.only_in_branch),
+        (&base.revision, &comparison.only_in_base),
+    ] {
+        println!(
+            "\n{} ({})",
+            format!("Only in {}", revision).bold(),
+            only.len()
+        );
+        for finding in only.iter().take(COMPARE_FINDINGS) {
+            println!(
+                "  {} {} (risk {:.1})",
+                &finding.commit_id[..finding.commit_id.len().min(8)],
+                finding.commit_message.lines().next().unwrap_or_default(),
+                finding.risk_score
+            );
+        }
+        if only.len() > COMPARE_FINDINGS {
+            println!("  ... and {} more", only.len() - COMPARE_FINDINGS);
+        }
+    }
+}
+
+fn print_timing_summary(timings: &PhaseTimings, total_secs: f64) {
+    println!("{}", "Timing:".bold());
+    println!("  Git analysis:     {:>8.2}s", timings.git_analysis_secs);
+    match timings.code_analysis_secs {
+        Some(secs) => println!("  Code analysis:    {:>8.2}s", secs),
+        None => println!(
+            "  Code analysis:    {}",
+            "skipped (enable with --stats)".dimmed()
+        ),
+    }
+    println!("  Pattern scanning: {:>8.2}s", timings.pattern_scan_secs);
+    println!(
+        "  {}            {}",
+        "Total:".bold(),
+        format!("{:>8.2}s", total_secs).bright_white().bold()
+    );
+}
+
+/// Risk tier used by --fail-on-tier, optionally ignoring low-confidence matches
+fn gate_tier(findings: &CombinedFindings, min_confidence: Option<Confidence>) -> RiskTier {
+    gate_findings(findings, min_confidence).risk_tier()
+}
+
+/// Findings the CI gates judge: all of them, or those at or above `min_confidence`
+fn gate_findings(
+    findings: &CombinedFindings,
+    min_confidence: Option<Confidence>,
+) -> std::borrow::Cow<'_, CombinedFindings> {
+    match min_confidence {
+        Some(min) => std::borrow::Cow::Owned(findings.with_min_confidence(min)),
+        None => std::borrow::Cow::Borrowed(findings),
+    }
+}
+
+/// Number of contributors listed per component by --explain
+const EXPLAIN_CONTRIBUTORS: usize = 3;
+
+fn print_risk_breakdown(findings: &CombinedFindings) {
+    let breakdown = findings.risk_breakdown();
+    println!(
+        "Overall risk: {} (confidence-weighted {:.1}/10)",
+        format!("{:.1}/10", breakdown.total).bright_yellow().bold(),
+        breakdown.confidence_weighted_total
+    );
+    for component in breakdown.components() {
+        println!(
+            "  {}: {:.2} points",
+            component.name.bold(),
+            component.points
+        );
+        for contributor in component.top_contributors(EXPLAIN_CONTRIBUTORS) {
+            println!("    +{:.2}  {}", contributor.points, contributor.label);
+        }
+    }
+}
+
+fn print_pattern_hits(findings: &CombinedFindings) {
+    println!("{}", "Pattern hits".bold());
+    for hits in &findings.pattern_hits {
+        let label = format!(
+            "{:>6} match(es) in {:>5} commit(s)",
+            hits.matches, hits.commits
+        );
+        if hits.matches == 0 {
+            println!("  {}  {}", label.dimmed(), hits.name.dimmed());
+        } else {
+            println!(
+                "  {}  {} ({})",
+                label,
+                hits.name.bright_white(),
+                hits.category
+            );
+        }
+    }
+}
+
+fn print_tier_summary(findings: &CombinedFindings) {
+    let tier_summary = findings.risk_tier_summary();
+    println!(
+        "Risk tier: {} ({})",
+        tier_summary
+            .tier
+            .to_string()
+            .to_uppercase()
+            .bright_yellow()
+            .bold(),
+        tier_summary.rationale
+    );
 }
@@ -240,6 +240,9 @@ let vulnerabilityState = {
     itemsPerPage: 10,
     searchTerm: '',
     severityFilter: '',
+    confidenceFilter: '',
+    categoryFilter: '',
+    ageFilter: '',
     authorFilter: '',
     sortBy: 'risk-desc'
 };
@@ -258,6 +261,9 @@ function initializeVulnerabilitySearch() {
     // Set up event listeners
     const searchInput = document.getElementById('vulnerability-search');
     const severityFilter = document.getElementById('severity-filter');
+    const confidenceFilter = document.getElementById('confidence-filter');
+    const categoryFilter = document.getElementById('category-filter');
+    const ageFilter = document.getElementById('age-filter');
     const authorFilter = document.getElementById('author-filter');
     const sortSelect = document.getElementById('sort-select');
 
@@ -269,6 +275,18 @@ function initializeVulnerabilitySearch() {
         severityFilter.addEventListener('change', handleFilter);
     }
 
+    if (confidenceFilter) {
+        confidenceFilter.addEventListener('change', handleFilter);
+    }
+
+    if (categoryFilter) {
+        categoryFilter.addEventListener('change', handleFilter);
+    }
+
+    if (ageFilter) {
+        ageFilter.addEventListener('change', handleFilter);
+    }
+
     if (authorFilter) {
         authorFilter.addEventListener('change', handleFilter);
     }
@@ -323,9 +341,15 @@ function handleSearch(event) {
 
 function handleFilter() {
     const severityFilter = document.getElementById('severity-filter');
+    const confidenceFilter = document.getElementById('confidence-filter');
+    const categoryFilter = document.getElementById('category-filter');
+    const ageFilter = document.getElementById('age-filter');
     const authorFilter = document.getElementById('author-filter');
 
     vulnerabilityState.severityFilter = severityFilter ? severityFilter.value : '';
+    vulnerabilityState.confidenceFilter = confidenceFilter ? confidenceFilter.value : '';
+    vulnerabilityState.categoryFilter = categoryFilter ? categoryFilter.value : '';
+    vulnerabilityState.ageFilter = ageFilter ? ageFilter.value : '';
     vulnerabilityState.authorFilter = authorFilter ? authorFilter.value : '';
     vulnerabilityState.currentPage = 1;
     applyFiltersAndPagination();
@@ -358,6 +382,28 @@ function applyFiltersAndPagination() {
             return false;
         }
 
+        // Confidence filter
+        if (vulnerabilityState.confidenceFilter &&
+            item.dataset.confidence !== vulnerabilityState.confidenceFilter) {
+            return false;
+        }
+
+        // Category filter
+        if (vulnerabilityState.categoryFilter &&
+            !(item.dataset.categories || '').split('|').includes(vulnerabilityState.categoryFilter)) {
+            return false;
+        }
+
+        // Age filter: "N" keeps findings at most N days old, "older-N" those older
+        if (vulnerabilityState.ageFilter) {
+            const ageDays = parseInt(item.dataset.ageDays || '0');
+            const olderThan = vulnerabilityState.ageFilter.startsWith('older-');
+            const days = parseInt(vulnerabilityState.ageFilter.replace('older-', ''));
+            if (olderThan ? ageDays <= days : ageDays > days) {
+                return false;
+            }
+        }
+
         // Author filter
         if (vulnerabilityState.authorFilter &&
             item.dataset.author !== vulnerabilityState.authorFilter) {
@@ -439,6 +485,10 @@ function sortItems() {
                     return new Date(a.dataset.date) - new Date(b.dataset.date);
                 }
                 return parseInt(a.dataset.index || 0) - parseInt(b.dataset.index || 0);
+            case 'age-asc':
+                return parseInt(a.dataset.ageDays || '0') - parseInt(b.dataset.ageDays || '0');
+            case 'age-desc':
+                return parseInt(b.dataset.ageDays || '0') - parseInt(a.dataset.ageDays || '0');
             case 'author':
                 return (a.dataset.author || '').localeCompare(b.dataset.author || '');
             default:
@@ -0,0 +1,81 @@
+use crate::analysis::{CombinedFindings, MultiRepoFindings};
+use crate::git::RepositoryLinker;
+
+/// Files listed per repository, matching the report's priority areas
+pub const CHECKLIST_FILE_LIMIT: usize = 15;
+
+const PARTIAL_NOTICE: &str =
+    "> **Incomplete:** the scan was interrupted, so findings after that point are missing.\n";
+
+/// Markdown to-do list of the files with the most findings, for pasting into an issue tracker
+pub fn render(findings: &CombinedFindings, partial: bool) -> String {
+    let project = RepositoryLinker::new(&findings.git_stats).get_project_name();
+    let mut markdown = format!("# Remediation checklist: {}\n\n", project);
+    if partial {
+        markdown.push_str(PARTIAL_NOTICE);
+        markdown.push('\n');
+    }
+    render_repository(&mut markdown, findings, "##");
+    markdown
+}
+
+/// One checklist section per repository, riskiest repository first
+pub fn render_multi(multi: &MultiRepoFindings, partial: bool) -> String {
+    let mut markdown = String::from("# Remediation checklist\n");
+    if partial {
+        markdown.push('\n');
+        markdown.push_str(PARTIAL_NOTICE);
+    }
+    for repo in multi.ranked() {
+        markdown.push_str(&format!("\n## {}\n\n", repo.name));
+        render_repository(&mut markdown, &repo.findings, "###");
+    }
+    markdown
+}
+
+fn render_repository(markdown: &mut String, findings: &CombinedFindings, heading: &str) {
+    let summary = findings.risk_tier_summary();
+    let items = findings.remediation_checklist(CHECKLIST_FILE_LIMIT);
+    markdown.push_str(&format!(
+        "Overall risk {:.1}/10 ({}). {} finding(s); the {} file(s) with the most findings are listed.\n",
+        summary.overall_risk,
+        summary.tier,
+        findings.vulnerabilities.len(),
+        items.len()
+    ));
+    if items.is_empty() {
+        markdown.push_str("\nNo findings to remediate.\n");
+        return;
+    }
+
+    let linker = RepositoryLinker::new(&findings.git_stats);
+    for item in items {
+        markdown.push_str(&format!(
+            "\n{} `{}` ({} finding(s), max risk {:.1})\n\n",
+            heading,
+            item.file,
+            item.steps.len(),
+            item.max_risk
+        ));
+        for step in item.steps {
+            let short_id = &step.commit_id[..step.commit_id.len().min(8)];
+            let commit = match linker.get_commit_url(&step.commit_id) {
+                Some(url) => format!("[`{}`]({})", short_id, url),
+                None => format!("`{}`", short_id),
+            };
+            markdown.push_str(&format!(
+                "- [ ] {} {} (risk {:.1})\n",
+                commit, step.commit_subject, step.risk_score
+            ));
+            if !step.issues.is_empty() {
+                markdown.push_str(&format!("  - Issues: {}\n", step.issues.join(", ")));
+            }
+            if step.recommendations.is_empty() {
+                markdown.push_str("  - Recommendation: review the change manually\n");
+            }
+            for recommendation in step.recommendations {
+                markdown.push_str(&format!("  - Recommendation: {}\n", recommendation));
+            }
+        }
+    }
+}
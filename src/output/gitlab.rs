@@ -0,0 +1,134 @@
+use super::{to_json, OutputGenerator};
+use crate::analysis::{CombinedFindings, MultiRepoFindings};
+use crate::patterns::{PatternMatch, Severity, VulnerabilityFinding};
+use anyhow::Result;
+use serde::Serialize;
+
+/// One entry of a GitLab Code Quality report, shown inline in merge requests
+#[derive(Debug, Serialize)]
+struct CodeQualityIssue {
+    description: String,
+    check_name: String,
+    fingerprint: String,
+    severity: &'static str,
+    location: Location,
+}
+
+#[derive(Debug, Serialize)]
+struct Location {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Debug, Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+/// GitLab Code Quality JSON (`artifacts:reports:codequality`), one issue per pattern match
+pub struct GitLabGenerator {
+    compact: bool,
+}
+
+impl GitLabGenerator {
+    pub fn new(compact: bool) -> Self {
+        Self { compact }
+    }
+
+    /// Issues of every repository, descriptions prefixed with the repository name
+    pub fn generate_multi_repo(&self, multi: &MultiRepoFindings) -> Result<String> {
+        let issues: Vec<CodeQualityIssue> = multi
+            .repositories
+            .iter()
+            .flat_map(|repo| issues(&repo.findings, false, Some(&repo.name)))
+            .collect();
+        Ok(to_json(&issues, self.compact)?)
+    }
+}
+
+impl OutputGenerator for GitLabGenerator {
+    async fn generate(
+        &mut self,
+        findings: &CombinedFindings,
+        cve_only: bool,
+        _include_stats: bool,
+    ) -> Result<String> {
+        Ok(to_json(&issues(findings, cve_only, None), self.compact)?)
+    }
+}
+
+fn issues(
+    findings: &CombinedFindings,
+    cve_only: bool,
+    repository: Option<&str>,
+) -> Vec<CodeQualityIssue> {
+    findings
+        .vulnerabilities
+        .iter()
+        .filter(|v| !cve_only || !v.cve_references.is_empty())
+        .flat_map(|finding| {
+            finding
+                .patterns_matched
+                .iter()
+                .filter_map(move |m| issue(finding, m, repository))
+        })
+        .collect()
+}
+
+/// Issue for a match; matches in a commit message are placed on the first file the commit
+/// changes, and commits changing no file cannot be placed at all
+fn issue(
+    finding: &VulnerabilityFinding,
+    pattern_match: &PatternMatch,
+    repository: Option<&str>,
+) -> Option<CodeQualityIssue> {
+    let (path, line) = match pattern_match.line_number {
+        Some(line) => (pattern_match.file_path.clone(), line),
+        None => (finding.files_changed.first()?.clone(), 1),
+    };
+    let commit = &finding.commit_id[..finding.commit_id.len().min(8)];
+    let subject = finding.commit_message.lines().next().unwrap_or_default();
+    let description = format!(
+        "{}{} (\"{}\") in commit {}: {}",
+        repository.map(|r| format!("[{}] ", r)).unwrap_or_default(),
+        pattern_match.pattern_name,
+        pattern_match.matched_text,
+        commit,
+        subject
+    );
+
+    // Stable across runs so GitLab can tell new issues from resolved ones
+    let key = format!(
+        "{}:{}:{}:{}:{}",
+        repository.unwrap_or_default(),
+        finding.commit_id,
+        pattern_match.pattern_name,
+        pattern_match.file_path,
+        line
+    );
+    let fingerprint = git2::Oid::hash_object(git2::ObjectType::Blob, key.as_bytes())
+        .map(|oid| oid.to_string())
+        .unwrap_or(key);
+
+    Some(CodeQualityIssue {
+        description,
+        check_name: pattern_match.pattern_name.clone(),
+        fingerprint,
+        severity: severity(&pattern_match.severity),
+        location: Location {
+            path,
+            lines: Lines { begin: line },
+        },
+    })
+}
+
+/// GitLab's severities are info, minor, major, critical and blocker; blocker is left for
+/// findings that should stop a merge outright, which a history scan cannot judge
+fn severity(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "critical",
+        Severity::High => "major",
+        Severity::Medium => "minor",
+        Severity::Low | Severity::Info => "info",
+    }
+}
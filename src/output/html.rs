@@ -1,13 +1,18 @@
+use super::trend::{self, TrendRecord};
 use super::*;
-use crate::analysis::CombinedFindings;
+use crate::analysis::{self, ChurnScale, CombinedFindings, MultiRepoFindings, SeverityScale};
+use crate::error::{self, CommitRaiderError};
 use crate::git::RepositoryLinker;
 use crate::patterns::VulnerabilityFinding;
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
 use rust_embed::RustEmbed;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::path::Path;
 use tera::{Context, Tera};
+use tracing::warn;
 
 #[derive(RustEmbed)]
 #[folder = "src/output/templates/"]
@@ -20,8 +25,24 @@ struct Templates;
 #[include = "*.js"]
 struct Assets;
 
+/// Bare styling used when the embedded stylesheet cannot be loaded
+const FALLBACK_CSS: &str = "body { font-family: sans-serif; margin: 2rem; } \
+    table { border-collapse: collapse; } th, td { border: 1px solid #ccc; padding: 4px 8px; }";
+
+/// Used when the embedded script cannot be loaded; search and filters are unavailable
+const FALLBACK_JS: &str = "";
+
 pub struct HtmlGenerator {
     tera: Tera,
+    trend: Vec<TrendRecord>,
+    /// The scan was interrupted, so the report covers only part of it
+    partial: bool,
+    /// Thresholds behind every severity label and risk class in the report
+    scale: SeverityScale,
+    /// Timezone dates are displayed in
+    timezone: Tz,
+    /// Fixed generation date for reproducible reports, the current time if `None`
+    generated_at: Option<DateTime<Utc>>,
 }
 
 struct HeatmapData {
@@ -30,81 +51,180 @@ struct HeatmapData {
 }
 
 impl HtmlGenerator {
-    pub fn new() -> Result<Self> {
+    pub fn new(template_dir: Option<&Path>) -> error::Result<Self> {
         let mut tera = Tera::default();
 
         // Load templates from embedded resources
         for file in Templates::iter() {
             let template_name = file.as_ref();
-            let template_content = Templates::get(template_name)
-                .ok_or_else(|| anyhow::anyhow!("Template {} not found", template_name))?;
-            let template_str = std::str::from_utf8(&template_content.data)
-                .map_err(|e| anyhow::anyhow!("Invalid UTF-8 in template {}: {}", template_name, e))?;
+            let template_content = Templates::get(template_name).ok_or_else(|| {
+                CommitRaiderError::Render(format!("Template {} not found", template_name))
+            })?;
+            let template_str = std::str::from_utf8(&template_content.data).map_err(|e| {
+                CommitRaiderError::Render(format!(
+                    "Invalid UTF-8 in template {}: {}",
+                    template_name, e
+                ))
+            })?;
 
             tera.add_raw_template(template_name, template_str)
-                .map_err(|e| anyhow::anyhow!("Failed to add template {}: {}", template_name, e))?;
+                .map_err(|e| {
+                    CommitRaiderError::Render(format!(
+                        "Failed to add template {}: {}",
+                        template_name, e
+                    ))
+                })?;
+        }
+
+        if let Some(dir) = template_dir {
+            Self::load_template_dir(&mut tera, dir)?;
         }
 
-        // Add custom filters if needed
-        tera.register_filter("severity_class", Self::severity_class_filter);
-        tera.register_filter("risk_class", Self::risk_class_filter);
-        tera.register_filter("severity_text", Self::severity_text_filter);
+        // Styles and scripts can fall back, but there is no report without its main template
+        if !tera.get_template_names().any(|name| name == "report.html") {
+            return Err(CommitRaiderError::Render(
+                "Template report.html not found".to_string(),
+            ));
+        }
 
-        Ok(Self { tera })
+        let mut generator = Self {
+            tera,
+            trend: Vec::new(),
+            partial: false,
+            scale: SeverityScale::default(),
+            timezone: Tz::UTC,
+            generated_at: None,
+        };
+        generator.register_filters();
+        Ok(generator)
     }
 
-    fn load_asset(&self, filename: &str) -> Result<String> {
-        let asset = Assets::get(filename)
-            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", filename))?;
-        let content = std::str::from_utf8(&asset.data)
-            .map_err(|e| anyhow::anyhow!("Invalid UTF-8 in asset {}: {}", filename, e))?;
-        Ok(content.to_string())
+    /// Classify risk scores with these thresholds instead of the default scale
+    pub fn with_severity_scale(mut self, scale: SeverityScale) -> Self {
+        self.scale = scale;
+        self.register_filters();
+        self
     }
 
-    fn severity_class_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
-        let risk_score = value.as_f64().unwrap_or(0.0);
-        let class = if risk_score >= 8.0 {
-            "severity-critical"
-        } else if risk_score >= 6.0 {
-            "severity-high"
-        } else if risk_score >= 4.0 {
-            "severity-medium"
-        } else if risk_score >= 2.0 {
-            "severity-low"
-        } else {
-            "severity-info"
-        };
-        Ok(Value::String(class.to_string()))
+    /// Render a sparkline of these earlier runs in the risk overview
+    pub fn with_trend(mut self, trend: Vec<TrendRecord>) -> Self {
+        self.trend = trend;
+        self
     }
 
-    fn risk_class_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
-        let risk_score = value.as_f64().unwrap_or(0.0);
-        let class = if risk_score >= 8.0 {
-            "risk-critical"
-        } else if risk_score >= 6.0 {
-            "risk-high"
-        } else if risk_score >= 4.0 {
-            "risk-medium"
-        } else {
-            "risk-low"
-        };
-        Ok(Value::String(class.to_string()))
+    /// Display dates in `timezone` instead of UTC
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self.register_filters();
+        self
     }
 
-    fn severity_text_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
-        let risk_score = value.as_f64().unwrap_or(0.0);
-        let text = if risk_score >= 8.0 {
-            "critical"
-        } else if risk_score >= 6.0 {
-            "high"
-        } else if risk_score >= 4.0 {
-            "medium"
-        } else if risk_score >= 2.0 {
-            "low"
-        } else {
-            "info"
-        };
-        Ok(Value::String(text.to_string()))
+    /// Stamp the report with this generation date instead of the current time
+    pub fn with_generated_at(mut self, generated_at: Option<DateTime<Utc>>) -> Self {
+        self.generated_at = generated_at;
+        self
+    }
+
+    /// Mark the report as incomplete because the scan was interrupted
+    pub fn with_partial(mut self, partial: bool) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Load user templates from disk, overriding embedded templates with the same name
+    fn load_template_dir(tera: &mut Tera, dir: &Path) -> error::Result<()> {
+        if !dir.join("report.html").is_file() {
+            return Err(CommitRaiderError::Config(format!(
+                "Template directory {} does not contain report.html",
+                dir.display()
+            )));
+        }
+
+        let mut templates = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("html") {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                templates.push((path.clone(), Some(name.to_string())));
+            }
+        }
+
+        tera.add_template_files(templates).map_err(|e| {
+            CommitRaiderError::Render(format!(
+                "Failed to load templates from {}: {}",
+                dir.display(),
+                e
+            ))
+        })
+    }
+
+    /// Embedded asset content, or `fallback` with a warning so a finished scan still renders
+    fn load_asset(&self, filename: &str, fallback: &str) -> String {
+        let content = Assets::get(filename)
+            .ok_or_else(|| format!("Asset {} not found", filename))
+            .and_then(|asset| {
+                String::from_utf8(asset.data.into_owned())
+                    .map_err(|e| format!("Invalid UTF-8 in asset {}: {}", filename, e))
+            });
+
+        content.unwrap_or_else(|e| {
+            warn!("{}; using a minimal fallback", e);
+            fallback.to_string()
+        })
+    }
+
+    /// Register the score filters for the current scale and the date filter for the current
+    /// timezone, replacing any registered before
+    fn register_filters(&mut self) {
+        let scale = self.scale;
+        let timezone = self.timezone;
+        self.tera.register_filter(
+            "severity_class",
+            move |value: &Value, _: &HashMap<String, Value>| {
+                let risk_score = value.as_f64().unwrap_or(0.0);
+                let class = format!("severity-{}", scale.severity(risk_score));
+                Ok(Value::String(class))
+            },
+        );
+        self.tera.register_filter(
+            "risk_class",
+            move |value: &Value, _: &HashMap<String, Value>| {
+                let risk_score = value.as_f64().unwrap_or(0.0);
+                Ok(Value::String(format!("risk-{}", scale.tier(risk_score))))
+            },
+        );
+        self.tera.register_filter(
+            "severity_text",
+            move |value: &Value, _: &HashMap<String, Value>| {
+                let risk_score = value.as_f64().unwrap_or(0.0);
+                Ok(Value::String(scale.severity(risk_score).to_string()))
+            },
+        );
+        // Replaces Tera's built-in `date` so every template, including user templates, shows
+        // the configured timezone
+        self.tera.register_filter(
+            "date",
+            move |value: &Value, args: &HashMap<String, Value>| {
+                let format = match args.get("format") {
+                    Some(format) => format
+                        .as_str()
+                        .ok_or_else(|| tera::Error::msg("date format must be a string"))?,
+                    None => "%Y-%m-%d",
+                };
+                format_date(value, format, timezone).map(Value::String)
+            },
+        );
+    }
+
+    /// The current time as shown in report headers
+    fn generated_date(&self) -> String {
+        self.generated_at
+            .unwrap_or_else(Utc::now)
+            .with_timezone(&self.timezone)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string()
     }
 
     fn prepare_template_context(
@@ -116,19 +236,17 @@ impl HtmlGenerator {
         let mut context = Context::new();
 
         // Load CSS and JavaScript content
-        let css_content = self.load_asset("styles.css")?;
-        let js_content = self.load_asset("script.js")?;
+        let css_content = self.load_asset("styles.css", FALLBACK_CSS);
+        let js_content = self.load_asset("script.js", FALLBACK_JS);
 
         context.insert("css_content", &css_content);
         context.insert("js_content", &js_content);
         context.insert("repo_path", &findings.git_stats.path);
-        context.insert(
-            "generated_date",
-            &Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-        );
+        context.insert("generated_date", &self.generated_date());
         context.insert("findings", findings);
         context.insert("include_stats", &include_stats);
         context.insert("cve_only", &cve_only);
+        context.insert("partial", &self.partial);
 
         // Risk overview calculations
         let overall_risk = findings.calculate_overall_risk();
@@ -136,13 +254,35 @@ impl HtmlGenerator {
 
         context.insert("overall_risk", &overall_risk);
         context.insert("risk_percentage", &risk_percentage);
-
-        let single_author_percentage = findings.git_stats.single_author_files.len() as f64
-            / findings.git_stats.total_files as f64
-            * 100.0;
-        let stale_files_percentage = findings.git_stats.stale_files.len() as f64
-            / findings.git_stats.total_files as f64
-            * 100.0;
+        context.insert(
+            "confidence_weighted_risk",
+            &findings.calculate_confidence_weighted_risk(),
+        );
+        context.insert("risk_tier", &findings.risk_tier_summary());
+        context.insert("trend", &self.trend);
+        context.insert("trend_points", &trend::sparkline_points(&self.trend));
+        context.insert("category_counts", &findings.category_counts());
+        context.insert(
+            "directory_risk",
+            &findings.directory_risk(findings.config.output.directory_depth),
+        );
+        let cwe_coverage = findings.cwe_coverage();
+        context.insert("cwe_other_findings", &cwe_coverage.other_findings());
+        context.insert("cwe_coverage", &cwe_coverage);
+        context.insert("findings_by_extension", &findings.findings_by_extension());
+        context.insert(
+            "marker_files_by_extension",
+            &findings.marker_files_by_extension(),
+        );
+        context.insert("findings_per_release", &findings.findings_per_release());
+        context.insert("removed_files", &findings.removed_file_findings());
+
+        // Shallow or truncated histories can yield no changed files at all
+        let total_files = findings.git_stats.total_files.max(1) as f64;
+        let single_author_percentage =
+            findings.git_stats.single_author_files.len() as f64 / total_files * 100.0;
+        let stale_files_percentage =
+            findings.git_stats.stale_files.len() as f64 / total_files * 100.0;
         let high_complexity_count = findings
             .code_stats
             .file_complexity
@@ -152,10 +292,14 @@ impl HtmlGenerator {
 
         context.insert("single_author_percentage", &single_author_percentage);
         context.insert("stale_files_percentage", &stale_files_percentage);
+        context.insert(
+            "single_commit_percentage",
+            &(findings.git_stats.single_commit_files.len() as f64 / total_files * 100.0),
+        );
         context.insert("high_complexity_count", &high_complexity_count);
 
         // Vulnerability data
-        let filtered_vulnerabilities: Vec<_> = if cve_only {
+        let mut filtered_vulnerabilities: Vec<_> = if cve_only {
             findings
                 .vulnerabilities
                 .iter()
@@ -164,7 +308,41 @@ impl HtmlGenerator {
         } else {
             findings.vulnerabilities.iter().collect()
         };
+        // Browsers choke on reports with many thousands of findings; keep the riskiest ones,
+        // listed in the same order as an untruncated report
+        let vulnerabilities_total = filtered_vulnerabilities.len();
+        let max_findings = findings.config.output.max_report_findings;
+        if max_findings > 0 && vulnerabilities_total > max_findings {
+            let mut by_risk: Vec<usize> = (0..vulnerabilities_total).collect();
+            by_risk.sort_by(|&a, &b| {
+                filtered_vulnerabilities[b]
+                    .risk_score
+                    .partial_cmp(&filtered_vulnerabilities[a].risk_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            let mut kept = vec![false; vulnerabilities_total];
+            for &i in &by_risk[..max_findings] {
+                kept[i] = true;
+            }
+            let mut index = 0;
+            filtered_vulnerabilities.retain(|_| {
+                index += 1;
+                kept[index - 1]
+            });
+        }
+        context.insert("vulnerabilities_total", &vulnerabilities_total);
 
+        let mut recent_findings: Vec<_> = findings
+            .recent_findings(findings.config.output.recent_days)
+            .into_iter()
+            .filter(|v| !cve_only || !v.cve_references.is_empty())
+            .collect();
+        recent_findings.truncate(findings.config.output.max_items_per_section);
+        context.insert(
+            "recent_findings",
+            &self.prepare_vulnerability_data_with_links(&recent_findings, findings),
+        );
+        context.insert("recent_days", &findings.config.output.recent_days);
         let show_vulnerabilities = !filtered_vulnerabilities.is_empty();
         context.insert("show_vulnerabilities", &show_vulnerabilities);
         context.insert(
@@ -194,10 +372,18 @@ impl HtmlGenerator {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
         context.insert("all_complexity_files", &all_complexity_files);
+        context.insert(
+            "maintainability_ranking",
+            &findings.maintainability_ranking(findings.config.output.max_items_per_section),
+        );
 
         // Git analysis data
         let top_contributors = findings.git_stats.get_top_contributors(5);
         context.insert("top_contributors", &top_contributors);
+        context.insert(
+            "riskiest_authors",
+            &findings.riskiest_authors(super::reporter::RISKIEST_AUTHORS_LIMIT),
+        );
 
         // Heatmap data with repository links
         let linker = RepositoryLinker::new(&findings.git_stats);
@@ -221,14 +407,20 @@ impl HtmlGenerator {
         let mut priority_files: Vec<_> = file_findings
             .iter()
             .map(|(file, findings_vec)| {
-                let high_risk_count = findings_vec.iter().filter(|f| f.risk_score >= 7.0).count();
+                let high_risk_count = findings_vec
+                    .iter()
+                    .filter(|f| f.risk_score >= self.scale.high)
+                    .count();
                 let medium_risk_count = findings_vec
                     .iter()
-                    .filter(|f| f.risk_score >= 4.0 && f.risk_score < 7.0)
+                    .filter(|f| f.risk_score >= self.scale.medium && f.risk_score < self.scale.high)
+                    .count();
+                let low_risk_count = findings_vec
+                    .iter()
+                    .filter(|f| f.risk_score < self.scale.medium)
                     .count();
-                let low_risk_count = findings_vec.iter().filter(|f| f.risk_score < 4.0).count();
 
-                let file_url = linker.get_file_url(file, None);
+                let file_url = linker.get_file_url(file, None, None);
 
                 (
                     file,
@@ -243,16 +435,29 @@ impl HtmlGenerator {
 
         priority_files.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by total findings count descending
 
+        let authored_dates: HashMap<&str, _> = findings
+            .git_stats
+            .commit_history
+            .iter()
+            .map(|commit| (commit.id.as_str(), commit.authored_date))
+            .collect();
         let priority_areas_by_file: Vec<_> = priority_files
             .into_iter()
             .take(15) // Show top 15 files with most findings
             .map(
                 |(file, total_count, high_count, medium_count, low_count, file_url)| {
-                    // Find the most recent commit that modified this file
+                    // Latest authored commit touching the file. History is walked newest first,
+                    // so the first entry stands in when dates are unknown (streaming)
                     let recent_commit = findings.git_stats.file_history.get(file)
                         .and_then(|history| {
-                            // Get the most recent commit from the history
-                            history.commits.last().cloned()
+                            history
+                                .commits
+                                .iter()
+                                .filter_map(|id| Some((id, *authored_dates.get(id.as_str())?)))
+                                .max_by_key(|(_, date)| *date)
+                                .map(|(id, _)| id)
+                                .or_else(|| history.commits.first())
+                                .cloned()
                         });
 
                     let (commit_url, commit_id_short) = if let Some(commit_id) = &recent_commit {
@@ -320,11 +525,19 @@ impl HtmlGenerator {
             .collect();
         context.insert("single_author_files", &single_author_files);
 
+        let single_commit_files: Vec<_> = findings
+            .git_stats
+            .single_commit_files
+            .iter()
+            .take(20)
+            .collect();
+        context.insert("single_commit_files", &single_commit_files);
+
         // File extension distributions
         let single_author_extensions =
-            self.calculate_extension_distribution(&findings.git_stats.single_author_files);
+            analysis::extension_distribution(&findings.git_stats.single_author_files);
         let stale_files_extensions =
-            self.calculate_extension_distribution(&findings.git_stats.stale_files);
+            analysis::extension_distribution(&findings.git_stats.stale_files);
         context.insert("single_author_extensions", &single_author_extensions);
         context.insert("stale_files_extensions", &stale_files_extensions);
 
@@ -349,6 +562,41 @@ impl HtmlGenerator {
         Ok(context)
     }
 
+    /// Render the aggregate page listing every analyzed repository by risk
+    pub fn generate_multi_repo(&self, multi: &MultiRepoFindings) -> Result<String> {
+        let mut context = Context::new();
+        context.insert("css_content", &self.load_asset("styles.css", FALLBACK_CSS));
+        context.insert("generated_date", &self.generated_date());
+        context.insert("highest_tier", &multi.highest_tier());
+        context.insert("partial", &self.partial);
+
+        let repositories: Vec<_> = multi
+            .ranked()
+            .into_iter()
+            .map(|repo| {
+                // Per-repo reports are written next to the aggregate, so link by file name
+                let report_link = repo.report_path.as_ref().and_then(|path| {
+                    std::path::Path::new(path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                });
+
+                json!({
+                    "name": repo.name,
+                    "path": repo.findings.git_stats.path,
+                    "overall_risk": repo.findings.calculate_overall_risk(),
+                    "risk_tier": repo.findings.risk_tier_summary(),
+                    "vulnerabilities": repo.findings.vulnerabilities.len(),
+                    "total_commits": repo.findings.git_stats.total_commits,
+                    "report_link": report_link
+                })
+            })
+            .collect();
+        context.insert("repositories", &repositories);
+
+        Ok(self.tera.render("multi_repo_report.html", &context)?)
+    }
+
     fn prepare_vulnerability_data_with_links(
         &self,
         vulnerabilities: &[&crate::patterns::VulnerabilityFinding],
@@ -372,9 +620,32 @@ impl HtmlGenerator {
                 })
                 .collect();
 
+            // `Fixes: <sha> ("subject")` names the commit that introduced the problem
+            let fixes_links: Vec<_> = trailer_values(&vuln.trailers, &["Fixes"])
+                .into_iter()
+                .filter_map(|value| {
+                    let sha = value.split_whitespace().next()?;
+                    if sha.len() < 7 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return None;
+                    }
+                    Some(json!({
+                        "commit": &sha[..sha.len().min(12)],
+                        "url": linker.get_commit_url(sha)
+                    }))
+                })
+                .collect();
+
             let file_links: Vec<_> = vuln.files_changed.iter()
                 .filter_map(|file| {
-                    linker.get_file_url(file, Some(&vuln.commit_id)).map(|url| {
+                    // Diff matches know their lines; link the span they cover
+                    let lines = vuln.patterns_matched.iter()
+                        .filter(|m| &m.file_path == file)
+                        .filter_map(|m| m.line_number)
+                        .fold(None, |range: Option<(usize, usize)>, line| match range {
+                            Some((start, end)) => Some((start.min(line), end.max(line))),
+                            None => Some((line, line)),
+                        });
+                    linker.get_file_url(file, Some(&vuln.commit_id), lines).map(|url| {
                         json!({
                             "path": file,
                             "url": url
@@ -389,6 +660,7 @@ impl HtmlGenerator {
                 "commit_message": vuln.commit_message,
                 "author": vuln.author,
                 "date": vuln.date,
+                "age_days": vuln.age_days,
                 "files_changed": vuln.files_changed,
                 "patterns_matched": vuln.patterns_matched,
                 "risk_score": vuln.risk_score,
@@ -396,10 +668,24 @@ impl HtmlGenerator {
                 "severity_class": self.get_severity_class(vuln.risk_score),
                 "risk_class": self.get_risk_class(vuln.risk_score),
                 "severity_text": self.get_severity_text(vuln.risk_score),
+                "confidence": vuln.confidence(),
+                "categories": vuln.patterns_matched.iter()
+                    .map(|p| p.category.name().to_lowercase())
+                    .collect::<std::collections::BTreeSet<_>>(),
                 "commit_url": commit_url,
                 "diff_url": diff_url,
                 "issue_links": issue_links,
-                "file_links": file_links
+                "file_links": file_links,
+                "reviewers": trailer_values(&vuln.trailers, &["Reviewed-by", "Acked-by", "Tested-by"]),
+                "signed_off_by": trailer_values(&vuln.trailers, &["Signed-off-by"]),
+                "fixes_links": fixes_links,
+                "related_commits": vuln.related_commits.iter().map(|related| json!({
+                    "commit_id_short": &related.commit_id[..related.commit_id.len().min(8)],
+                    "url": linker.get_commit_url(&related.commit_id),
+                    "subject": related.subject,
+                    "author": related.author,
+                    "date": related.date
+                })).collect::<Vec<_>>()
             })
         }).collect()
     }
@@ -419,11 +705,9 @@ impl HtmlGenerator {
         }
 
         // Determine thresholds for color coding
-        let max_commits = file_commit_counts.values().max().unwrap_or(&0);
-        let threshold_1 = max_commits / 5;
-        let threshold_2 = max_commits * 2 / 5;
-        let threshold_3 = max_commits * 3 / 5;
-        let threshold_4 = max_commits * 4 / 5;
+        let counts: Vec<usize> = file_commit_counts.values().copied().collect();
+        let scale = ChurnScale::new(&counts, findings.config.output.heatmap_bucketing);
+        let [_, threshold_2, _, threshold_4] = scale.thresholds();
 
         // Create sorted list of files by commit count (descending) - limit to top 100
         let mut sorted_files: Vec<_> = file_commit_counts.iter().collect();
@@ -433,19 +717,7 @@ impl HtmlGenerator {
             .iter()
             //.take(100)
             .map(|(file, &count)| {
-                let css_class = if count == 0 {
-                    "commits-0"
-                } else if count <= threshold_1 {
-                    "commits-1"
-                } else if count <= threshold_2 {
-                    "commits-2"
-                } else if count <= threshold_3 {
-                    "commits-3"
-                } else if count <= threshold_4 {
-                    "commits-4"
-                } else {
-                    "commits-high"
-                };
+                let css_class = scale.css_class(count);
 
                 // Get file extension for icon
                 let extension = std::path::Path::new(file)
@@ -491,11 +763,17 @@ impl HtmlGenerator {
                     .git_stats
                     .file_history
                     .get(*file)
-                    .map(|history| history.last_commit.format("%Y-%m-%d").to_string())
+                    .map(|history| {
+                        history
+                            .last_commit
+                            .with_timezone(&self.timezone)
+                            .format("%Y-%m-%d")
+                            .to_string()
+                    })
                     .unwrap_or_else(|| "Unknown".to_string());
 
                 // Get file URL using the repository linker
-                let file_url = linker.get_file_url(file, None);
+                let file_url = linker.get_file_url(file, None, None);
 
                 json!({
                     "path": file,
@@ -510,18 +788,12 @@ impl HtmlGenerator {
             })
             .collect();
 
-        let high_churn_files = file_commit_counts
-            .values()
-            .filter(|&&c| c > threshold_4)
-            .count();
-        let medium_churn_files = file_commit_counts
-            .values()
-            .filter(|&&c| c > threshold_2 && c <= threshold_4)
-            .count();
-        let low_churn_files = file_commit_counts
-            .values()
-            .filter(|&&c| c <= threshold_2)
+        let high_churn_files = counts.iter().filter(|&&c| scale.bucket(c) > 4).count();
+        let medium_churn_files = counts
+            .iter()
+            .filter(|&&c| (3..=4).contains(&scale.bucket(c)))
             .count();
+        let low_churn_files = counts.iter().filter(|&&c| scale.bucket(c) <= 2).count();
 
         let stats = json!({
             "high_churn_files": high_churn_files,
@@ -534,94 +806,16 @@ impl HtmlGenerator {
         HeatmapData { files, stats }
     }
 
-    fn get_severity_class(&self, risk_score: f64) -> &'static str {
-        if risk_score >= 8.0 {
-            "severity-critical"
-        } else if risk_score >= 6.0 {
-            "severity-high"
-        } else if risk_score >= 4.0 {
-            "severity-medium"
-        } else if risk_score >= 2.0 {
-            "severity-low"
-        } else {
-            "severity-info"
-        }
+    fn get_severity_class(&self, risk_score: f64) -> String {
+        format!("severity-{}", self.scale.severity(risk_score))
     }
 
-    fn get_risk_class(&self, risk_score: f64) -> &'static str {
-        if risk_score >= 8.0 {
-            "risk-critical"
-        } else if risk_score >= 6.0 {
-            "risk-high"
-        } else if risk_score >= 4.0 {
-            "risk-medium"
-        } else {
-            "risk-low"
-        }
+    fn get_risk_class(&self, risk_score: f64) -> String {
+        format!("risk-{}", self.scale.tier(risk_score))
     }
 
     fn get_severity_text(&self, risk_score: f64) -> &'static str {
-        if risk_score >= 8.0 {
-            "critical"
-        } else if risk_score >= 6.0 {
-            "high"
-        } else if risk_score >= 4.0 {
-            "medium"
-        } else if risk_score >= 2.0 {
-            "low"
-        } else {
-            "info"
-        }
-    }
-
-    fn calculate_extension_distribution(&self, files: &[String]) -> Vec<serde_json::Value> {
-        let mut extension_counts = HashMap::new();
-        let mut no_extension_count = 0;
-
-        for file in files {
-            if let Some(extension) = std::path::Path::new(file)
-                .extension()
-                .and_then(|s| s.to_str())
-            {
-                *extension_counts
-                    .entry(extension.to_lowercase())
-                    .or_insert(0) += 1;
-            } else {
-                no_extension_count += 1;
-            }
-        }
-
-        let mut distribution: Vec<_> = extension_counts
-            .into_iter()
-            .map(|(ext, count)| {
-                let percentage = (count as f64 / files.len() as f64) * 100.0;
-                json!({
-                    "extension": ext,
-                    "count": count,
-                    "percentage": percentage
-                })
-            })
-            .collect();
-
-        // Sort by count descending
-        distribution.sort_by(|a, b| {
-            b["count"]
-                .as_u64()
-                .unwrap_or(0)
-                .cmp(&a["count"].as_u64().unwrap_or(0))
-        });
-
-        // Add "no extension" category if there are files without extensions
-        if no_extension_count > 0 {
-            let percentage = (no_extension_count as f64 / files.len() as f64) * 100.0;
-            distribution.push(json!({
-                "extension": "no extension",
-                "count": no_extension_count,
-                "percentage": percentage
-            }));
-        }
-
-        distribution
+        self.scale.severity(risk_score)
     }
 }
 
@@ -637,3 +831,38 @@ impl OutputGenerator for HtmlGenerator {
         Ok(html)
     }
 }
+
+/// Values of the trailers named by `keys`, compared case-insensitively
+fn trailer_values<'a>(trailers: &'a HashMap<String, Vec<String>>, keys: &[&str]) -> Vec<&'a str> {
+    let mut values: Vec<&str> = trailers
+        .iter()
+        .filter(|(key, _)| keys.iter().any(|k| k.eq_ignore_ascii_case(key)))
+        .flat_map(|(_, values)| values.iter().map(String::as_str))
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+/// Tera `date` filter input (an RFC 3339 string, a Unix timestamp or a bare `YYYY-MM-DD` day)
+/// formatted in `timezone`
+fn format_date(value: &Value, format: &str, timezone: Tz) -> tera::Result<String> {
+    let date = match value {
+        Value::Number(seconds) => seconds
+            .as_i64()
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single()),
+        Value::String(text) => match DateTime::parse_from_rfc3339(text) {
+            Ok(date) => Some(date.with_timezone(&Utc)),
+            // A bare day has no time to convert
+            Err(_) => {
+                let day = NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| {
+                    tera::Error::msg(format!("date got an invalid date '{}'", text))
+                })?;
+                return Ok(day.format(format).to_string());
+            }
+        },
+        _ => None,
+    };
+    let date = date.ok_or_else(|| tera::Error::msg(format!("date got {}", value)))?;
+    Ok(date.with_timezone(&timezone).format(format).to_string())
+}
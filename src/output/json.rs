@@ -0,0 +1,49 @@
+use super::reporter::RISKIEST_AUTHORS_LIMIT;
+use crate::analysis::{
+    AuthorRisk, CategoryCount, CombinedFindings, CweCoverage, DirectoryRisk, ExtensionCount,
+    MaintainabilityRank, ReleaseFindings, RemovedFileFindings, RiskBreakdown, RiskTierSummary,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The JSON report: the findings of one repository and the summaries derived from them.
+/// `--emit-schema` describes this type, so every key written is covered by the schema.
+#[derive(Serialize, JsonSchema)]
+pub struct JsonReport<'a> {
+    #[serde(flatten)]
+    pub findings: &'a CombinedFindings,
+    /// The scan was interrupted and covers only part of the history
+    pub partial: bool,
+    pub risk_tier: RiskTierSummary,
+    pub risk_breakdown: RiskBreakdown,
+    pub category_counts: Vec<CategoryCount>,
+    pub cwe_coverage: CweCoverage,
+    /// Directories down to the configured `directory_depth`
+    pub directory_risk: Vec<DirectoryRisk>,
+    pub removed_file_findings: Vec<RemovedFileFindings>,
+    pub findings_by_extension: Vec<ExtensionCount>,
+    pub findings_per_release: Vec<ReleaseFindings>,
+    pub riskiest_authors: Vec<AuthorRisk>,
+    /// Least maintainable files, at most `max_items_per_section`
+    pub maintainability_ranking: Vec<MaintainabilityRank>,
+}
+
+impl<'a> JsonReport<'a> {
+    pub fn from_findings(findings: &'a CombinedFindings, partial: bool) -> Self {
+        let output = &findings.config.output;
+        Self {
+            findings,
+            partial,
+            risk_tier: findings.risk_tier_summary(),
+            risk_breakdown: findings.risk_breakdown(),
+            category_counts: findings.category_counts(),
+            cwe_coverage: findings.cwe_coverage(),
+            directory_risk: findings.directory_risk(output.directory_depth),
+            removed_file_findings: findings.removed_file_findings(),
+            findings_by_extension: findings.findings_by_extension(),
+            findings_per_release: findings.findings_per_release(),
+            riskiest_authors: findings.riskiest_authors(RISKIEST_AUTHORS_LIMIT),
+            maintainability_ranking: findings.maintainability_ranking(output.max_items_per_section),
+        }
+    }
+}
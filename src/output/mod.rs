@@ -1,18 +1,35 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+pub mod checklist;
+pub mod gitlab;
 pub mod html;
+pub mod json;
 pub mod reporter;
 pub mod sarif;
+pub mod sqlite;
+pub mod trend;
 
 pub use reporter::Reporter;
 
 use crate::analysis::CombinedFindings;
+use crate::error::{self, CommitRaiderError};
+use crate::git::{RepositoryLinker, RepositoryStats};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OutputFormat {
     Json,
     Html,
+    /// Flat JSON list with one record per pattern match
+    Matches,
+    /// JSON array of the findings alone, without statistics or configuration
+    FindingsJson,
+    /// Rows appended to a SQLite database, one run per repository scan
+    Sqlite,
+    /// Markdown remediation to-do list grouped by file
+    Checklist,
+    /// GitLab Code Quality report for merge request annotations
+    GitLab,
 }
 
 impl From<&str> for OutputFormat {
@@ -20,15 +37,73 @@ impl From<&str> for OutputFormat {
         match s.to_lowercase().as_str() {
             "json" => OutputFormat::Json,
             "html" => OutputFormat::Html,
+            "matches" => OutputFormat::Matches,
+            "findings-json" => OutputFormat::FindingsJson,
+            "sqlite" => OutputFormat::Sqlite,
+            "checklist" => OutputFormat::Checklist,
+            "gitlab" => OutputFormat::GitLab,
             _ => OutputFormat::Html,
         }
     }
 }
 
+/// Value used for `{repo}` and `{branch}` in the aggregate multi-repository report
+const AGGREGATE_PLACEHOLDER: &str = "aggregate";
+
+/// Expand `{repo}`, `{date}` and `{branch}` in an output file name; `{date}` is `report_date`
+/// or today. Without `stats` (aggregate reports, validation) repository placeholders become
+/// "aggregate".
+pub fn expand_output_path(
+    template: &str,
+    stats: Option<&RepositoryStats>,
+    report_date: Option<chrono::DateTime<chrono::Utc>>,
+) -> error::Result<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            CommitRaiderError::Config(format!(
+                "Unclosed placeholder in output file name '{}'",
+                template
+            ))
+        })?;
+        let placeholder = &rest[start + 1..start + end];
+
+        let value = match (placeholder, stats) {
+            ("date", _) => report_date
+                .unwrap_or_else(chrono::Utc::now)
+                .format("%Y-%m-%d")
+                .to_string(),
+            ("repo", Some(stats)) => RepositoryLinker::new(stats).get_project_name(),
+            ("branch", Some(stats)) => stats.head_branch.clone().unwrap_or_else(|| "detached".to_string()),
+            ("repo" | "branch", None) => AGGREGATE_PLACEHOLDER.to_string(),
+            _ => {
+                return Err(CommitRaiderError::Config(format!(
+                    "Unknown placeholder '{{{}}}' in output file name '{}' (expected {{repo}}, {{date}} or {{branch}})",
+                    placeholder, template
+                )))
+            }
+        };
+        // Branch names like feature/x must not introduce directories
+        expanded.push_str(&value.replace(['/', '\\'], "-"));
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
 pub fn add_file_extension(path: &str, format: &OutputFormat) -> String {
     let extension = match format {
         OutputFormat::Html => ".html",
-        OutputFormat::Json => ".json",
+        OutputFormat::Json
+        | OutputFormat::Matches
+        | OutputFormat::FindingsJson
+        | OutputFormat::GitLab => ".json",
+        OutputFormat::Sqlite => ".db",
+        OutputFormat::Checklist => ".md",
     };
 
     if path.ends_with(extension) {
@@ -38,6 +113,20 @@ pub fn add_file_extension(path: &str, format: &OutputFormat) -> String {
     }
 }
 
+/// JSON Schema of the report written by the JSON output, for downstream validation
+pub fn findings_schema(compact: bool) -> serde_json::Result<String> {
+    to_json(&schemars::schema_for!(json::JsonReport<'static>), compact)
+}
+
+/// Serialize JSON output, on a single line when `compact` for machine consumption
+pub fn to_json<T: Serialize + ?Sized>(value: &T, compact: bool) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    }
+}
+
 pub trait OutputGenerator {
     async fn generate(
         &mut self,
@@ -1,45 +1,206 @@
 use super::*;
-use crate::analysis::CombinedFindings;
+use crate::analysis::{CombinedFindings, MultiRepoFindings};
+use crate::config::OutputConfig;
+use crate::error;
+use crate::git::RepositoryStats;
 use anyhow::Result;
 use std::fs;
 use tracing::info;
 
+use super::gitlab::GitLabGenerator;
 use super::html::HtmlGenerator;
+use super::json::JsonReport;
+use super::sqlite::SqliteGenerator;
+use super::trend::TrendRecord;
+
+/// Number of authors listed in the riskiest-authors summary
+pub const RISKIEST_AUTHORS_LIMIT: usize = 10;
 
 pub struct Reporter {
     format: OutputFormat,
     output_path: String,
+    config: OutputConfig,
+    trend: Vec<TrendRecord>,
+    partial: bool,
 }
 
 impl Reporter {
-    pub fn new(format: &str, output_path: &str) -> Result<Self> {
+    /// `output_path` may contain `{repo}`, `{date}` and `{branch}`, filled from `stats`
+    pub fn new(
+        format: &str,
+        output_path: &str,
+        config: &OutputConfig,
+        stats: Option<&RepositoryStats>,
+    ) -> error::Result<Self> {
         let format = OutputFormat::from(format);
-        let output_path = super::add_file_extension(output_path, &format);
+        let output_path = super::expand_output_path(output_path, stats, config.report_date()?)?;
+        let output_path = super::add_file_extension(&output_path, &format);
 
         Ok(Self {
             format,
             output_path,
+            config: config.clone(),
+            trend: Vec::new(),
+            partial: false,
         })
     }
 
+    /// Earlier runs of this repository, shown as a risk sparkline in HTML reports
+    pub fn set_trend(&mut self, trend: Vec<TrendRecord>) {
+        self.trend = trend;
+    }
+
+    /// Mark the report as incomplete because the scan was interrupted. HTML, JSON and
+    /// checklist reports say so; bare finding arrays and SQLite databases cannot.
+    pub fn set_partial(&mut self, partial: bool) {
+        self.partial = partial;
+    }
+
+    pub fn output_path(&self) -> &str {
+        &self.output_path
+    }
+
     pub async fn generate_report(
         &mut self,
         findings: &CombinedFindings,
         cve_only: bool,
         include_stats: bool,
     ) -> Result<()> {
+        if let OutputFormat::Sqlite = self.format {
+            let repository = RepositoryLinker::new(&findings.git_stats).get_project_name();
+            SqliteGenerator::open(&self.output_path)?.write(&repository, findings)?;
+            info!("Findings saved to {}", self.output_path);
+            return Ok(());
+        }
+
         let content = match self.format {
             OutputFormat::Html => {
-                let mut generator = HtmlGenerator::new()?;
+                let mut generator = HtmlGenerator::new(self.config.template_dir.as_deref())?
+                    .with_trend(self.trend.clone())
+                    .with_partial(self.partial)
+                    .with_timezone(self.config.timezone()?)
+                    .with_generated_at(self.config.report_date()?)
+                    .with_severity_scale(findings.config.risk.severity_scale);
                 generator
                     .generate(findings, cve_only, include_stats)
                     .await?
             }
-            OutputFormat::Json => serde_json::to_string_pretty(findings)?,
+            OutputFormat::Json => to_json(
+                &JsonReport::from_findings(findings, self.partial),
+                self.config.compact_json,
+            )?,
+            OutputFormat::Matches => to_json(&findings.flat_matches(), self.config.compact_json)?,
+            OutputFormat::FindingsJson => {
+                let vulnerabilities: Vec<_> = findings
+                    .vulnerabilities
+                    .iter()
+                    .filter(|v| !cve_only || !v.cve_references.is_empty())
+                    .collect();
+                to_json(&vulnerabilities, self.config.compact_json)?
+            }
+            OutputFormat::Checklist => checklist::render(findings, self.partial),
+            OutputFormat::GitLab => {
+                GitLabGenerator::new(self.config.compact_json)
+                    .generate(findings, cve_only, include_stats)
+                    .await?
+            }
+            OutputFormat::Sqlite => unreachable!("SQLite output is written above"),
         };
 
         fs::write(&self.output_path, content)?;
         info!("Report saved to {}", self.output_path);
         Ok(())
     }
+
+    /// Write an aggregate report ranking several repositories by risk
+    pub async fn generate_multi_repo_report(&mut self, multi: &MultiRepoFindings) -> Result<()> {
+        if let OutputFormat::Sqlite = self.format {
+            // One database holding every repository, queryable side by side
+            let mut generator = SqliteGenerator::open(&self.output_path)?;
+            for repo in &multi.repositories {
+                generator.write(&repo.name, &repo.findings)?;
+            }
+            info!("Multi-repository findings saved to {}", self.output_path);
+            return Ok(());
+        }
+
+        let content = match self.format {
+            OutputFormat::Html => {
+                let mut generator = HtmlGenerator::new(self.config.template_dir.as_deref())?
+                    .with_partial(self.partial)
+                    .with_timezone(self.config.timezone()?)
+                    .with_generated_at(self.config.report_date()?);
+                // Every repository is scanned with the same configuration
+                if let Some(repo) = multi.repositories.first() {
+                    generator =
+                        generator.with_severity_scale(repo.findings.config.risk.severity_scale);
+                }
+                generator.generate_multi_repo(multi)?
+            }
+            OutputFormat::Json => {
+                let repositories: Vec<_> = multi
+                    .ranked()
+                    .into_iter()
+                    .map(|repo| {
+                        serde_json::json!({
+                            "name": repo.name,
+                            "path": repo.findings.git_stats.path,
+                            "report_path": repo.report_path,
+                            "overall_risk": repo.findings.calculate_overall_risk(),
+                            "risk_tier": repo.findings.risk_tier_summary(),
+                            "vulnerabilities": repo.findings.vulnerabilities.len(),
+                            "total_commits": repo.findings.git_stats.total_commits,
+                        })
+                    })
+                    .collect();
+                to_json(
+                    &serde_json::json!({
+                        "highest_tier": multi.highest_tier(),
+                        "partial": self.partial,
+                        "repositories": repositories,
+                    }),
+                    self.config.compact_json,
+                )?
+            }
+            OutputFormat::Matches => {
+                let matches: Vec<_> = multi
+                    .repositories
+                    .iter()
+                    .flat_map(|repo| {
+                        repo.findings.flat_matches().into_iter().map(|m| {
+                            serde_json::json!({
+                                "repository": repo.name,
+                                "match": m,
+                            })
+                        })
+                    })
+                    .collect();
+                to_json(&matches, self.config.compact_json)?
+            }
+            OutputFormat::FindingsJson => {
+                let vulnerabilities: Vec<_> = multi
+                    .repositories
+                    .iter()
+                    .flat_map(|repo| {
+                        repo.findings.vulnerabilities.iter().map(|v| {
+                            serde_json::json!({
+                                "repository": repo.name,
+                                "finding": v,
+                            })
+                        })
+                    })
+                    .collect();
+                to_json(&vulnerabilities, self.config.compact_json)?
+            }
+            OutputFormat::Checklist => checklist::render_multi(multi, self.partial),
+            OutputFormat::GitLab => {
+                GitLabGenerator::new(self.config.compact_json).generate_multi_repo(multi)?
+            }
+            OutputFormat::Sqlite => unreachable!("SQLite output is written above"),
+        };
+
+        fs::write(&self.output_path, content)?;
+        info!("Multi-repository report saved to {}", self.output_path);
+        Ok(())
+    }
 }
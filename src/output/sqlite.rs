@@ -0,0 +1,194 @@
+use crate::analysis::CombinedFindings;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, Transaction};
+use std::collections::HashSet;
+
+/// Tables written by `--output sqlite`. Every row carries the `run_id` of the scan that wrote
+/// it, so one database can collect scans of many repositories over time.
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    repository TEXT NOT NULL,
+    path TEXT NOT NULL,
+    branch TEXT,
+    scanned_at TEXT NOT NULL,
+    total_commits INTEGER NOT NULL,
+    overall_risk REAL NOT NULL,
+    risk_tier TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS findings (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    commit_id TEXT NOT NULL,
+    commit_subject TEXT NOT NULL,
+    author TEXT NOT NULL,
+    date TEXT NOT NULL,
+    risk_score REAL NOT NULL,
+    confidence TEXT NOT NULL,
+    cve_references TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vulnerabilities (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    finding_id INTEGER NOT NULL REFERENCES findings(id),
+    pattern_name TEXT NOT NULL,
+    matched_text TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    category TEXT NOT NULL,
+    cwe TEXT,
+    confidence TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    line_number INTEGER,
+    remediation TEXT
+);
+CREATE TABLE IF NOT EXISTS authors (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    name TEXT NOT NULL,
+    email TEXT NOT NULL,
+    commits INTEGER NOT NULL,
+    lines_added INTEGER NOT NULL,
+    lines_removed INTEGER NOT NULL,
+    first_commit TEXT NOT NULL,
+    last_commit TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS files (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    path TEXT NOT NULL,
+    commits INTEGER NOT NULL,
+    authors INTEGER NOT NULL,
+    first_commit TEXT NOT NULL,
+    last_commit TEXT NOT NULL,
+    single_author INTEGER NOT NULL,
+    stale INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS findings_run ON findings(run_id);
+CREATE INDEX IF NOT EXISTS vulnerabilities_finding ON vulnerabilities(finding_id);
+";
+
+/// Appends scan results to a SQLite database, creating the schema on first use
+pub struct SqliteGenerator {
+    connection: Connection,
+}
+
+impl SqliteGenerator {
+    pub fn open(path: &str) -> Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite database {}", path))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Record one repository's findings as a new run
+    pub fn write(&mut self, repository: &str, findings: &CombinedFindings) -> Result<()> {
+        let tx = self.connection.transaction()?;
+        let run_id = Self::insert_run(&tx, repository, findings)?;
+        Self::insert_findings(&tx, run_id, findings)?;
+        Self::insert_authors(&tx, run_id, findings)?;
+        Self::insert_files(&tx, run_id, findings)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn insert_run(tx: &Transaction, repository: &str, findings: &CombinedFindings) -> Result<i64> {
+        let git_stats = &findings.git_stats;
+        tx.execute(
+            "INSERT INTO runs (repository, path, branch, scanned_at, total_commits, overall_risk, risk_tier)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                repository,
+                git_stats.path,
+                git_stats.head_branch,
+                findings.config.output.reference_date().to_rfc3339(),
+                git_stats.total_commits as i64,
+                findings.calculate_overall_risk(),
+                findings.risk_tier().as_str(),
+            ],
+        )?;
+        Ok(tx.last_insert_rowid())
+    }
+
+    fn insert_findings(tx: &Transaction, run_id: i64, findings: &CombinedFindings) -> Result<()> {
+        let mut insert_finding = tx.prepare(
+            "INSERT INTO findings (run_id, commit_id, commit_subject, author, date, risk_score, confidence, cve_references)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let mut insert_match = tx.prepare(
+            "INSERT INTO vulnerabilities (run_id, finding_id, pattern_name, matched_text, severity, category, cwe, confidence, file_path, line_number, remediation)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+
+        for finding in &findings.vulnerabilities {
+            insert_finding.execute(params![
+                run_id,
+                finding.commit_id,
+                finding.commit_message.lines().next().unwrap_or(""),
+                finding.author,
+                finding.date.to_rfc3339(),
+                finding.risk_score,
+                finding.confidence().as_str(),
+                finding.cve_references.join(","),
+            ])?;
+            let finding_id = tx.last_insert_rowid();
+
+            for pattern_match in &finding.patterns_matched {
+                insert_match.execute(params![
+                    run_id,
+                    finding_id,
+                    pattern_match.pattern_name,
+                    pattern_match.matched_text,
+                    format!("{:?}", pattern_match.severity),
+                    pattern_match.category.name(),
+                    pattern_match.cwe,
+                    pattern_match.confidence.as_str(),
+                    pattern_match.file_path,
+                    pattern_match.line_number.map(|n| n as i64),
+                    pattern_match.remediation,
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_authors(tx: &Transaction, run_id: i64, findings: &CombinedFindings) -> Result<()> {
+        let mut insert = tx.prepare(
+            "INSERT INTO authors (run_id, name, email, commits, lines_added, lines_removed, first_commit, last_commit)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for author in findings.git_stats.author_stats.values() {
+            insert.execute(params![
+                run_id,
+                author.name,
+                author.email,
+                author.commits as i64,
+                author.lines_added as i64,
+                author.lines_removed as i64,
+                author.first_commit.to_rfc3339(),
+                author.last_commit.to_rfc3339(),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn insert_files(tx: &Transaction, run_id: i64, findings: &CombinedFindings) -> Result<()> {
+        let git_stats = &findings.git_stats;
+        let single_author: HashSet<_> = git_stats.single_author_files.iter().collect();
+        let stale: HashSet<_> = git_stats.stale_files.iter().collect();
+        let mut insert = tx.prepare(
+            "INSERT INTO files (run_id, path, commits, authors, first_commit, last_commit, single_author, stale)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for history in git_stats.file_history.values() {
+            insert.execute(params![
+                run_id,
+                history.path,
+                history.commits.len() as i64,
+                history.authors.len() as i64,
+                history.first_commit.to_rfc3339(),
+                history.last_commit.to_rfc3339(),
+                single_author.contains(&history.path),
+                stale.contains(&history.path),
+            ])?;
+        }
+        Ok(())
+    }
+}
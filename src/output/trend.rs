@@ -0,0 +1,152 @@
+use crate::analysis::CombinedFindings;
+use crate::error::{self, CommitRaiderError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const CSV_HEADER: &str = "timestamp,commit,overall_risk,findings,repository";
+
+/// Width and height of the sparkline's SVG view box
+const SPARKLINE_WIDTH: f64 = 200.0;
+const SPARKLINE_HEIGHT: f64 = 40.0;
+
+/// One run's headline numbers, appended to the trend file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendRecord {
+    pub timestamp: DateTime<Utc>,
+    pub repository: String,
+    /// Newest commit included in the analysis
+    pub commit: Option<String>,
+    pub overall_risk: f64,
+    pub findings: usize,
+}
+
+impl TrendRecord {
+    pub fn from_findings(findings: &CombinedFindings) -> Self {
+        Self {
+            timestamp: findings.config.output.reference_date(),
+            repository: findings.git_stats.path.clone(),
+            commit: findings
+                .git_stats
+                .commit_history
+                .first()
+                .map(|c| c.id.clone()),
+            overall_risk: findings.calculate_overall_risk(),
+            findings: findings.vulnerabilities.len(),
+        }
+    }
+}
+
+/// Append this run to the trend file and return the repository's history including it.
+/// Files ending in `.csv` are written as CSV, anything else as a JSON array.
+pub fn record(path: &Path, findings: &CombinedFindings) -> error::Result<Vec<TrendRecord>> {
+    let mut records = load(path)?;
+    records.push(TrendRecord::from_findings(findings));
+    save(path, &records)?;
+
+    Ok(records
+        .into_iter()
+        .filter(|r| r.repository == findings.git_stats.path)
+        .collect())
+}
+
+pub fn load(path: &Path) -> error::Result<Vec<TrendRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+
+    if is_csv(path) {
+        content
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| parse_csv_record(path, line))
+            .collect()
+    } else if content.trim().is_empty() {
+        Ok(Vec::new())
+    } else {
+        serde_json::from_str(&content).map_err(|e| {
+            CommitRaiderError::Config(format!("Invalid trend file {}: {}", path.display(), e))
+        })
+    }
+}
+
+fn save(path: &Path, records: &[TrendRecord]) -> error::Result<()> {
+    let content = if is_csv(path) {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+        for r in records {
+            csv.push_str(&format!(
+                "{},{},{:.4},{},\"{}\"\n",
+                r.timestamp.to_rfc3339(),
+                r.commit.as_deref().unwrap_or_default(),
+                r.overall_risk,
+                r.findings,
+                r.repository.replace('"', "\"\"")
+            ));
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(records)
+            .map_err(|e| CommitRaiderError::Render(format!("Failed to encode trend file: {}", e)))?
+    };
+
+    fs::write(path, content)?;
+    Ok(())
+}
+
+fn parse_csv_record(path: &Path, line: &str) -> error::Result<TrendRecord> {
+    let invalid = || {
+        CommitRaiderError::Config(format!(
+            "Invalid trend record in {}: '{}'",
+            path.display(),
+            line
+        ))
+    };
+
+    // The repository path is last and quoted, so it may itself contain commas
+    let fields: Vec<&str> = line.splitn(5, ',').collect();
+    if fields.len() != 5 {
+        return Err(invalid());
+    }
+
+    Ok(TrendRecord {
+        timestamp: DateTime::parse_from_rfc3339(fields[0])
+            .map_err(|_| invalid())?
+            .with_timezone(&Utc),
+        commit: Some(fields[1].to_string()).filter(|c| !c.is_empty()),
+        overall_risk: fields[2].parse().map_err(|_| invalid())?,
+        findings: fields[3].parse().map_err(|_| invalid())?,
+        repository: fields[4].trim_matches('"').replace("\"\"", "\""),
+    })
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+}
+
+/// SVG polyline points plotting overall risk (0-10) across runs, oldest on the left
+pub fn sparkline_points(records: &[TrendRecord]) -> String {
+    let step = SPARKLINE_WIDTH / (records.len().max(2) - 1) as f64;
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let risk = if r.overall_risk.is_finite() {
+                r.overall_risk.clamp(0.0, 10.0)
+            } else {
+                0.0
+            };
+            format!(
+                "{:.1},{:.1}",
+                i as f64 * step,
+                SPARKLINE_HEIGHT - risk / 10.0 * SPARKLINE_HEIGHT
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,38 +1,333 @@
 use super::*;
-use crate::git::RepositoryStats;
-use anyhow::{Context, Result};
+use crate::cancel;
+use crate::config::{AnalysisConfig, Config, CustomPattern, RiskConfig};
+use crate::error::{self, CommitRaiderError};
+use crate::git::normalize::normalize_path;
+use crate::git::patch::PatchCommit;
+use crate::git::{AddedLine, CommitInfo, RepositoryStats};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
 use fancy_regex::Regex;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::overrides::OverrideBuilder;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
-use tracing::info;
+use tracing::{info, warn};
+
+/// Pattern matches collected for one commit, with the context weight of each match
+#[derive(Default)]
+struct CommitMatches {
+    patterns_matched: Vec<PatternMatch>,
+    match_weights: Vec<f64>,
+    cve_references: Vec<String>,
+}
 
 pub struct PatternEngine {
-    compiled_patterns: Vec<(Regex, VulnerabilityPattern)>,
+    compiled_patterns: Vec<(Regex, VulnerabilityPattern, PatternSource)>,
+    /// Any negative keyword as a whole word, so "test" does not hit "latest"; `None` without
+    /// keywords
+    negative_keywords: Option<regex::Regex>,
+    negative_context_window: usize,
+    negative_context_penalty: f64,
+    risk: RiskConfig,
+    /// Commit messages are scanned up to this many bytes (0 scans them whole)
+    max_message_length: usize,
+    /// Earliest year accepted in a CVE reference
+    min_cve_year: i32,
+    /// Date finding ages are measured from, see `OutputConfig::reference_date`
+    reference_date: DateTime<Utc>,
+    sensitive_paths: GlobSet,
+    /// Multiplier for each glob in `sensitive_paths`, by index
+    sensitivity_multipliers: Vec<f64>,
+    /// Pool the scans run on instead of the global one, bounding CPU-heavy regexes separately
+    scan_pool: Option<rayon::ThreadPool>,
 }
 
 impl PatternEngine {
-    pub fn new(pattern_set: &str) -> Result<Self> {
-        let patterns = match pattern_set {
+    pub fn new(pattern_set: &str, config: &Config) -> error::Result<Self> {
+        let builtin = match pattern_set {
             "memorysafety" => Self::get_memory_safety_patterns(),
             "crypto" => Self::get_crypto_patterns(),
             "web" => Self::get_web_patterns(),
-            "all" => default_patterns(),
-            _ => Self::get_vuln_patterns(),
+            "debt" => debt_patterns(),
+            "regression" => security_regression_patterns(),
+            "all" => [default_patterns(), security_regression_patterns()].concat(),
+            _ => [Self::get_vuln_patterns(), security_regression_patterns()].concat(),
         };
+        let custom = config
+            .patterns
+            .custom_patterns
+            .iter()
+            .map(Self::custom_pattern)
+            .collect::<error::Result<Vec<_>>>()?;
+        let from_file = match &config.patterns.pattern_file {
+            Some(path) => Self::load_pattern_file(path)?,
+            None => Vec::new(),
+        };
+
+        let mut patterns = Self::merge_patterns([
+            (PatternSource::BuiltIn, builtin),
+            (PatternSource::Config, custom),
+            (PatternSource::File, from_file),
+        ]);
+        Self::apply_severity_map(&mut patterns, &config.patterns.severity_map)?;
+        Self::disable_patterns(&mut patterns, &config.patterns.disabled_patterns);
+        patterns.retain(|(p, _)| p.category.is_enabled(&config.patterns.enabled_categories));
 
         info!("Loading {} vulnerability patterns", patterns.len());
 
         let compiled_patterns = patterns
+            .into_iter()
+            .map(|(pattern, origin)| {
+                let source = pattern.regex_source().map_err(|e| {
+                    CommitRaiderError::Config(format!("pattern '{}': {}", pattern.name, e))
+                })?;
+                let regex =
+                    Regex::new(&source).map_err(|source| CommitRaiderError::PatternCompile {
+                        name: pattern.name.clone(),
+                        source: Box::new(source),
+                    })?;
+                Ok((regex, pattern, origin))
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+
+        let mut sensitive_paths = GlobSetBuilder::new();
+        let mut sensitivity_multipliers = Vec::new();
+        for (glob, multiplier) in &config.risk.path_sensitivity {
+            let glob = Glob::new(glob).map_err(|e| {
+                CommitRaiderError::Config(format!("path sensitivity glob '{}': {}", glob, e))
+            })?;
+            sensitive_paths.add(glob);
+            sensitivity_multipliers.push(*multiplier);
+        }
+        let sensitive_paths = sensitive_paths
+            .build()
+            .map_err(|e| CommitRaiderError::Config(format!("path sensitivity: {}", e)))?;
+
+        let scan_pool = match config.patterns.scan_threads {
+            0 => None,
+            threads => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|i| format!("pattern-scan-{}", i))
+                    .build()
+                    .map_err(|e| {
+                        CommitRaiderError::Config(format!("pattern scan thread pool: {}", e))
+                    })?,
+            ),
+        };
+
+        Ok(Self {
+            compiled_patterns,
+            negative_keywords: Self::negative_keyword_regex(&config.patterns.negative_keywords)?,
+            negative_context_window: config.patterns.negative_context_window,
+            negative_context_penalty: config.patterns.negative_context_penalty,
+            risk: config.risk.clone(),
+            max_message_length: config.patterns.max_message_length,
+            min_cve_year: config.patterns.min_cve_year,
+            reference_date: config.output.reference_date(),
+            sensitive_paths,
+            sensitivity_multipliers,
+            scan_pool,
+        })
+    }
+
+    /// Case-insensitive regex matching any of `keywords` as whole words, `None` without any
+    fn negative_keyword_regex(keywords: &[String]) -> error::Result<Option<regex::Regex>> {
+        if keywords.is_empty() {
+            return Ok(None);
+        }
+        let alternatives: Vec<String> = keywords.iter().map(|k| regex::escape(k.trim())).collect();
+        let source = format!(r"(?i)\b(?:{})\b", alternatives.join("|"));
+        regex::Regex::new(&source)
+            .map(Some)
+            .map_err(|e| CommitRaiderError::Config(format!("negative keywords: {}", e)))
+    }
+
+    /// Run a parallel scan on the dedicated pool, or on the global pool without one
+    fn in_scan_pool<T: Send>(&self, scan: impl FnOnce() -> T + Send) -> T {
+        match &self.scan_pool {
+            Some(pool) => pool.install(scan),
+            None => scan(),
+        }
+    }
+
+    /// Combine pattern sources in increasing precedence, keeping one pattern per name.
+    /// A later definition replaces an earlier one in place, so load order stays stable.
+    fn merge_patterns(
+        sources: impl IntoIterator<Item = (PatternSource, Vec<VulnerabilityPattern>)>,
+    ) -> Vec<(VulnerabilityPattern, PatternSource)> {
+        let mut merged: Vec<(VulnerabilityPattern, PatternSource)> = Vec::new();
+        for (source, patterns) in sources {
+            for pattern in patterns {
+                match merged.iter_mut().find(|(p, _)| p.name == pattern.name) {
+                    Some(existing) => {
+                        info!(
+                            "Pattern '{}' from {} overrides the {} definition",
+                            pattern.name, source, existing.1
+                        );
+                        *existing = (pattern, source);
+                    }
+                    None => merged.push((pattern, source)),
+                }
+            }
+        }
+        merged
+    }
+
+    /// Read custom patterns from a JSON array in the `custom_patterns` format
+    fn load_pattern_file(path: &Path) -> error::Result<Vec<VulnerabilityPattern>> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            CommitRaiderError::Config(format!(
+                "Failed to read pattern file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let patterns: Vec<CustomPattern> = serde_json::from_str(&content).map_err(|e| {
+            CommitRaiderError::Config(format!("Invalid pattern file {}: {}", path.display(), e))
+        })?;
+        patterns.iter().map(Self::custom_pattern).collect()
+    }
+
+    /// Effective patterns after merging and category filtering, with where each came from
+    pub fn patterns(&self) -> impl Iterator<Item = (&VulnerabilityPattern, PatternSource)> {
+        self.compiled_patterns
+            .iter()
+            .map(|(_, pattern, source)| (pattern, *source))
+    }
+
+    /// Matches per loaded pattern in `findings`, most frequent first. Patterns that never
+    /// fired are included with zero hits, so dead rules stand out. Regression patterns only
+    /// match added diff lines, so unless `diff_scan` they could not fire and are left out.
+    pub fn pattern_hits(
+        &self,
+        findings: &[VulnerabilityFinding],
+        diff_scan: bool,
+    ) -> Vec<PatternHits> {
+        let mut hits: Vec<PatternHits> = self
+            .compiled_patterns
             .iter()
-            .map(|pattern| {
-                let regex = Regex::new(&pattern.pattern)
-                    .with_context(|| format!("Failed to compile pattern: {}", pattern.name))?;
-                Ok((regex, pattern.clone()))
+            .filter(|(_, pattern, _)| diff_scan || pattern.category != Category::SecurityRegression)
+            .map(|(_, pattern, _)| PatternHits {
+                name: pattern.name.clone(),
+                category: pattern.category.clone(),
+                matches: 0,
+                commits: 0,
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect();
+        for finding in findings {
+            for entry in &mut hits {
+                let matches = finding
+                    .patterns_matched
+                    .iter()
+                    .filter(|m| m.pattern_name == entry.name)
+                    .count();
+                entry.matches += matches;
+                entry.commits += usize::from(matches > 0);
+            }
+        }
+        // Stable, so patterns with equal counts keep their load order
+        hits.sort_by_key(|h| std::cmp::Reverse(h.matches));
+        hits
+    }
 
-        Ok(Self { compiled_patterns })
+    /// Number of compiled patterns
+    pub fn pattern_count(&self) -> usize {
+        self.compiled_patterns.len()
+    }
+
+    /// Distinct categories of the loaded patterns, in load order
+    pub fn categories(&self) -> Vec<&Category> {
+        let mut categories = Vec::new();
+        for (_, pattern, _) in &self.compiled_patterns {
+            if !categories.contains(&&pattern.category) {
+                categories.push(&pattern.category);
+            }
+        }
+        categories
+    }
+
+    /// Count technical-debt markers in the files of `work_tree`, densest files first. Report
+    /// artifacts and `line_count_exclusions` (lock files, vendored code) are skipped.
+    pub fn marker_density(
+        &self,
+        work_tree: &Path,
+        config: &AnalysisConfig,
+    ) -> Result<Vec<MarkerDensity>> {
+        let markers: Vec<_> = self
+            .compiled_patterns
+            .iter()
+            .filter(|(_, pattern, _)| pattern.category == Category::TechnicalDebt)
+            .map(|(regex, pattern, _)| (regex, pattern.name.as_str()))
+            .collect();
+        if markers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut overrides = OverrideBuilder::new(work_tree);
+        for exclusion in &config.line_count_exclusions {
+            overrides.add(&format!("!{}", exclusion))?;
+        }
+        let mut files = Vec::new();
+        for entry in config
+            .file_walker(work_tree)
+            .overrides(overrides.build()?)
+            .build()
+        {
+            let entry = entry?;
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let relative_path =
+                normalize_path(entry.path().strip_prefix(work_tree).unwrap_or(entry.path()));
+            if !config.is_report_artifact(&relative_path) {
+                files.push((entry.into_path(), relative_path));
+            }
+        }
+
+        let mut densities: Vec<MarkerDensity> = files
+            .par_iter()
+            .filter_map(|(path, relative_path)| {
+                // Binary and non-UTF-8 files have no markers worth reporting
+                let content = std::fs::read_to_string(path).ok()?;
+                let mut counts = BTreeMap::new();
+                let mut lines = 0;
+                for line in content.lines() {
+                    lines += 1;
+                    for (regex, name) in &markers {
+                        let found = regex.find_iter(line).filter(|m| m.is_ok()).count();
+                        if found > 0 {
+                            *counts.entry(name.to_string()).or_insert(0) += found;
+                        }
+                    }
+                }
+                let total: usize = counts.values().sum();
+                (total > 0).then(|| MarkerDensity {
+                    path: relative_path.clone(),
+                    lines,
+                    markers: counts,
+                    total,
+                    density: total as f64 * 1000.0 / lines.max(1) as f64,
+                })
+            })
+            .collect();
+
+        densities.sort_by(|a, b| {
+            b.density
+                .partial_cmp(&a.density)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.total.cmp(&a.total))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        info!(
+            "Found technical-debt markers in {} of {} files",
+            densities.len(),
+            files.len()
+        );
+        Ok(densities)
     }
 
     pub async fn scan_repository(
@@ -51,93 +346,386 @@ impl PatternEngine {
                 .progress_chars("=>-"),
         );
 
-        let findings: Vec<_> = git_stats
-            .commit_history
-            .par_iter()
-            .filter_map(|commit| {
-                pb.inc(1);
-                self.analyze_commit(commit).ok().flatten()
-            })
-            .collect();
+        let findings: Vec<_> = self.in_scan_pool(|| {
+            git_stats
+                .commit_history
+                .par_iter()
+                .filter_map(|commit| {
+                    pb.inc(1);
+                    // Commits left once interrupted are skipped rather than scanned
+                    if cancel::is_cancelled() {
+                        return None;
+                    }
+                    self.analyze_commit(commit).ok().flatten()
+                })
+                .collect()
+        });
 
         pb.finish_with_message("Scan completed");
         info!("Found {} potential vulnerabilities", findings.len());
         Ok(findings)
     }
 
+    /// Scan one batch of commits as the git analysis reads it, for streaming analysis
+    pub fn scan_commits(&self, commits: &[CommitInfo]) -> Vec<VulnerabilityFinding> {
+        self.in_scan_pool(|| {
+            commits
+                .par_iter()
+                .filter_map(|commit| self.analyze_commit(commit).ok().flatten())
+                .collect()
+        })
+    }
+
+    /// Scan the single commit in `git_stats` and the lines it adds, attributing line matches
+    /// to their file and line. `scan_message` is off for the synthetic uncommitted-changes commit.
+    pub fn scan_diff(
+        &self,
+        git_stats: &RepositoryStats,
+        added_lines: &[AddedLine],
+        scan_message: bool,
+    ) -> Vec<VulnerabilityFinding> {
+        let Some(commit) = git_stats.commit_history.first() else {
+            return Vec::new();
+        };
+
+        let findings: Vec<_> = self
+            .analyze_commit_diff(commit, added_lines, scan_message)
+            .into_iter()
+            .collect();
+        info!("Found {} potential vulnerabilities", findings.len());
+        findings
+    }
+
+    /// Scan the message and added lines of each patch of a patch or mbox file
+    pub fn scan_patches(&self, patches: &[PatchCommit]) -> Vec<VulnerabilityFinding> {
+        let findings: Vec<_> = self.in_scan_pool(|| {
+            patches
+                .par_iter()
+                .filter_map(|patch| {
+                    self.analyze_commit_diff(&patch.commit, &patch.added_lines, true)
+                })
+                .collect()
+        });
+        info!("Found {} potential vulnerabilities", findings.len());
+        findings
+    }
+
+    fn analyze_commit_diff(
+        &self,
+        commit: &CommitInfo,
+        added_lines: &[AddedLine],
+        scan_message: bool,
+    ) -> Option<VulnerabilityFinding> {
+        let mut matches = CommitMatches::default();
+        if scan_message {
+            self.match_message(commit, &mut matches);
+        }
+        for line in added_lines {
+            // Code lines rank like message bodies, never like a subject line
+            self.match_text(
+                &line.content,
+                0,
+                &line.path,
+                Some(line.line_number),
+                &mut matches,
+            );
+        }
+        self.finding(commit, matches)
+    }
+
     fn analyze_commit(
         &self,
         commit: &crate::git::CommitInfo,
     ) -> Result<Option<VulnerabilityFinding>> {
-        let mut patterns_matched = Vec::new();
-        let mut cve_references = Vec::new();
-
         // Go through commit message and match the compiled patterns
-        for (regex, pattern) in &self.compiled_patterns {
-            if let Ok(Some(captures)) = regex.captures(&commit.message) {
-                let matched_text = captures.get(0).unwrap().as_str().to_string();
+        let mut matches = CommitMatches::default();
+        self.match_message(commit, &mut matches);
+
+        Ok(self.finding(commit, matches))
+    }
+
+    /// Match the commit message, scanning at most `max_message_length` bytes of it
+    fn match_message(&self, commit: &crate::git::CommitInfo, matches: &mut CommitMatches) {
+        let message = commit.message.as_str();
+        let mut scanned_len = message.len();
+        if self.max_message_length > 0 && scanned_len > self.max_message_length {
+            scanned_len = self.max_message_length;
+            while !message.is_char_boundary(scanned_len) {
+                scanned_len -= 1;
+            }
+        }
+        let scanned = &message[..scanned_len];
+        let subject_len = scanned.lines().next().map_or(0, str::len);
+
+        let first_match = matches.patterns_matched.len();
+        self.match_text(scanned, subject_len, "commit_message", None, matches);
+
+        if scanned_len < message.len() {
+            for pattern_match in &mut matches.patterns_matched[first_match..] {
+                pattern_match.context.push_str(&format!(
+                    "\n[message truncated: scanned {} of {} bytes]",
+                    scanned_len,
+                    message.len()
+                ));
+            }
+        }
+
+        // Trailers are stripped from the message, so `CVE:` trailers are matched on their own
+        let cve_trailers = commit
+            .trailers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("CVE"))
+            .flat_map(|(_, values)| values);
+        for value in cve_trailers {
+            self.match_text(value, 0, "commit_trailer", None, matches);
+        }
+    }
+
+    /// Canonical `CVE-YYYY-NNNN` form of the digits captured after "CVE", whatever separators
+    /// the source text used. Years before `min_cve_year` or after next year are rejected.
+    fn normalize_cve_id(&self, captured: &str) -> Option<String> {
+        let digits: String = captured.chars().filter(|c| c.is_ascii_digit()).collect();
+        if digits.len() < 8 {
+            return None;
+        }
+        let (year, sequence) = digits.split_at(4);
+        let year: i32 = year.parse().ok()?;
+        if year < self.min_cve_year || year > Utc::now().year() + 1 {
+            return None;
+        }
+        Some(format!("CVE-{}-{}", year, sequence))
+    }
+
+    /// Match every compiled pattern against `text`; matches starting before `subject_len`
+    /// count as subject-line matches
+    fn match_text(
+        &self,
+        text: &str,
+        subject_len: usize,
+        file_path: &str,
+        line_number: Option<usize>,
+        matches: &mut CommitMatches,
+    ) {
+        for (regex, pattern, _) in &self.compiled_patterns {
+            // Regressions are judged by the code a diff adds, which alone carries a line number
+            if pattern.category == Category::SecurityRegression && line_number.is_none() {
+                continue;
+            }
+            // First match the pattern's own token and length constraints accept. A CVE
+            // reference with an implausible year, e.g. a ticket or build number after "CVE",
+            // is passed over for the next one.
+            let accepted = regex.captures_iter(text).flatten().find_map(|captures| {
+                let whole_match = captures.get(0).unwrap();
+                if !pattern.accepts_match(text, whole_match.start(), whole_match.end()) {
+                    return None;
+                }
+                let cve_id = if pattern.name == "CVE Reference" {
+                    Some(self.normalize_cve_id(captures.get(1)?.as_str())?)
+                } else {
+                    None
+                };
+                Some((captures, cve_id))
+            });
+            if let Some((captures, cve_id)) = accepted {
+                let whole_match = captures.get(0).unwrap();
+                let weight = self.context_weight(text, whole_match.start(), whole_match.end());
+                if weight <= 0.0 {
+                    continue;
+                }
+                let mut matched_text = whole_match.as_str().to_string();
                 if pattern.name == "CVE Reference" {
-                    if let Ok(Some(cve_match)) = regex.captures(&commit.message) {
-                        if let Some(cve_id) = cve_match.get(1) {
-                            cve_references.push(format!("CVE-{}", cve_id.as_str()));
-                        }
-                    }
+                    // A reference wrapped across lines reads as one
+                    matched_text = matched_text
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
                 }
-                patterns_matched.push(PatternMatch {
+                if let Some(cve_id) = cve_id {
+                    matches.cve_references.push(cve_id);
+                }
+                matches.patterns_matched.push(PatternMatch {
                     pattern_name: pattern.name.clone(),
                     matched_text,
                     severity: pattern.severity.clone(),
                     category: pattern.category.clone(),
-                    file_path: "commit_message".to_string(),
-                    line_number: None,
-                    context: commit.message.clone(),
-                    cve_references: cve_references.clone(),
+                    file_path: file_path.to_string(),
+                    line_number,
+                    context: text.to_string(),
+                    cve_references: matches.cve_references.clone(),
+                    cwe: pattern.cwe.clone(),
+                    remediation: None,
+                    confidence: Confidence::for_match(
+                        whole_match.start() < subject_len,
+                        pattern.cwe.is_some(),
+                    ),
                 });
+                matches.match_weights.push(weight);
             }
         }
+    }
 
-        if patterns_matched.is_empty() {
-            return Ok(None);
+    /// Finding for a commit from its matches, `None` when nothing matched
+    fn finding(
+        &self,
+        commit: &crate::git::CommitInfo,
+        matches: CommitMatches,
+    ) -> Option<VulnerabilityFinding> {
+        if matches.patterns_matched.is_empty() {
+            return None;
         }
 
-        let risk_score = self.calculate_risk_score(&patterns_matched, commit);
+        let risk_score =
+            self.calculate_risk_score(&matches.patterns_matched, &matches.match_weights, commit);
 
-        Ok(Some(VulnerabilityFinding {
+        Some(VulnerabilityFinding {
             commit_id: commit.id.clone(),
             commit_message: commit.message.clone(),
             author: commit.author.clone(),
             date: commit.authored_date,
+            age_days: age_in_days(commit.authored_date, self.reference_date),
             files_changed: commit.files_changed.clone(),
-            patterns_matched,
+            patterns_matched: matches.patterns_matched,
             risk_score,
-            cve_references,
-        }))
+            cve_references: matches.cve_references,
+            trailers: commit.trailers.clone(),
+            related_commits: Vec::new(),
+        })
+    }
+
+    /// Weight for a match based on negative-context keywords surrounding it.
+    /// Returns 1.0 when no negative context is found.
+    fn context_weight(&self, message: &str, start: usize, end: usize) -> f64 {
+        let Some(negative_keywords) = &self.negative_keywords else {
+            return 1.0;
+        };
+
+        // Keywords are found in the whole message, so a window edge cutting "latest" short
+        // does not leave a "test"
+        let window_start = start.saturating_sub(self.negative_context_window);
+        let window_end = end + self.negative_context_window;
+        if negative_keywords
+            .find_iter(message)
+            .any(|k| k.start() < window_end && k.end() > window_start)
+        {
+            self.negative_context_penalty.max(0.0)
+        } else {
+            1.0
+        }
     }
 
     fn calculate_risk_score(
         &self,
         patterns: &[PatternMatch],
+        weights: &[f64],
         commit: &crate::git::CommitInfo,
     ) -> f64 {
         let base_score: f64 = patterns
             .iter()
-            .map(|p| match p.severity {
-                Severity::Critical => 9.0,
-                Severity::High => 7.0,
-                Severity::Medium => 5.0,
-                Severity::Low => 3.0,
-                Severity::Info => 1.0,
+            .zip(weights)
+            .map(|(p, weight)| {
+                let severity_score = match p.severity {
+                    Severity::Critical => 9.0,
+                    Severity::High => 7.0,
+                    Severity::Medium => 5.0,
+                    Severity::Low => 3.0,
+                    Severity::Info => 1.0,
+                };
+                severity_score * weight
             })
             .sum();
 
-        let file_multiplier = (commit.files_changed.len() as f64).sqrt();
+        let file_multiplier =
+            (commit.files_changed.len().max(1) as f64).powf(self.risk.file_count_exponent);
+
+        // Tiny targeted commits are weighted up, huge mechanical ones down
+        let lines_changed = commit.insertions + commit.deletions;
+        let size_factor = if lines_changed > 0 && lines_changed <= self.risk.small_commit_lines {
+            self.risk.small_commit_boost
+        } else if lines_changed >= self.risk.large_commit_lines {
+            self.risk.large_commit_penalty
+        } else {
+            1.0
+        };
         let cve_multiplier = if patterns.iter().any(|p| p.pattern_name == "CVE Reference") {
             2.0
         } else {
             1.0
         };
 
-        (base_score * file_multiplier * cve_multiplier).min(10.0)
+        let sensitivity = self.path_sensitivity(&commit.files_changed);
+
+        (base_score * file_multiplier * size_factor * cve_multiplier * sensitivity).min(10.0)
+    }
+
+    /// Highest configured sensitivity multiplier among the files, 1.0 when no glob matches
+    fn path_sensitivity(&self, files: &[String]) -> f64 {
+        files
+            .iter()
+            .flat_map(|file| self.sensitive_paths.matches(file))
+            .map(|index| self.sensitivity_multipliers[index])
+            .reduce(f64::max)
+            .unwrap_or(1.0)
+    }
+
+    /// Override pattern severities by name, before scoring ever sees them. Names matching no
+    /// loaded pattern only warn, since a map is often shared between pattern sets.
+    fn apply_severity_map(
+        patterns: &mut [(VulnerabilityPattern, PatternSource)],
+        severity_map: &HashMap<String, String>,
+    ) -> error::Result<()> {
+        for (name, severity) in severity_map {
+            let severity: Severity = severity.parse().map_err(|e| {
+                CommitRaiderError::Config(format!("severity map entry '{}': {}", name, e))
+            })?;
+            match patterns
+                .iter_mut()
+                .find(|(pattern, _)| &pattern.name == name)
+            {
+                Some((pattern, _)) => {
+                    info!(
+                        "Severity of pattern '{}' remapped from {:?} to {:?}",
+                        name, pattern.severity, severity
+                    );
+                    pattern.severity = severity;
+                }
+                None => warn!("Severity map entry '{}' matches no loaded pattern", name),
+            }
+        }
+        Ok(())
+    }
+
+    fn disable_patterns(
+        patterns: &mut Vec<(VulnerabilityPattern, PatternSource)>,
+        disabled: &[String],
+    ) {
+        for name in disabled {
+            let before = patterns.len();
+            patterns.retain(|(pattern, _)| &pattern.name != name);
+            if patterns.len() == before {
+                warn!("Disabled pattern '{}' matches no loaded pattern", name);
+            } else {
+                info!("Pattern '{}' disabled", name);
+            }
+        }
+    }
+
+    fn custom_pattern(custom: &CustomPattern) -> error::Result<VulnerabilityPattern> {
+        let severity = custom.severity.parse().map_err(|e| {
+            CommitRaiderError::Config(format!("custom pattern '{}': {}", custom.name, e))
+        })?;
+
+        Ok(VulnerabilityPattern {
+            name: custom.name.clone(),
+            pattern: custom.pattern.clone(),
+            severity,
+            category: Category::from(custom.category.clone()),
+            description: custom.description.clone(),
+            cwe: None,
+            examples: Vec::new(),
+            flags: custom.flags.clone(),
+            standalone: custom.standalone,
+            min_match_length: custom.min_match_length,
+        })
     }
 
     fn get_memory_safety_patterns() -> Vec<VulnerabilityPattern> {
@@ -168,3 +756,53 @@ impl PatternEngine {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CVE ids a commit message referencing them would be reported with
+    fn cve_references(message: &str) -> Vec<String> {
+        let engine = PatternEngine::new("all", &Config::default()).unwrap();
+        let mut matches = CommitMatches::default();
+        engine.match_text(message, 0, "commit_message", None, &mut matches);
+        matches.cve_references
+    }
+
+    #[test]
+    fn wrapped_cve_reference_is_normalized() {
+        assert_eq!(cve_references("CVE-2021-\n1234"), ["CVE-2021-1234"]);
+    }
+
+    #[test]
+    fn space_separated_cve_reference_is_normalized() {
+        assert_eq!(cve_references("CVE 2021 1234"), ["CVE-2021-1234"]);
+    }
+
+    #[test]
+    fn lowercase_cve_reference_is_normalized() {
+        assert_eq!(cve_references("cve-2021-44228"), ["CVE-2021-44228"]);
+    }
+
+    #[test]
+    fn implausible_cve_year_does_not_hide_later_reference() {
+        assert_eq!(
+            cve_references("fixes CVE-1900-0001, CVE-2021-44228"),
+            ["CVE-2021-44228"]
+        );
+    }
+
+    #[test]
+    fn negative_keywords_match_whole_words() {
+        let engine = PatternEngine::new("all", &Config::default()).unwrap();
+        let message = "Fix buffer overflow in the latest parser";
+        assert_eq!(engine.context_weight(message, 4, 19), 1.0);
+        let message = "Fix buffer overflow in the parser test";
+        assert!(engine.context_weight(message, 4, 19) < 1.0);
+    }
+
+    #[test]
+    fn cve_followed_by_prose_does_not_match() {
+        assert!(cve_references("CVE\n\nfoo").is_empty());
+    }
+}
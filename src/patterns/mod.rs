@@ -1,8 +1,14 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
 
 pub mod engine;
+pub mod remediation;
 
 pub use engine::PatternEngine;
+pub use remediation::RemediationGuide;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VulnerabilityPattern {
@@ -13,9 +19,72 @@ pub struct VulnerabilityPattern {
     pub description: String,
     pub cwe: Option<String>,
     pub examples: Vec<String>,
+    /// Regex flags applied when compiling: `i` case-insensitive, `m` multi-line anchors,
+    /// `s` dot matches newline, `x` verbose. Inline groups like `(?is)` work as well.
+    #[serde(default)]
+    pub flags: Option<String>,
+    /// Only accept matches that stand alone, not glued to letters, digits, `_` or `-` on
+    /// either side, e.g. "exploit" but not "non-exploitable" or "exploit_test"
+    #[serde(default)]
+    pub standalone: bool,
+    /// Matches shorter than this many characters are ignored (0 accepts any length)
+    #[serde(default)]
+    pub min_match_length: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a pattern was defined. On name collisions the pattern file wins over the
+/// configuration, which wins over the built-in set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternSource {
+    BuiltIn,
+    Config,
+    File,
+}
+
+impl fmt::Display for PatternSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PatternSource::BuiltIn => "built-in",
+            PatternSource::Config => "config",
+            PatternSource::File => "pattern file",
+        })
+    }
+}
+
+/// Regex flags accepted in `VulnerabilityPattern::flags`
+pub const REGEX_FLAGS: &str = "imsx";
+
+impl VulnerabilityPattern {
+    /// Pattern source with `flags` prepended as an inline group
+    pub fn regex_source(&self) -> Result<String, String> {
+        match self.flags.as_deref().filter(|f| !f.is_empty()) {
+            None => Ok(self.pattern.clone()),
+            Some(flags) => match flags.chars().find(|c| !REGEX_FLAGS.contains(*c)) {
+                Some(unknown) => Err(format!(
+                    "unknown regex flag '{}' (expected any of '{}')",
+                    unknown, REGEX_FLAGS
+                )),
+                None => Ok(format!("(?{}){}", flags, self.pattern)),
+            },
+        }
+    }
+
+    /// Whether the match at `start..end` of `text` satisfies `standalone` and
+    /// `min_match_length`
+    pub fn accepts_match(&self, text: &str, start: usize, end: usize) -> bool {
+        if text[start..end].trim().chars().count() < self.min_match_length {
+            return false;
+        }
+        if !self.standalone {
+            return true;
+        }
+        let is_glue = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+        !text[..start].chars().next_back().is_some_and(is_glue)
+            && !text[end..].chars().next().is_some_and(is_glue)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum Severity {
     Critical,
     High,
@@ -24,7 +93,27 @@ pub enum Severity {
     Info,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "critical" => Ok(Severity::Critical),
+            "high" => Ok(Severity::High),
+            "medium" => Ok(Severity::Medium),
+            "low" => Ok(Severity::Low),
+            "info" => Ok(Severity::Info),
+            _ => Err(anyhow::anyhow!(
+                "Unknown severity '{}' (expected critical, high, medium, low or info)",
+                s
+            )),
+        }
+    }
+}
+
+/// Pattern category. Serialized as its name so custom categories read the same as built-in ones.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
 pub enum Category {
     MemorySafety,
     Cryptography,
@@ -35,9 +124,147 @@ pub enum Category {
     DataExposure,
     CodeInjection,
     Generic,
+    /// Maintenance markers such as TODO and FIXME, only loaded by the `debt` pattern set
+    TechnicalDebt,
+    /// Code that disables or weakens a security setting, matched against the lines a diff
+    /// adds and never against commit messages
+    SecurityRegression,
+    /// User-defined category from a custom pattern
+    Custom(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Category {
+    pub const BUILTIN: [Category; 11] = [
+        Category::MemorySafety,
+        Category::Cryptography,
+        Category::WebSecurity,
+        Category::InputValidation,
+        Category::AuthenticationAuthorization,
+        Category::Concurrency,
+        Category::DataExposure,
+        Category::CodeInjection,
+        Category::Generic,
+        Category::TechnicalDebt,
+        Category::SecurityRegression,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Category::MemorySafety => "MemorySafety",
+            Category::Cryptography => "Cryptography",
+            Category::WebSecurity => "WebSecurity",
+            Category::InputValidation => "InputValidation",
+            Category::AuthenticationAuthorization => "AuthenticationAuthorization",
+            Category::Concurrency => "Concurrency",
+            Category::DataExposure => "DataExposure",
+            Category::CodeInjection => "CodeInjection",
+            Category::Generic => "Generic",
+            Category::TechnicalDebt => "TechnicalDebt",
+            Category::SecurityRegression => "SecurityRegression",
+            Category::Custom(name) => name,
+        }
+    }
+
+    /// Whether this category is listed in `enabled` (compared case-insensitively)
+    pub fn is_enabled(&self, enabled: &[String]) -> bool {
+        enabled.iter().any(|e| e.eq_ignore_ascii_case(self.name()))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<String> for Category {
+    /// Built-in names map to their variant (case-insensitively); anything else is custom
+    fn from(name: String) -> Self {
+        Category::BUILTIN
+            .into_iter()
+            .find(|c| c.name().eq_ignore_ascii_case(&name))
+            .unwrap_or(Category::Custom(name))
+    }
+}
+
+impl From<Category> for String {
+    fn from(category: Category) -> Self {
+        category.name().to_string()
+    }
+}
+
+/// How likely a match is to indicate a real security change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum Confidence {
+    High,
+    Medium,
+    Low,
+}
+
+impl Confidence {
+    /// Confidence for a match given where it was found and whether the pattern maps to a CWE.
+    /// Broad catch-all patterns are always low; precise patterns rank higher in the subject line.
+    pub fn for_match(in_subject: bool, precise_pattern: bool) -> Self {
+        match (precise_pattern, in_subject) {
+            (false, _) => Confidence::Low,
+            (true, true) => Confidence::High,
+            (true, false) => Confidence::Medium,
+        }
+    }
+
+    fn level(&self) -> u8 {
+        match self {
+            Confidence::High => 2,
+            Confidence::Medium => 1,
+            Confidence::Low => 0,
+        }
+    }
+
+    pub fn is_at_least(&self, other: Confidence) -> bool {
+        self.level() >= other.level()
+    }
+
+    /// Share of a finding's score that counts toward the confidence-weighted overall risk
+    pub fn weight(&self) -> f64 {
+        match self {
+            Confidence::High => 1.0,
+            Confidence::Medium => 0.6,
+            Confidence::Low => 0.25,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        }
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Confidence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "high" => Ok(Confidence::High),
+            "medium" => Ok(Confidence::Medium),
+            "low" => Ok(Confidence::Low),
+            _ => Err(anyhow::anyhow!(
+                "Unknown confidence '{}' (expected high, medium or low)",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PatternMatch {
     pub pattern_name: String,
     pub matched_text: String,
@@ -47,18 +274,132 @@ pub struct PatternMatch {
     pub line_number: Option<usize>,
     pub context: String,
     pub cve_references: Vec<String>,
+    pub cwe: Option<String>,
+    pub remediation: Option<String>,
+    pub confidence: Confidence,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VulnerabilityFinding {
     pub commit_id: String,
     pub commit_message: String,
     pub author: String,
     pub date: chrono::DateTime<chrono::Utc>,
+    /// Whole days between `date` and the report date, see `age_in_days`
+    #[serde(default)]
+    pub age_days: i64,
     pub files_changed: Vec<String>,
     pub patterns_matched: Vec<PatternMatch>,
     pub risk_score: f64,
     pub cve_references: Vec<String>,
+    /// Trailers of the commit, such as reviewers and `Fixes:` references
+    #[serde(default)]
+    pub trailers: HashMap<String, Vec<String>>,
+    /// Near-duplicate findings folded into this one by `--group-duplicates`, oldest first
+    #[serde(default)]
+    pub related_commits: Vec<RelatedCommit>,
+}
+
+/// Commit of a finding grouped into another finding for the same underlying issue
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedCommit {
+    pub commit_id: String,
+    pub subject: String,
+    pub author: String,
+    pub date: chrono::DateTime<chrono::Utc>,
+}
+
+/// Whole days from `date` until `reference_date` (the report date, see
+/// `OutputConfig::reference_date`); commits dated after it count as that day
+pub fn age_in_days(
+    date: chrono::DateTime<chrono::Utc>,
+    reference_date: chrono::DateTime<chrono::Utc>,
+) -> i64 {
+    (reference_date - date).num_days().max(0)
+}
+
+/// Technical-debt markers in one file of the working tree
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MarkerDensity {
+    pub path: String,
+    pub lines: usize,
+    /// Marker count per pattern name
+    pub markers: BTreeMap<String, usize>,
+    pub total: usize,
+    /// Markers per 1000 lines
+    pub density: f64,
+}
+
+/// How often one loaded pattern fired during a scan, for tuning rule packs
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PatternHits {
+    pub name: String,
+    pub category: Category,
+    /// Matches across all findings; a commit can match once per scanned text
+    pub matches: usize,
+    /// Findings (commits) with at least one match
+    pub commits: usize,
+}
+
+/// A single pattern match flattened together with its commit context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlatMatch {
+    pub commit_id: String,
+    pub commit_subject: String,
+    pub author: String,
+    pub date: chrono::DateTime<chrono::Utc>,
+    pub finding_risk_score: f64,
+    pub pattern_name: String,
+    pub matched_text: String,
+    pub severity: Severity,
+    pub category: Category,
+    pub cwe: Option<String>,
+    pub confidence: Confidence,
+    pub file_path: String,
+    pub line_number: Option<usize>,
+}
+
+impl VulnerabilityFinding {
+    /// Highest confidence among the matches of this finding
+    pub fn confidence(&self) -> Confidence {
+        self.patterns_matched
+            .iter()
+            .map(|m| m.confidence)
+            .fold(
+                Confidence::Low,
+                |best, c| {
+                    if c.is_at_least(best) {
+                        c
+                    } else {
+                        best
+                    }
+                },
+            )
+    }
+
+    /// One record per pattern match, carrying the commit context
+    pub fn flat_matches(&self) -> Vec<FlatMatch> {
+        let commit_subject = self.commit_message.lines().next().unwrap_or("").to_string();
+
+        self.patterns_matched
+            .iter()
+            .map(|m| FlatMatch {
+                commit_id: self.commit_id.clone(),
+                commit_subject: commit_subject.clone(),
+                author: self.author.clone(),
+                date: self.date,
+                finding_risk_score: self.risk_score,
+                pattern_name: m.pattern_name.clone(),
+                matched_text: m.matched_text.clone(),
+                severity: m.severity.clone(),
+                category: m.category.clone(),
+                cwe: m.cwe.clone(),
+                confidence: m.confidence,
+                file_path: m.file_path.clone(),
+                line_number: m.line_number,
+            })
+            .collect()
+    }
 }
 
 pub fn default_patterns() -> Vec<VulnerabilityPattern> {
@@ -72,6 +413,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential use-after-free vulnerability".to_string(),
             cwe: Some("CWE-416".to_string()),
             examples: vec!["Fix use after free".to_string(), "UAF vulnerability".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Buffer Overflow".to_string(),
@@ -81,6 +425,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential buffer overflow vulnerability".to_string(),
             cwe: Some("CWE-120".to_string()),
             examples: vec!["Fix buffer overflow".to_string(), "Stack overflow protection".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Double Free".to_string(),
@@ -90,6 +437,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential double-free vulnerability".to_string(),
             cwe: Some("CWE-415".to_string()),
             examples: vec!["Fix double free".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Race Condition".to_string(),
@@ -99,6 +449,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential race condition vulnerability".to_string(),
             cwe: Some("CWE-362".to_string()),
             examples: vec!["Fix race condition".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Memory Leak".to_string(),
@@ -108,6 +461,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential memory leak".to_string(),
             cwe: Some("CWE-401".to_string()),
             examples: vec!["Fix memory leak".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Null Pointer Dereference".to_string(),
@@ -117,6 +473,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential null pointer dereference".to_string(),
             cwe: Some("CWE-476".to_string()),
             examples: vec!["Fix null pointer".to_string(), "Segmentation fault".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
 
         // Security Patterns
@@ -128,6 +487,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential code injection vulnerability".to_string(),
             cwe: Some("CWE-94".to_string()),
             examples: vec!["Fix code injection".to_string(), "SQL injection".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
 
         // Type confusion
@@ -139,6 +501,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Access of Resource Using Incompatible Type ('Type Confusion')".to_string(),
             cwe: Some("CWE-843".to_string()),
             examples: vec!["Fix code injection".to_string(), "Type confusion".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Authentication Bypass".to_string(),
@@ -148,6 +513,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential authentication bypass".to_string(),
             cwe: Some("CWE-287".to_string()),
             examples: vec!["Fix auth bypass".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Cross-Site Scripting".to_string(),
@@ -157,6 +525,9 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Potential XSS vulnerability".to_string(),
             cwe: Some("CWE-79".to_string()),
             examples: vec!["Fix XSS".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
 
         // Crypto Patterns
@@ -168,17 +539,29 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "Weak cryptographic implementation".to_string(),
             cwe: Some("CWE-327".to_string()),
             examples: vec!["Replace weak crypto".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
 
         // Generic Security
+        // Parts may be split by a dash, odd spacing or one line break each, as in wrapped
+        // messages ("CVE-2021-\n1234", "CVE 2021 1234"); the engine emits the canonical id
         VulnerabilityPattern {
             name: "CVE Reference".to_string(),
-            pattern: r"(?i)\bcve[-\s]?(\d{4}[-\s]?\d{4,})\b".to_string(),
+            pattern: concat!(
+                r"(?i)\bcve[ \t]*(?:\r?\n[ \t]*)?[-_:]?[ \t]*(?:\r?\n[ \t]*)?",
+                r"(\d{4}[ \t]*(?:\r?\n[ \t]*)?[-_]?[ \t]*(?:\r?\n[ \t]*)?\d{4,})\b"
+            )
+            .to_string(),
             severity: Severity::Info,
             category: Category::Generic,
             description: "CVE reference found".to_string(),
             cwe: None,
-            examples: vec!["CVE-2021-1234".to_string()],
+            examples: vec!["CVE-2021-1234".to_string(), "CVE 2021\n1234".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
         VulnerabilityPattern {
             name: "Security Fix".to_string(),
@@ -188,6 +571,191 @@ pub fn default_patterns() -> Vec<VulnerabilityPattern> {
             description: "General security-related change".to_string(),
             cwe: None,
             examples: vec!["Security fix".to_string()],
+            flags: None,
+            standalone: true,
+            min_match_length: 0,
+        },
+    ]
+}
+
+/// Changes that disable or weaken a security setting. They are matched against added diff
+/// lines only: adding `verify=False` is a regression, removing it a fix.
+pub fn security_regression_patterns() -> Vec<VulnerabilityPattern> {
+    vec![
+        VulnerabilityPattern {
+            name: "TLS Verification Disabled".to_string(),
+            pattern: r#"(?i)\b(verify(_ssl|_peer|_host|_certs?)?|ssl_?verify|check_hostname|reject_?unauthorized|strict_?ssl|tls_?verify)["']?\s*(=|:|=>)\s*["']?(false|0|no|off)\b"#.to_string(),
+            severity: Severity::High,
+            category: Category::SecurityRegression,
+            description: "Certificate or hostname verification switched off".to_string(),
+            cwe: Some("CWE-295".to_string()),
+            examples: vec![r#"requests.get(url, verify=False)"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Certificate Validation Bypass".to_string(),
+            pattern: r#"\b(InsecureSkipVerify\s*:\s*true|danger_accept_invalid_(certs|hostnames)\s*\(\s*true|ssl\.CERT_NONE|NODE_TLS_REJECT_UNAUTHORIZED\s*=\s*["']?0|CURLOPT_SSL_VERIFY(PEER|HOST)\s*,\s*0)\b"#.to_string(),
+            severity: Severity::High,
+            category: Category::SecurityRegression,
+            description: "TLS client accepting any certificate".to_string(),
+            cwe: Some("CWE-295".to_string()),
+            examples: vec![r#".danger_accept_invalid_certs(true)"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Insecure Transport Flag".to_string(),
+            pattern: r#"(--insecure\b|--no-check-certificate\b|\bGIT_SSL_NO_VERIFY\b|\bPYTHONHTTPSVERIFY\s*=\s*["']?0)"#.to_string(),
+            severity: Severity::Medium,
+            category: Category::SecurityRegression,
+            description: "Command or environment skipping TLS verification".to_string(),
+            cwe: Some("CWE-295".to_string()),
+            examples: vec![r#"curl --insecure https://example.com/install.sh"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Dynamic Code Evaluation".to_string(),
+            pattern: r#"(\beval\s*\(|\bnew\s+Function\s*\()"#.to_string(),
+            severity: Severity::High,
+            category: Category::SecurityRegression,
+            description: "String evaluated as code".to_string(),
+            cwe: Some("CWE-94".to_string()),
+            examples: vec![r#"eval(request.args['expr'])"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Passwordless Sudo".to_string(),
+            pattern: r#"\bNOPASSWD\s*:"#.to_string(),
+            severity: Severity::High,
+            category: Category::SecurityRegression,
+            description: "sudo rule granting root without a password".to_string(),
+            cwe: Some("CWE-250".to_string()),
+            examples: vec![r#"deploy ALL=(ALL) NOPASSWD: ALL"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Unsafe Code Allowed".to_string(),
+            pattern: r#"(#!?\[\s*allow\s*\(\s*unsafe_code\s*\)\s*\]|\bunsafe_code\s*=\s*["'](allow|warn)["'])"#.to_string(),
+            severity: Severity::Medium,
+            category: Category::SecurityRegression,
+            description: "Lint against unsafe Rust code relaxed".to_string(),
+            cwe: None,
+            examples: vec![r#"#![allow(unsafe_code)]"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "CSRF Protection Disabled".to_string(),
+            pattern: r#"(?i)(@csrf_exempt\b|\bcsrf\s*\(\s*\)\s*\.\s*disable\s*\(|\bWTF_CSRF_ENABLED\s*=\s*False\b|\bcsrf(_?protection)?["']?\s*[:=]\s*false\b)"#.to_string(),
+            severity: Severity::High,
+            category: Category::SecurityRegression,
+            description: "Cross-site request forgery protection turned off".to_string(),
+            cwe: Some("CWE-352".to_string()),
+            examples: vec![r#"http.csrf().disable()"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "World-Writable Permissions".to_string(),
+            pattern: r#"(\bchmod\s+(-R\s+)?(0?777|a\+rwx|o\+w)\b|\bchmod\s*\([^)]*\b0o?777\b)"#.to_string(),
+            severity: Severity::Medium,
+            category: Category::SecurityRegression,
+            description: "Files made writable by every user".to_string(),
+            cwe: Some("CWE-732".to_string()),
+            examples: vec![r#"chmod -R 777 /var/www"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Wildcard CORS Origin".to_string(),
+            pattern: r#"Access-Control-Allow-Origin["']?\s*[:,]\s*["']\*["']"#.to_string(),
+            severity: Severity::Medium,
+            category: Category::SecurityRegression,
+            description: "Cross-origin requests allowed from any site".to_string(),
+            cwe: Some("CWE-942".to_string()),
+            examples: vec![r#"res.setHeader("Access-Control-Allow-Origin", "*")"#.to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+    ]
+}
+
+/// Maintenance markers counted in file contents by the `debt` pattern set. They are kept out
+/// of `default_patterns` so cleanup work never mixes with security findings.
+pub fn debt_patterns() -> Vec<VulnerabilityPattern> {
+    vec![
+        VulnerabilityPattern {
+            name: "TODO Marker".to_string(),
+            pattern: r"\bTODO\b".to_string(),
+            severity: Severity::Info,
+            category: Category::TechnicalDebt,
+            description: "Unfinished work left in the code".to_string(),
+            cwe: None,
+            examples: vec!["// TODO: handle retries".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "FIXME Marker".to_string(),
+            pattern: r"\bFIXME\b".to_string(),
+            severity: Severity::Low,
+            category: Category::TechnicalDebt,
+            description: "Known defect left in the code".to_string(),
+            cwe: None,
+            examples: vec!["# FIXME breaks on empty input".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "XXX Marker".to_string(),
+            pattern: r"\bXXX\b".to_string(),
+            severity: Severity::Low,
+            category: Category::TechnicalDebt,
+            description: "Code flagged as questionable".to_string(),
+            cwe: None,
+            examples: vec!["/* XXX: not thread safe */".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "HACK Marker".to_string(),
+            pattern: r"\bHACK\b".to_string(),
+            severity: Severity::Low,
+            category: Category::TechnicalDebt,
+            description: "Workaround that needs a proper fix".to_string(),
+            cwe: None,
+            examples: vec!["// HACK around upstream bug".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
+        },
+        VulnerabilityPattern {
+            name: "Deprecated Marker".to_string(),
+            pattern: r"@deprecated\b".to_string(),
+            severity: Severity::Info,
+            category: Category::TechnicalDebt,
+            description: "Deprecated API still in the code".to_string(),
+            cwe: None,
+            examples: vec!["@deprecated use parse_v2".to_string()],
+            flags: None,
+            standalone: false,
+            min_match_length: 0,
         },
     ]
 }
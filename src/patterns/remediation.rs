@@ -0,0 +1,46 @@
+use super::VulnerabilityFinding;
+use crate::error::{CommitRaiderError, Result};
+use rust_embed::RustEmbed;
+use std::collections::HashMap;
+
+#[derive(RustEmbed)]
+#[folder = "src/patterns/data/"]
+#[include = "*.json"]
+struct RemediationData;
+
+/// Maps CWE identifiers to short remediation guidance
+pub struct RemediationGuide {
+    guidance: HashMap<String, String>,
+}
+
+impl RemediationGuide {
+    /// Load the built-in table and apply overrides from the configuration
+    pub fn load(overrides: &HashMap<String, String>) -> Result<Self> {
+        let data = RemediationData::get("cwe_remediation.json").ok_or_else(|| {
+            CommitRaiderError::Config("Remediation table cwe_remediation.json not found".into())
+        })?;
+        let mut guidance: HashMap<String, String> = serde_json::from_slice(&data.data)
+            .map_err(|e| CommitRaiderError::Config(format!("Invalid remediation table: {}", e)))?;
+
+        for (cwe, text) in overrides {
+            guidance.insert(cwe.to_uppercase(), text.clone());
+        }
+
+        Ok(Self { guidance })
+    }
+
+    pub fn lookup(&self, cwe: &str) -> Option<&str> {
+        self.guidance.get(&cwe.to_uppercase()).map(|s| s.as_str())
+    }
+
+    /// Attach remediation text to every pattern match that carries a CWE id
+    pub fn annotate(&self, findings: &mut [VulnerabilityFinding]) {
+        for finding in findings {
+            for pattern_match in &mut finding.patterns_matched {
+                if let Some(cwe) = &pattern_match.cwe {
+                    pattern_match.remediation = self.lookup(cwe).map(|s| s.to_string());
+                }
+            }
+        }
+    }
+}
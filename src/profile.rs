@@ -0,0 +1,91 @@
+use colored::Colorize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<Vec<PhaseProfile>> = Mutex::new(Vec::new());
+
+/// Wall time and work done in one phase, summed over every repository of the run
+struct PhaseProfile {
+    name: &'static str,
+    unit: &'static str,
+    wall: Duration,
+    items: usize,
+    /// Peak resident set size of the process when the phase last finished, in KiB
+    peak_rss_kib: Option<u64>,
+}
+
+/// Start recording phases (--profile)
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Add a run of a phase that took `wall` and processed `items` `unit`s. Phases are listed in
+/// the order they first finished.
+pub fn record(name: &'static str, unit: &'static str, wall: Duration, items: usize) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let peak_rss_kib = peak_rss_kib();
+    let mut phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    match phases.iter_mut().find(|phase| phase.name == name) {
+        Some(phase) => {
+            phase.wall += wall;
+            phase.items += items;
+            phase.peak_rss_kib = phase.peak_rss_kib.max(peak_rss_kib);
+        }
+        None => phases.push(PhaseProfile {
+            name,
+            unit,
+            wall,
+            items,
+            peak_rss_kib,
+        }),
+    }
+}
+
+/// Peak resident set size of this process so far, in KiB. Only Linux exposes it without
+/// platform bindings (`VmHWM` in /proc), elsewhere it is not measured.
+fn peak_rss_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Print the recorded phases as a table; nothing unless profiling was enabled
+pub fn print_summary() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    println!("{}", "Profile:".bold());
+    println!(
+        "  {:<18} {:>10} {:>20} {:>12}",
+        "Phase".bold(),
+        "Wall".bold(),
+        "Processed".bold(),
+        "Peak RSS".bold()
+    );
+    for phase in phases.iter() {
+        let peak = match phase.peak_rss_kib {
+            Some(kib) => format!("{:.1} MiB", kib as f64 / 1024.0),
+            None => "n/a".to_string(),
+        };
+        println!(
+            "  {:<18} {:>9.2}s {:>20} {:>12}",
+            phase.name,
+            phase.wall.as_secs_f64(),
+            format!("{} {}", phase.items, phase.unit),
+            peak
+        );
+    }
+    if phases.iter().all(|phase| phase.peak_rss_kib.is_none()) {
+        println!("  {}", "Peak memory is only measured on Linux".dimmed());
+    }
+}